@@ -0,0 +1,128 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use messagepack_core::{
+    Decode,
+    io::SliceReader,
+    value::{PreservedKind, PreservedValue},
+};
+use messagepack_serde::{de::StreamDeserializer, value::Raw};
+
+/// Read the whole input, from standard input when `path` is `None` or `-`.
+pub(crate) fn read(path: Option<&Path>) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    match path {
+        Some(path) if path != Path::new("-") => {
+            File::open(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?
+                .read_to_end(&mut buf)?;
+        }
+        _ => {
+            io::stdin().lock().read_to_end(&mut buf)?;
+        }
+    }
+    Ok(buf)
+}
+
+/// A value that could not be processed, with the offset it starts at.
+#[derive(Debug)]
+pub(crate) struct Failure {
+    offset: usize,
+    message: String,
+}
+
+impl Failure {
+    pub(crate) fn new(offset: usize, message: impl fmt::Display) -> Self {
+        Self {
+            offset,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value at offset {} (0x{:x}): {}",
+            self.offset, self.offset, self.message
+        )
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Split concatenated values into their encoded bytes and start offsets.
+pub(crate) fn values(buf: &[u8]) -> impl Iterator<Item = Result<(usize, &[u8]), Failure>> {
+    let mut stream = StreamDeserializer::<_, Raw<'_>>::new(SliceReader::new(buf));
+    std::iter::from_fn(move || {
+        let offset = stream.byte_offset();
+        let raw = stream.next()?;
+        Some(
+            raw.map(|raw| (offset, raw.as_bytes()))
+                .map_err(|e| Failure::new(offset, e)),
+        )
+    })
+}
+
+/// Decode one value keeping the format of every node.
+///
+/// Values nested deeper than [`MAX_DEPTH`](messagepack_core::value::MAX_DEPTH)
+/// cannot be decoded, whatever the limits of `validate`.
+pub(crate) fn decode(bytes: &[u8]) -> Result<PreservedValue, String> {
+    PreservedValue::decode(&mut SliceReader::new(bytes)).map_err(|e| e.to_string())
+}
+
+/// Call `f` with every node and its nesting depth, parents first.
+///
+/// Depth counts the enclosing arrays and maps, so top level nodes are at `0`.
+pub(crate) fn visit(v: &PreservedValue, depth: usize, f: &mut impl FnMut(&PreservedValue, usize)) {
+    f(v, depth);
+    match v.kind() {
+        PreservedKind::Array(items) => {
+            for item in items {
+                visit(item, depth + 1, f);
+            }
+        }
+        PreservedKind::Map(entries) => {
+            for (k, v) in entries {
+                visit(k, depth + 1, f);
+                visit(v, depth + 1, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_concatenated_values() {
+        let buf: &[u8] = &[0x01, 0x92, 0xc0, 0xc3, 0xa1, 0x61];
+        let parts: Vec<_> = values(buf).map(Result::unwrap).collect();
+        assert_eq!(
+            parts,
+            vec![
+                (0, &[0x01][..]),
+                (1, &[0x92, 0xc0, 0xc3][..]),
+                (4, &[0xa1, 0x61][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_value_reports_its_offset() {
+        let buf: &[u8] = &[0x01, 0x92, 0xc0];
+        let mut parts = values(buf);
+        assert!(parts.next().unwrap().is_ok());
+        let err = parts.next().unwrap().unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert!(parts.next().is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! Command-line tool for inspecting and converting MessagePack.
+
+mod input;
+mod stats;
+mod validate;
+
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use messagepack_core::{
+    debug,
+    io::SliceReader,
+    notation::{self, Style},
+};
+use messagepack_serde::{
+    Value,
+    transcode::{self, BinPolicy, FromJsonConfig, StringConversion, ToJsonConfig, ToJsonError},
+};
+use serde::de::IgnoredAny;
+
+use input::{Failure, values};
+
+#[derive(Debug, Parser)]
+#[command(name = "messagepack", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print every value in the text notation
+    Dump {
+        /// Print each value on a single line
+        #[arg(long, conflicts_with = "disassemble")]
+        compact: bool,
+        /// Print an annotated hex dump instead
+        #[arg(long)]
+        disassemble: bool,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Convert every value to JSON, one document per line
+    ToJson {
+        /// Indent the JSON output
+        #[arg(long)]
+        pretty: bool,
+        /// How bin payloads are written
+        #[arg(long, value_enum, default_value_t = Bin::Base64)]
+        bin: Bin,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Convert a stream of JSON documents to concatenated MessagePack
+    FromJson {
+        /// Convert base64 strings at this JSON Pointer to bin, `*` matches any segment
+        #[arg(long = "bin", value_name = "PATH")]
+        bin_paths: Vec<String>,
+        /// Convert RFC 3339 strings at this JSON Pointer to timestamps
+        #[arg(long = "timestamp", value_name = "PATH")]
+        timestamp_paths: Vec<String>,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Check that the input is well formed and within limits
+    Validate {
+        #[command(flatten)]
+        limits: validate::Limits,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Extract a sub-value by JSON Pointer, e.g. `/users/0/name`
+    Get {
+        /// JSON Pointer to look up in every value
+        path: String,
+        /// Print the result as JSON
+        #[arg(long, conflicts_with = "raw")]
+        json: bool,
+        /// Write the result as MessagePack
+        #[arg(long)]
+        raw: bool,
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Print a histogram of formats, nesting depth and size statistics
+    Stats {
+        #[command(flatten)]
+        input: Input,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Bin {
+    Base64,
+    Hex,
+    Array,
+}
+
+impl From<Bin> for BinPolicy {
+    fn from(bin: Bin) -> Self {
+        match bin {
+            Bin::Base64 => BinPolicy::Base64,
+            Bin::Hex => BinPolicy::Hex,
+            Bin::Array => BinPolicy::Array,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct Input {
+    /// File to read, standard input when omitted or `-`
+    file: Option<PathBuf>,
+}
+
+impl Input {
+    fn read(&self) -> io::Result<Vec<u8>> {
+        input::read(self.file.as_deref())
+    }
+}
+
+type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let result = run(cli.command, &mut out).and_then(|code| {
+        out.flush()?;
+        Ok(code)
+    });
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            let _ = out.flush();
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, out: &mut impl Write) -> Result<ExitCode> {
+    match command {
+        Command::Dump {
+            compact,
+            disassemble,
+            input,
+        } => {
+            let buf = input.read()?;
+            if disassemble {
+                write!(out, "{}", debug::disassemble(&buf))?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let style = if compact {
+                Style::Compact
+            } else {
+                Style::Pretty
+            };
+            for value in values(&buf) {
+                let (offset, bytes) = value?;
+                let mut text = String::new();
+                notation::write(&mut SliceReader::new(bytes), &mut text, style)
+                    .map_err(|e| Failure::new(offset, e))?;
+                writeln!(out, "{text}")?;
+            }
+        }
+        Command::ToJson { pretty, bin, input } => {
+            let buf = input.read()?;
+            let config = ToJsonConfig::new().with_pretty(pretty).with_bin(bin.into());
+            for value in values(&buf) {
+                let (offset, bytes) = value?;
+                transcode::to_json(&mut SliceReader::new(bytes), &mut *out, config).map_err(
+                    |e| match e {
+                        ToJsonError::Output(e) => Box::new(e) as Box<dyn std::error::Error>,
+                        e => Box::new(Failure::new(offset, e)),
+                    },
+                )?;
+                writeln!(out)?;
+            }
+        }
+        Command::FromJson {
+            bin_paths,
+            timestamp_paths,
+            input,
+        } => {
+            let config = || {
+                let config = bin_paths
+                    .iter()
+                    .try_fold(FromJsonConfig::new(), |c, path| {
+                        c.with_rule(path, StringConversion::Base64)
+                    })?;
+                timestamp_paths.iter().try_fold(config, |c, path| {
+                    c.with_rule(path, StringConversion::Rfc3339)
+                })
+            };
+            // Reject bad rule paths before reading any input.
+            config()?;
+            let buf = input.read()?;
+            // Find where each document ends, then convert them one by one.
+            let mut documents =
+                serde_json::Deserializer::from_slice(&buf).into_iter::<IgnoredAny>();
+            let mut start = 0;
+            while let Some(document) = documents.next() {
+                document?;
+                let end = documents.byte_offset();
+                transcode::from_json_reader(&buf[start..end], &mut *out, config()?)?;
+                start = end;
+            }
+        }
+        Command::Validate { limits, input } => {
+            let buf = input.read()?;
+            let mut count = 0;
+            for value in values(&buf) {
+                let (offset, bytes) = value?;
+                limits
+                    .check(bytes)
+                    .map_err(|message| Failure::new(offset, message))?;
+                count += 1;
+            }
+            writeln!(out, "ok: {count} values, {} bytes", buf.len())?;
+        }
+        Command::Get {
+            path,
+            json,
+            raw,
+            input,
+        } => {
+            let buf = input.read()?;
+            let mut found = false;
+            for value in values(&buf) {
+                let (offset, bytes) = value?;
+                let v: Value =
+                    messagepack_serde::from_slice(bytes).map_err(|e| Failure::new(offset, e))?;
+                let Some(v) = v.pointer(&path) else {
+                    continue;
+                };
+                found = true;
+                if raw {
+                    out.write_all(&messagepack_serde::to_vec(v)?)?;
+                } else if json {
+                    serde_json::to_writer(&mut *out, v)?;
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, "{v:#}")?;
+                }
+            }
+            if !found {
+                eprintln!("error: `{path}` not found");
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Stats { input } => {
+            let buf = input.read()?;
+            let mut stats = stats::Stats::default();
+            for value in values(&buf) {
+                let (offset, bytes) = value?;
+                stats
+                    .add(bytes)
+                    .map_err(|message| Failure::new(offset, message))?;
+            }
+            write!(out, "{stats}")?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
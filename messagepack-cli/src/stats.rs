@@ -0,0 +1,140 @@
+use std::{collections::BTreeMap, fmt};
+
+use messagepack_core::value::PreservedKind;
+
+use crate::input::{decode, visit};
+
+/// Running statistics over all inspected values.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    values: usize,
+    bytes: usize,
+    nodes: usize,
+    max_depth: usize,
+    formats: BTreeMap<&'static str, usize>,
+    strings: Sizes,
+    binaries: Sizes,
+    extensions: Sizes,
+    arrays: Sizes,
+    maps: Sizes,
+}
+
+/// Count, extremes and sum of a size.
+#[derive(Debug, Default)]
+struct Sizes {
+    count: usize,
+    min: usize,
+    max: usize,
+    total: usize,
+}
+
+impl Sizes {
+    fn add(&mut self, n: usize) {
+        self.min = if self.count == 0 { n } else { self.min.min(n) };
+        self.max = self.max.max(n);
+        self.total += n;
+        self.count += 1;
+    }
+}
+
+impl Stats {
+    /// Account one encoded value.
+    pub(crate) fn add(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let value = decode(bytes)?;
+        self.values += 1;
+        self.bytes += bytes.len();
+        visit(&value, 0, &mut |node, depth| {
+            self.nodes += 1;
+            if let Some(format) = node.format() {
+                *self.formats.entry(format.name()).or_default() += 1;
+            }
+            match node.kind() {
+                PreservedKind::String(s) => self.strings.add(s.len()),
+                PreservedKind::Bin(b) => self.binaries.add(b.len()),
+                PreservedKind::Extension(ext) => self.extensions.add(ext.data.len()),
+                PreservedKind::Array(items) => {
+                    self.arrays.add(items.len());
+                    self.max_depth = self.max_depth.max(depth + 1);
+                }
+                PreservedKind::Map(entries) => {
+                    self.maps.add(entries.len());
+                    self.max_depth = self.max_depth.max(depth + 1);
+                }
+                _ => {}
+            }
+        });
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "values     {}", self.values)?;
+        writeln!(f, "bytes      {}", self.bytes)?;
+        writeln!(f, "nodes      {}", self.nodes)?;
+        writeln!(f, "max depth  {}", self.max_depth)?;
+
+        writeln!(f)?;
+        writeln!(f, "{:<18}{:>10}", "format", "count")?;
+        let mut formats: Vec<_> = self.formats.iter().collect();
+        formats.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (name, count) in formats {
+            writeln!(f, "{name:<18}{count:>10}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<18}{:>10}{:>10}{:>10}{:>12}",
+            "size", "count", "min", "max", "total"
+        )?;
+        for (name, sizes) in [
+            ("str bytes", &self.strings),
+            ("bin bytes", &self.binaries),
+            ("ext bytes", &self.extensions),
+            ("array items", &self.arrays),
+            ("map entries", &self.maps),
+        ] {
+            writeln!(
+                f,
+                "{name:<18}{:>10}{:>10}{:>10}{:>12}",
+                sizes.count, sizes.min, sizes.max, sizes.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_formats_depth_and_sizes() {
+        let mut stats = Stats::default();
+        // {"ab": [1, "c"]}
+        stats
+            .add(&[0x81, 0xa2, 0x61, 0x62, 0x92, 0x01, 0xa1, 0x63])
+            .unwrap();
+        // "defg" as str8
+        stats.add(&[0xd9, 0x04, 0x64, 0x65, 0x66, 0x67]).unwrap();
+
+        assert_eq!(stats.values, 2);
+        assert_eq!(stats.bytes, 14);
+        assert_eq!(stats.nodes, 6);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.formats["fixstr"], 2);
+        assert_eq!(stats.formats["str8"], 1);
+        assert_eq!(stats.formats["positive fixint"], 1);
+        assert_eq!(
+            (
+                stats.strings.count,
+                stats.strings.min,
+                stats.strings.max,
+                stats.strings.total
+            ),
+            (3, 1, 4, 7)
+        );
+        assert_eq!((stats.arrays.count, stats.arrays.max), (1, 2));
+    }
+}
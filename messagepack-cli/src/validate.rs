@@ -0,0 +1,106 @@
+use clap::Args;
+use messagepack_core::value::{MAX_DEPTH, PreservedKind};
+
+use crate::input::{decode, visit};
+
+/// Limits applied to every top level value.
+#[derive(Debug, Default, Args)]
+pub(crate) struct Limits {
+    /// Maximum nesting depth of arrays and maps
+    #[arg(long, value_name = "N", value_parser = parse_depth)]
+    max_depth: Option<usize>,
+    /// Maximum number of elements in an array or entries in a map
+    #[arg(long, value_name = "N")]
+    max_items: Option<usize>,
+    /// Maximum length in bytes of a str, bin or ext payload
+    #[arg(long, value_name = "BYTES")]
+    max_bytes: Option<usize>,
+    /// Maximum encoded size of a single top level value
+    #[arg(long, value_name = "BYTES")]
+    max_size: Option<usize>,
+}
+
+/// Parse `--max-depth`, which cannot exceed what the decoder accepts.
+fn parse_depth(s: &str) -> Result<usize, String> {
+    let depth = s
+        .parse()
+        .map_err(|e: std::num::ParseIntError| e.to_string())?;
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "values nested deeper than {MAX_DEPTH} cannot be decoded"
+        ));
+    }
+    Ok(depth)
+}
+
+impl Limits {
+    /// Check one encoded value, returning the first violation.
+    pub(crate) fn check(&self, bytes: &[u8]) -> Result<(), String> {
+        if let Some(max) = self.max_size
+            && bytes.len() > max
+        {
+            return Err(format!("encoded size {} exceeds {max}", bytes.len()));
+        }
+
+        let value = decode(bytes)?;
+        let mut violation = None;
+        visit(&value, 0, &mut |node, depth| {
+            if violation.is_some() {
+                return;
+            }
+            let (items, len) = match node.kind() {
+                PreservedKind::Array(items) => (Some(items.len()), None),
+                PreservedKind::Map(entries) => (Some(entries.len()), None),
+                PreservedKind::String(s) => (None, Some(s.len())),
+                PreservedKind::Bin(b) => (None, Some(b.len())),
+                PreservedKind::Extension(ext) => (None, Some(ext.data.len())),
+                _ => (None, None),
+            };
+            if let Some(max) = self.max_depth
+                && items.is_some()
+                && depth >= max
+            {
+                violation = Some(format!("nesting depth exceeds {max}"));
+            } else if let (Some(max), Some(n)) = (self.max_items, items)
+                && n > max
+            {
+                violation = Some(format!("{n} items exceed {max}"));
+            } else if let (Some(max), Some(n)) = (self.max_bytes, len)
+                && n > max
+            {
+                violation = Some(format!("{n} byte payload exceeds {max}"));
+            }
+        });
+        violation.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Limits::default(), &[0x91, 0x91, 0xc0], true)]
+    #[case(Limits { max_depth: Some(2), ..Default::default() }, &[0x91, 0x91, 0xc0], true)]
+    #[case(Limits { max_depth: Some(1), ..Default::default() }, &[0x91, 0x91, 0xc0], false)]
+    #[case(Limits { max_items: Some(1), ..Default::default() }, &[0x92, 0xc0, 0xc0], false)]
+    #[case(Limits { max_items: Some(1), ..Default::default() }, &[0x81, 0xc0, 0xc0], true)]
+    #[case(Limits { max_bytes: Some(1), ..Default::default() }, &[0xa2, 0x61, 0x62], false)]
+    #[case(Limits { max_bytes: Some(1), ..Default::default() }, &[0xd5, 0x01, 0x00, 0x00], false)]
+    #[case(Limits { max_size: Some(2), ..Default::default() }, &[0xa2, 0x61, 0x62], false)]
+    #[case(Limits::default(), &[0xa1, 0xff], false)]
+    fn check(#[case] limits: Limits, #[case] bytes: &[u8], #[case] ok: bool) {
+        assert_eq!(limits.check(bytes).is_ok(), ok);
+    }
+
+    #[test]
+    fn depth_is_bounded_by_the_decoder() {
+        assert_eq!(parse_depth(&MAX_DEPTH.to_string()), Ok(MAX_DEPTH));
+        assert!(parse_depth(&(MAX_DEPTH + 1).to_string()).is_err());
+
+        let mut too_deep = vec![0x91; MAX_DEPTH + 1];
+        too_deep.push(0xc0);
+        assert!(Limits::default().check(&too_deep).is_err());
+    }
+}
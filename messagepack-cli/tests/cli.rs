@@ -0,0 +1,114 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_messagepack"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    assert!(output.status.success(), "{output:?}");
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+// {"a": [1, "x"]} followed by `true`
+const STREAM: &[u8] = &[0x81, 0xa1, 0x61, 0x92, 0x01, 0xa1, 0x78, 0xc3];
+
+#[test]
+fn dump_prints_each_value() {
+    let output = run(&["dump", "--compact"], STREAM);
+    assert_eq!(stdout(&output), "{\"a\": [1, \"x\"]}\ntrue\n");
+}
+
+#[test]
+fn dump_disassemble() {
+    let output = run(&["dump", "--disassemble", "-"], &[0xc3]);
+    assert!(stdout(&output).starts_with("00000000  c3"));
+}
+
+#[test]
+fn json_round_trip() {
+    let json = run(&["to-json"], STREAM);
+    assert_eq!(stdout(&json), "{\"a\":[1,\"x\"]}\ntrue\n");
+
+    let msgpack = run(&["from-json"], json.stdout.as_slice());
+    assert!(msgpack.status.success());
+    assert_eq!(msgpack.stdout, STREAM);
+}
+
+#[test]
+fn to_json_bin_policy() {
+    let output = run(&["to-json", "--bin", "hex"], &[0xc4, 0x02, 0x00, 0xff]);
+    assert_eq!(stdout(&output), "\"00ff\"\n");
+
+    let output = run(&["to-json"], &[0xc4, 0x02, 0x00, 0xff]);
+    assert_eq!(stdout(&output), "\"AP8=\"\n");
+}
+
+#[test]
+fn from_json_rules() {
+    let json = br#"{"blob": "AP8=", "at": "1970-01-01T00:00:00Z"} "AP8=""#;
+    let output = run(&["from-json", "--bin", "/blob", "--timestamp", "/at"], json);
+    assert!(output.status.success(), "{output:?}");
+    let expected = [
+        &[0x82, 0xa4][..],
+        b"blob",
+        &[0xc4, 0x02, 0x00, 0xff, 0xa2],
+        b"at",
+        &[0xd6, 0xff, 0, 0, 0, 0, 0xa4],
+        b"AP8=",
+    ]
+    .concat();
+    assert_eq!(output.stdout, expected);
+
+    let output = run(&["from-json", "--bin", "blob"], json);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("not a JSON Pointer"), "{stderr}");
+}
+
+#[test]
+fn validate_reports_offset_of_bad_value() {
+    let output = run(&["validate"], STREAM);
+    assert_eq!(stdout(&output), "ok: 2 values, 8 bytes\n");
+
+    let output = run(&["validate", "--max-items", "1"], STREAM);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("offset 0"), "{stderr}");
+
+    let output = run(&["validate"], &[0xc3, 0x92, 0x01]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("offset 1"), "{stderr}");
+}
+
+#[test]
+fn get_by_pointer() {
+    let output = run(&["get", "/a/1", "--json"], STREAM);
+    assert_eq!(stdout(&output), "\"x\"\n");
+
+    let output = run(&["get", "/a/0", "--raw"], STREAM);
+    assert_eq!(output.stdout, [0x01]);
+
+    let output = run(&["get", "/missing"], STREAM);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn stats_summary() {
+    let output = run(&["stats"], STREAM);
+    let text = stdout(&output);
+    assert!(text.contains("values     2"), "{text}");
+    assert!(text.contains("max depth  2"), "{text}");
+    assert!(text.contains("fixstr"), "{text}");
+}
@@ -0,0 +1,385 @@
+//! Tools for inspecting encoded MessagePack by hand.
+
+use core::fmt::{self, Write};
+
+use crate::{
+    Format,
+    extension::ExtensionRef,
+    notation::{Emitter, Style},
+};
+
+/// Maximum nesting depth of arrays and maps that is disassembled.
+const MAX_DEPTH: usize = 256;
+
+/// Number of raw bytes shown per line before eliding the rest.
+const RAW_BYTES: usize = 8;
+
+/// Width of the indentation plus format name column.
+const NAME_WIDTH: usize = 16;
+
+/// Disassemble `buf` into one line per token.
+///
+/// Each line shows the offset, the raw bytes, the [`Format`] name indented by
+/// nesting level and the decoded value in the [notation](crate::notation).
+/// Every value in `buf` is shown, so a stream of concatenated values can be
+/// inspected at once. Malformed input ends the listing with an `error` line
+/// pointing at the offending byte.
+///
+/// ```rust
+/// use messagepack_core::debug::disassemble;
+///
+/// let buf: &[u8] = &[0x82, 0xa1, 0x61, 0xcd, 0x01, 0x00, 0xa1, 0x62, 0x92, 0xc3, 0xa2, 0x68];
+///
+/// assert_eq!(
+///     disassemble(buf).to_string(),
+///     "\
+/// 00000000  82                       fixmap          entries: 2
+/// 00000001  a1 61                      fixstr        \"a\"
+/// 00000003  cd 01 00                   uint16        256
+/// 00000006  a1 62                      fixstr        \"b\"
+/// 00000008  92                         fixarray      items: 2
+/// 00000009  c3                           true        true
+/// 0000000a  a2 68                        fixstr
+/// 0000000c  error: unexpected end of data
+/// "
+/// );
+/// ```
+pub fn disassemble(buf: &[u8]) -> Disassembly<'_> {
+    Disassembly { buf }
+}
+
+/// Listing returned by [`disassemble`], rendered through [`fmt::Display`].
+#[derive(Debug, Clone, Copy)]
+pub struct Disassembly<'a> {
+    buf: &'a [u8],
+}
+
+impl fmt::Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut cursor = Cursor {
+            buf: self.buf,
+            pos: 0,
+        };
+        while cursor.pos < self.buf.len() {
+            if let Err(e) = cursor.token(f, 0)? {
+                writeln!(f, "{:08x}  error: {}", e.offset, e.reason)?;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where and why the input stopped making sense.
+struct Malformed {
+    offset: usize,
+    reason: &'static str,
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Slice of `len` bytes starting at `at`, flagging the first missing byte.
+    fn bytes(&self, at: usize, len: usize) -> Result<&'a [u8], Malformed> {
+        at.checked_add(len)
+            .and_then(|end| self.buf.get(at..end))
+            .ok_or(Malformed {
+                offset: self.buf.len().min(at.saturating_add(len)),
+                reason: "unexpected end of data",
+            })
+    }
+
+    fn be(&self, at: usize, len: usize) -> Result<u64, Malformed> {
+        Ok(self
+            .bytes(at, len)?
+            .iter()
+            .fold(0, |acc, b| (acc << 8) | u64::from(*b)))
+    }
+
+    /// Print one token and its children. The outer `fmt::Result` reports
+    /// output errors, the inner one malformed input.
+    fn token(
+        &mut self,
+        f: &mut fmt::Formatter<'_>,
+        depth: usize,
+    ) -> Result<Result<(), Malformed>, fmt::Error> {
+        let start = self.pos;
+        let format = Format::from_byte(self.buf[start]);
+        let (header, payload) = match header_layout(format) {
+            Some(layout) => layout,
+            None => {
+                line(f, start, &self.buf[start..=start], depth, format)?;
+                writeln!(f)?;
+                return Ok(Err(Malformed {
+                    offset: start,
+                    reason: "reserved format",
+                }));
+            }
+        };
+
+        // Length of the payload following the header, or the child count.
+        let size = match payload {
+            Payload::None => 0,
+            Payload::Fixed(n) => n,
+            Payload::Len(width) => match self.be(start + 1, width) {
+                Ok(n) => n as usize,
+                Err(e) => {
+                    line(f, start, &self.buf[start..], depth, format)?;
+                    writeln!(f)?;
+                    return Ok(Err(e));
+                }
+            },
+        };
+        let is_container = matches!(
+            format,
+            Format::FixArray(_)
+                | Format::Array16
+                | Format::Array32
+                | Format::FixMap(_)
+                | Format::Map16
+                | Format::Map32
+        );
+        let data_len = if is_container { 0 } else { size };
+        let token = match self.bytes(start, header + data_len) {
+            Ok(token) => token,
+            Err(e) => {
+                line(f, start, &self.buf[start..], depth, format)?;
+                writeln!(f)?;
+                return Ok(Err(e));
+            }
+        };
+        self.pos = start + token.len();
+
+        let value = &token[header..];
+        let used = line(f, start, token, depth, format)?;
+        if let Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 = format
+            && let Err(e) = core::str::from_utf8(value)
+        {
+            writeln!(f)?;
+            return Ok(Err(Malformed {
+                offset: start + header + e.valid_up_to(),
+                reason: "invalid utf-8",
+            }));
+        }
+        pad(f, used)?;
+        let mut emitter = Emitter::new(f, Style::Compact);
+        match format {
+            Format::Nil => emitter.nil()?,
+            Format::False => emitter.bool(false)?,
+            Format::True => emitter.bool(true)?,
+            Format::PositiveFixInt(v) => emitter.uint(v.into())?,
+            Format::NegativeFixInt(v) => emitter.int(v.into())?,
+            Format::Uint8 | Format::Uint16 | Format::Uint32 | Format::Uint64 => {
+                emitter.uint(be_u64(value))?
+            }
+            Format::Int8 => emitter.int((be_u64(value) as i8).into())?,
+            Format::Int16 => emitter.int((be_u64(value) as i16).into())?,
+            Format::Int32 => emitter.int((be_u64(value) as i32).into())?,
+            Format::Int64 => emitter.int(be_u64(value) as i64)?,
+            Format::Float32 => emitter.f32(f32::from_bits(be_u64(value) as u32))?,
+            Format::Float64 => emitter.f64(f64::from_bits(be_u64(value)))?,
+            Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+                // Validated above.
+                if let Ok(s) = core::str::from_utf8(value) {
+                    emitter.str(s)?
+                }
+            }
+            Format::Bin8 | Format::Bin16 | Format::Bin32 => emitter.bin(value)?,
+            Format::FixExt1
+            | Format::FixExt2
+            | Format::FixExt4
+            | Format::FixExt8
+            | Format::FixExt16
+            | Format::Ext8
+            | Format::Ext16
+            | Format::Ext32 => emitter.ext(ExtensionRef::new(token[header - 1] as i8, value))?,
+            Format::FixArray(_) | Format::Array16 | Format::Array32 => write!(f, "items: {size}")?,
+            Format::FixMap(_) | Format::Map16 | Format::Map32 => write!(f, "entries: {size}")?,
+            Format::NeverUsed => unreachable!("rejected by header_layout"),
+        }
+        writeln!(f)?;
+
+        if is_container {
+            if depth >= MAX_DEPTH {
+                return Ok(Err(Malformed {
+                    offset: start,
+                    reason: "nesting too deep",
+                }));
+            }
+            let children = match format {
+                Format::FixMap(_) | Format::Map16 | Format::Map32 => size.saturating_mul(2),
+                _ => size,
+            };
+            for _ in 0..children {
+                if self.pos >= self.buf.len() {
+                    return Ok(Err(Malformed {
+                        offset: self.pos,
+                        reason: "unexpected end of data",
+                    }));
+                }
+                if let Err(e) = self.token(f, depth + 1)? {
+                    return Ok(Err(e));
+                }
+            }
+        }
+        Ok(Ok(()))
+    }
+}
+
+/// How the bytes after the marker are laid out.
+enum Payload {
+    /// Nothing follows the header.
+    None,
+    /// A fixed number of bytes follows the header.
+    Fixed(usize),
+    /// The header ends with a big endian length of this many bytes.
+    Len(usize),
+}
+
+/// Header size including the marker, and the payload layout.
+fn header_layout(format: Format) -> Option<(usize, Payload)> {
+    let layout = match format {
+        Format::PositiveFixInt(_)
+        | Format::NegativeFixInt(_)
+        | Format::Nil
+        | Format::False
+        | Format::True => (1, Payload::None),
+        Format::FixStr(n) => (1, Payload::Fixed(n.into())),
+        Format::FixArray(n) | Format::FixMap(n) => (1, Payload::Fixed(n.into())),
+        Format::Uint8 | Format::Int8 => (1, Payload::Fixed(1)),
+        Format::Uint16 | Format::Int16 => (1, Payload::Fixed(2)),
+        Format::Uint32 | Format::Int32 | Format::Float32 => (1, Payload::Fixed(4)),
+        Format::Uint64 | Format::Int64 | Format::Float64 => (1, Payload::Fixed(8)),
+        Format::FixExt1 => (2, Payload::Fixed(1)),
+        Format::FixExt2 => (2, Payload::Fixed(2)),
+        Format::FixExt4 => (2, Payload::Fixed(4)),
+        Format::FixExt8 => (2, Payload::Fixed(8)),
+        Format::FixExt16 => (2, Payload::Fixed(16)),
+        Format::Str8 | Format::Bin8 => (2, Payload::Len(1)),
+        Format::Str16 | Format::Bin16 | Format::Array16 | Format::Map16 => (3, Payload::Len(2)),
+        Format::Str32 | Format::Bin32 | Format::Array32 | Format::Map32 => (5, Payload::Len(4)),
+        Format::Ext8 => (3, Payload::Len(1)),
+        Format::Ext16 => (4, Payload::Len(2)),
+        Format::Ext32 => (6, Payload::Len(4)),
+        Format::NeverUsed => return None,
+    };
+    Some(layout)
+}
+
+fn be_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, b| (acc << 8) | u64::from(*b))
+}
+
+/// Write offset, raw bytes and the indented format name, returning the width
+/// of the name column used so far.
+fn line(
+    f: &mut fmt::Formatter<'_>,
+    offset: usize,
+    token: &[u8],
+    depth: usize,
+    format: Format,
+) -> Result<usize, fmt::Error> {
+    write!(f, "{offset:08x} ")?;
+    let shown = if token.len() > RAW_BYTES {
+        RAW_BYTES - 1
+    } else {
+        token.len()
+    };
+    for b in &token[..shown] {
+        write!(f, " {b:02x}")?;
+    }
+    if shown < token.len() {
+        f.write_str(" ..")?;
+    }
+    for _ in shown * 3 + usize::from(shown < token.len()) * 3..RAW_BYTES * 3 {
+        f.write_char(' ')?;
+    }
+
+    let name = format.name();
+    f.write_str("  ")?;
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+    f.write_str(name)?;
+    Ok(depth * 2 + name.len())
+}
+
+/// Pad the name column before the value.
+fn pad(f: &mut fmt::Formatter<'_>, used: usize) -> fmt::Result {
+    for _ in used..NAME_WIDTH.max(used + 1) {
+        f.write_char(' ')?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn listing(buf: &[u8]) -> std::string::String {
+        std::format!("{}", disassemble(buf))
+    }
+
+    #[test]
+    fn nested_values_are_indented() {
+        let buf: &[u8] = &[
+            0x81, 0xa1, 0x6b, 0x92, 0xd0, 0xfe, 0xca, 0x3f, 0xc0, 0x00, 0x00,
+        ];
+        assert_eq!(
+            listing(buf),
+            "\
+00000000  81                       fixmap          entries: 1
+00000001  a1 6b                      fixstr        \"k\"
+00000003  92                         fixarray      items: 2
+00000004  d0 fe                        int8        -2
+00000006  ca 3f c0 00 00               float32     f32(1.5)
+"
+        );
+    }
+
+    #[test]
+    fn long_tokens_are_elided() {
+        let buf: &[u8] = &[
+            0xc4, 0x0a, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0xd6, 0xff, 0, 0, 0, 1,
+        ];
+        assert_eq!(
+            listing(buf),
+            "\
+00000000  c4 0a 00 01 02 03 04 ..  bin8            h'00010203040506070809'
+0000000c  d6 ff 00 00 00 01        fixext4         timestamp32(1)
+"
+        );
+    }
+
+    #[rstest]
+    #[case(&[0xc1], "00000000  error: reserved format")]
+    #[case(&[0x92, 0x01], "00000002  error: unexpected end of data")]
+    #[case(&[0xcd, 0x01], "00000002  error: unexpected end of data")]
+    #[case(&[0xda, 0x00], "00000002  error: unexpected end of data")]
+    #[case(&[0xc0, 0xa3, 0x61, 0xff, 0x62], "00000003  error: invalid utf-8")]
+    #[case(&[0xc7, 0x02, 0x01, 0x00], "00000004  error: unexpected end of data")]
+    fn malformed_input_is_flagged(#[case] buf: &[u8], #[case] last_line: &str) {
+        let text = listing(buf);
+        assert_eq!(text.lines().last(), Some(last_line), "{text}");
+        assert!(text.lines().all(|l| !l.ends_with(' ')), "{text}");
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let buf = [0x91; MAX_DEPTH + 2];
+        let text = listing(&buf);
+        assert_eq!(
+            text.lines().last(),
+            Some("00000100  error: nesting too deep")
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(listing(&[]), "");
+    }
+}
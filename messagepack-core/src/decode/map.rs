@@ -5,6 +5,34 @@ use core::marker::PhantomData;
 use super::{DecodeBorrowed, Error, NbyteReader};
 use crate::{formats::Format, io::IoRead};
 
+/// A value decoded from entries of an enclosing map, one entry at a time.
+///
+/// The enclosing map reads each key and offers it through
+/// [`DecodeMapEntries::accept_key`]. When the key is accepted the value is
+/// read with [`DecodeMapEntries::decode_value`]; otherwise it is left for the
+/// enclosing map. This is how the derive macro decodes fields marked
+/// `#[msgpack(flatten)]`.
+pub trait DecodeMapEntries<'de> {
+    /// The decoded value.
+    type Value;
+    /// State collected while entries are offered.
+    type Builder;
+
+    /// Create the state before any entry is offered.
+    fn builder() -> Self::Builder;
+
+    /// Offer a key, returning whether its value should be decoded into `builder`.
+    fn accept_key(builder: &mut Self::Builder, key: &[u8]) -> bool;
+
+    /// Decode the value of the key most recently accepted.
+    fn decode_value<R>(builder: &mut Self::Builder, reader: &mut R) -> Result<(), Error<R::Error>>
+    where
+        R: IoRead<'de>;
+
+    /// Build the value once the enclosing map has been read.
+    fn finish<E>(builder: Self::Builder) -> Result<Self::Value, Error<E>>;
+}
+
 /// Decode a MessagePack map of `K -> V` into `Map` collecting iterator.
 pub struct MapDecoder<Map, K, V>(PhantomData<(Map, K, V)>);
 
@@ -58,6 +86,18 @@ where
     }
 }
 
+/// Hold a UTF-8 key until its value is decoded, rejecting any other key.
+#[cfg(feature = "alloc")]
+fn accept_str_key(pending: &mut Option<alloc::string::String>, key: &[u8]) -> bool {
+    match core::str::from_utf8(key) {
+        Ok(key) => {
+            *pending = Some(key.into());
+            true
+        }
+        Err(_) => false,
+    }
+}
+
 #[cfg(feature = "alloc")]
 mod alloc_impl {
     use super::*;
@@ -80,6 +120,39 @@ mod alloc_impl {
             MapDecoder::<Self::Value, K, V>::decode_borrowed_with_format(format, reader)
         }
     }
+
+    /// Collects every offered entry whose key is valid UTF-8.
+    impl<'de, V> DecodeMapEntries<'de> for alloc::collections::BTreeMap<alloc::string::String, V>
+    where
+        V: DecodeBorrowed<'de>,
+    {
+        type Value = alloc::collections::BTreeMap<alloc::string::String, V::Value>;
+        type Builder = (Self::Value, Option<alloc::string::String>);
+
+        fn builder() -> Self::Builder {
+            (Self::Value::new(), None)
+        }
+
+        fn accept_key(builder: &mut Self::Builder, key: &[u8]) -> bool {
+            accept_str_key(&mut builder.1, key)
+        }
+
+        fn decode_value<R>(
+            builder: &mut Self::Builder,
+            reader: &mut R,
+        ) -> Result<(), Error<R::Error>>
+        where
+            R: IoRead<'de>,
+        {
+            let key = builder.1.take().ok_or(Error::InvalidData)?;
+            builder.0.insert(key, V::decode_borrowed(reader)?);
+            Ok(())
+        }
+
+        fn finish<E>(builder: Self::Builder) -> Result<Self::Value, Error<E>> {
+            Ok(builder.0)
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -104,6 +177,40 @@ mod std_impl {
             MapDecoder::<Self::Value, K, V>::decode_borrowed_with_format(format, reader)
         }
     }
+
+    /// Collects every offered entry whose key is valid UTF-8.
+    impl<'de, V, S> DecodeMapEntries<'de> for std::collections::HashMap<String, V, S>
+    where
+        V: DecodeBorrowed<'de>,
+        S: core::hash::BuildHasher + Default,
+    {
+        type Value = std::collections::HashMap<String, V::Value, S>;
+        type Builder = (Self::Value, Option<String>);
+
+        fn builder() -> Self::Builder {
+            (Self::Value::default(), None)
+        }
+
+        fn accept_key(builder: &mut Self::Builder, key: &[u8]) -> bool {
+            accept_str_key(&mut builder.1, key)
+        }
+
+        fn decode_value<R>(
+            builder: &mut Self::Builder,
+            reader: &mut R,
+        ) -> Result<(), Error<R::Error>>
+        where
+            R: IoRead<'de>,
+        {
+            let key = builder.1.take().ok_or(Error::InvalidData)?;
+            builder.0.insert(key, V::decode_borrowed(reader)?);
+            Ok(())
+        }
+
+        fn finish<E>(builder: Self::Builder) -> Result<Self::Value, Error<E>> {
+            Ok(builder.0)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -188,4 +295,31 @@ mod tests {
         assert_eq!(m.get(&3), Some(&false));
         assert!(r.rest().is_empty());
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hashmap_collects_offered_entries() {
+        // Any `BuildHasher + Default` works, matching `EncodeMapEntries`
+        type Entries = std::collections::HashMap<
+            String,
+            u8,
+            core::hash::BuildHasherDefault<std::hash::DefaultHasher>,
+        >;
+
+        // "a": 1, "b": 2, offered one entry at a time
+        let buf = &[0x01, 0x02];
+        let mut r = crate::io::SliceReader::new(buf);
+        let mut builder = <Entries as DecodeMapEntries>::builder();
+        assert!(Entries::accept_key(&mut builder, b"a"));
+        Entries::decode_value(&mut builder, &mut r).unwrap();
+        assert!(!Entries::accept_key(&mut builder, &[0xff]));
+        assert!(Entries::accept_key(&mut builder, b"b"));
+        Entries::decode_value(&mut builder, &mut r).unwrap();
+
+        let m = Entries::finish::<()>(builder).unwrap();
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get("a"), Some(&1));
+        assert_eq!(m.get("b"), Some(&2));
+        assert!(r.rest().is_empty());
+    }
 }
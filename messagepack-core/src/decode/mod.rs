@@ -14,7 +14,7 @@ mod bool;
 mod float;
 mod int;
 mod map;
-pub use map::MapDecoder;
+pub use map::{DecodeMapEntries, MapDecoder};
 mod nil;
 pub use nil::NilDecoder;
 mod str;
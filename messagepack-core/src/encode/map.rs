@@ -26,6 +26,38 @@ impl<K: Encode, V: Encode> KVEncode for (K, V) {
     }
 }
 
+/// A value written as the entries of a map, without the map header.
+///
+/// Used to inline a value into an enclosing map, such as a field marked
+/// `#[msgpack(flatten)]` in the derive macro.
+pub trait EncodeMapEntries {
+    /// The number of key-value pairs [`EncodeMapEntries::encode_entries`] writes.
+    fn entry_count(&self) -> usize;
+
+    /// Encode the key-value pairs to the writer and return the number of bytes written.
+    fn encode_entries<W: IoWrite>(&self, writer: &mut W) -> Result<usize, W::Error>;
+}
+
+impl<T: EncodeMapEntries + ?Sized> EncodeMapEntries for &T {
+    fn entry_count(&self) -> usize {
+        T::entry_count(self)
+    }
+
+    fn encode_entries<W: IoWrite>(&self, writer: &mut W) -> Result<usize, W::Error> {
+        T::encode_entries(self, writer)
+    }
+}
+
+fn encode_entries<W, I>(writer: &mut W, it: I) -> Result<usize, W::Error>
+where
+    W: IoWrite,
+    I: Iterator,
+    I::Item: KVEncode,
+{
+    it.map(|kv| kv.encode_kv(writer))
+        .try_fold(0, |acc, v| v.map(|n| acc + n))
+}
+
 /// Encode only the map header for a map of a given length.
 pub struct MapFormatEncoder(pub usize);
 impl MapFormatEncoder {
@@ -109,9 +141,7 @@ where
     I::Item: KVEncode,
 {
     let format_len = MapFormatEncoder::new(len).encode(writer)?;
-    let data_len = it
-        .map(|kv| kv.encode_kv(writer))
-        .try_fold(0, |acc, v| v.map(|n| acc + n))?;
+    let data_len = encode_entries(writer, it)?;
     Ok(format_len + data_len)
 }
 
@@ -153,6 +183,16 @@ mod alloc_impl {
             encode_iter(writer, self.len(), self.iter())
         }
     }
+
+    impl<K: Encode + Ord, V: Encode> EncodeMapEntries for alloc::collections::BTreeMap<K, V> {
+        fn entry_count(&self) -> usize {
+            self.len()
+        }
+
+        fn encode_entries<W: IoWrite>(&self, writer: &mut W) -> Result<usize, W::Error> {
+            encode_entries(writer, self.iter())
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -168,6 +208,21 @@ mod std_impl {
             encode_iter(writer, self.len(), self.iter())
         }
     }
+
+    impl<K, V, S> EncodeMapEntries for std::collections::HashMap<K, V, S>
+    where
+        K: Encode + Eq + core::hash::Hash,
+        V: Encode,
+        S: std::hash::BuildHasher,
+    {
+        fn entry_count(&self) -> usize {
+            self.len()
+        }
+
+        fn encode_entries<W: IoWrite>(&self, writer: &mut W) -> Result<usize, W::Error> {
+            encode_entries(writer, self.iter())
+        }
+    }
 }
 /// Encode a map from an owned iterator, writing items lazily.
 pub struct MapEncoder<I, J, KV> {
@@ -262,6 +317,21 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encode_btreemap_entries_without_header() {
+        let mut m = alloc::collections::BTreeMap::new();
+        m.insert(2u8, 20u8);
+        m.insert(1u8, 10u8);
+
+        let mut buf = alloc::vec::Vec::new();
+        let n = m.encode_entries(&mut buf).unwrap();
+
+        assert_eq!(m.entry_count(), 2);
+        assert_eq!(buf, [0x01, 0x0a, 0x02, 0x14]);
+        assert_eq!(n, buf.len());
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn encode_hashmap_roundtrip() {
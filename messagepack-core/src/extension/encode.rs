@@ -4,67 +4,69 @@ use crate::encode::{self, Encode};
 use crate::formats::Format;
 use crate::io::IoWrite;
 
+/// Write the header of an extension with a `len` byte payload.
+pub(crate) fn write_ext_header<W: IoWrite>(
+    len: usize,
+    r#type: i8,
+    writer: &mut W,
+) -> core::result::Result<usize, encode::Error<W::Error>> {
+    let type_byte = r#type.to_be_bytes()[0];
+
+    match len {
+        1 => {
+            writer.write(&[Format::FixExt1.as_byte(), type_byte])?;
+            Ok(2)
+        }
+        2 => {
+            writer.write(&[Format::FixExt2.as_byte(), type_byte])?;
+            Ok(2)
+        }
+        4 => {
+            writer.write(&[Format::FixExt4.as_byte(), type_byte])?;
+            Ok(2)
+        }
+        8 => {
+            writer.write(&[Format::FixExt8.as_byte(), type_byte])?;
+            Ok(2)
+        }
+        16 => {
+            writer.write(&[Format::FixExt16.as_byte(), type_byte])?;
+            Ok(2)
+        }
+        0..=0xff => {
+            let cast = len as u8;
+            writer.write(&[Format::Ext8.as_byte(), cast, type_byte])?;
+            Ok(3)
+        }
+        0x100..=U16_MAX => {
+            let cast = (len as u16).to_be_bytes();
+            writer.write(&[Format::Ext16.as_byte(), cast[0], cast[1], type_byte])?;
+            Ok(4)
+        }
+        0x1_0000..=U32_MAX => {
+            let cast = (len as u32).to_be_bytes();
+            writer.write(&[
+                Format::Ext32.as_byte(),
+                cast[0],
+                cast[1],
+                cast[2],
+                cast[3],
+                type_byte,
+            ])?;
+            Ok(6)
+        }
+        _ => Err(encode::Error::InvalidFormat),
+    }
+}
+
 impl Encode for ExtensionRef<'_> {
     fn encode<W: IoWrite>(
         &self,
         writer: &mut W,
     ) -> core::result::Result<usize, encode::Error<W::Error>> {
-        let data_len = self.data.len();
-        let type_byte = self.r#type.to_be_bytes()[0];
-
-        match data_len {
-            1 => {
-                writer.write(&[Format::FixExt1.as_byte(), type_byte])?;
-                writer.write(self.data)?;
-                Ok(2 + data_len)
-            }
-            2 => {
-                writer.write(&[Format::FixExt2.as_byte(), type_byte])?;
-                writer.write(self.data)?;
-                Ok(2 + data_len)
-            }
-            4 => {
-                writer.write(&[Format::FixExt4.as_byte(), type_byte])?;
-                writer.write(self.data)?;
-                Ok(2 + data_len)
-            }
-            8 => {
-                writer.write(&[Format::FixExt8.as_byte(), type_byte])?;
-                writer.write(self.data)?;
-                Ok(2 + data_len)
-            }
-            16 => {
-                writer.write(&[Format::FixExt16.as_byte(), type_byte])?;
-                writer.write(self.data)?;
-                Ok(2 + data_len)
-            }
-            0..=0xff => {
-                let cast = data_len as u8;
-                writer.write(&[Format::Ext8.as_byte(), cast, type_byte])?;
-                writer.write(self.data)?;
-                Ok(3 + data_len)
-            }
-            0x100..=U16_MAX => {
-                let cast = (data_len as u16).to_be_bytes();
-                writer.write(&[Format::Ext16.as_byte(), cast[0], cast[1], type_byte])?;
-                writer.write(self.data)?;
-                Ok(4 + data_len)
-            }
-            0x1_0000..=U32_MAX => {
-                let cast = (data_len as u32).to_be_bytes();
-                writer.write(&[
-                    Format::Ext32.as_byte(),
-                    cast[0],
-                    cast[1],
-                    cast[2],
-                    cast[3],
-                    type_byte,
-                ])?;
-                writer.write(self.data)?;
-                Ok(6 + data_len)
-            }
-            _ => Err(encode::Error::InvalidFormat),
-        }
+        let header = write_ext_header(self.data.len(), self.r#type, writer)?;
+        writer.write(self.data)?;
+        Ok(header + self.data.len())
     }
 }
 
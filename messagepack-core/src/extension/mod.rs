@@ -2,6 +2,12 @@
 
 mod decode;
 mod encode;
+mod payload;
+mod typed;
+pub use payload::{PayloadError, PayloadReader, PayloadWriter, decode_with, encode_with};
+pub use typed::{
+    Extension, ExtensionDecoder, ExtensionEncoder, TryFromExtensionError, decode_extension,
+};
 
 use crate::decode::Error as DecodeError;
 use crate::{formats::Format, io::IoRead};
@@ -48,7 +54,7 @@ where
 ///
 /// Note that the MessagePack header (FixExt vs Ext8/16/32) is determined by the
 /// payload length when encoding. See [`ExtensionRef::to_format`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ExtensionRef<'a> {
     /// Application‑defined extension type code.
     pub r#type: i8,
@@ -199,7 +205,7 @@ mod owned {
     use super::*;
 
     /// An owned container for extension payloads.
-    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct ExtensionOwned {
         /// Application‑defined extension type code.
         pub r#type: i8,
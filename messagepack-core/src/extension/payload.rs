@@ -0,0 +1,251 @@
+//! Extensions whose payload is itself written or read as MessagePack.
+
+use super::{TryFromExtensionError, encode::write_ext_header, read_ext_header};
+use crate::{
+    Format,
+    decode::Error as DecodeError,
+    encode,
+    io::{IoRead, IoWrite, Reference, private::Token},
+};
+
+/// Encode an extension of type `r#type` whose payload is written by `f`.
+///
+/// `f` is called twice: once to measure the payload so the smallest header
+/// can be picked, and once more to write it after the header. It must write
+/// the same bytes both times. While an enclosing `encode_with` measures its
+/// own payload the second call is skipped, but writing still measures each
+/// level again, so a closure nested `d` extensions deep runs `d + 1` times.
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_core::{Encode, extension};
+///
+/// let mut buf = [0u8; 6];
+/// let n = extension::encode_with(5, &mut buf.as_mut_slice(), |writer| (1u8, "a").encode(writer))
+///     .unwrap();
+/// assert_eq!(buf[..n], [0xd6, 5, 0x92, 0x01, 0xa1, b'a']);
+/// ```
+pub fn encode_with<W, F>(
+    r#type: i8,
+    writer: &mut W,
+    mut f: F,
+) -> core::result::Result<usize, encode::Error<W::Error>>
+where
+    W: IoWrite,
+    F: FnMut(&mut PayloadWriter<'_, W>) -> core::result::Result<usize, encode::Error<W::Error>>,
+{
+    let mut counter = PayloadWriter(Sink::Count(0));
+    f(&mut counter)?;
+    let Sink::Count(len) = counter.0 else {
+        unreachable!()
+    };
+
+    let header = write_ext_header(len, r#type, writer)?;
+    if !writer.measure(len, Token(())) {
+        let mut payload = PayloadWriter(Sink::Write(writer));
+        f(&mut payload)?;
+    }
+    Ok(header + len)
+}
+
+/// Decode an extension of type `r#type` whose payload is read by `f`.
+///
+/// As with [`decode_extension`](super::decode_extension), the outer error
+/// reports a malformed or truncated extension, including a payload which `f`
+/// rejects, does not read to the end or tries to read past. The inner result
+/// reports a type code other than `r#type`, with the code found, after
+/// skipping the payload.
+pub fn decode_with<'de, R, T, F>(
+    format: Format,
+    reader: &mut R,
+    r#type: i8,
+    f: F,
+) -> core::result::Result<Result<T, TryFromExtensionError>, DecodeError<R::Error>>
+where
+    R: IoRead<'de>,
+    F: FnOnce(
+        &mut PayloadReader<'_, R>,
+    ) -> core::result::Result<T, DecodeError<PayloadError<R::Error>>>,
+{
+    let (len, found) = read_ext_header(format, reader)?;
+    if found != r#type {
+        let skipped = reader.read_slice(len).map_err(DecodeError::Io)?;
+        if skipped.as_bytes().len() != len {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        return Ok(Err(TryFromExtensionError::InvalidType(found)));
+    }
+
+    let mut payload = PayloadReader {
+        reader,
+        remaining: len,
+    };
+    let value = f(&mut payload).map_err(|err| match err {
+        DecodeError::InvalidData => DecodeError::InvalidData,
+        DecodeError::UnexpectedFormat => DecodeError::UnexpectedFormat,
+        DecodeError::UnexpectedEof => DecodeError::UnexpectedEof,
+        DecodeError::Io(PayloadError::Io(err)) => DecodeError::Io(err),
+        DecodeError::Io(PayloadError::Overrun) => DecodeError::InvalidData,
+    })?;
+    if payload.remaining != 0 {
+        return Err(DecodeError::InvalidData);
+    }
+    Ok(Ok(value))
+}
+
+enum Sink<'a, W> {
+    Count(usize),
+    Write(&'a mut W),
+}
+
+/// The writer passed to the closure of [`encode_with`].
+pub struct PayloadWriter<'a, W>(Sink<'a, W>);
+
+impl<W: IoWrite> IoWrite for PayloadWriter<'_, W> {
+    type Error = W::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        match &mut self.0 {
+            Sink::Count(len) => {
+                *len += buf.len();
+                Ok(())
+            }
+            Sink::Write(writer) => writer.write(buf),
+        }
+    }
+
+    fn measure(&mut self, len: usize, token: Token) -> bool {
+        match &mut self.0 {
+            Sink::Count(count) => {
+                *count += len;
+                true
+            }
+            Sink::Write(writer) => writer.measure(len, token),
+        }
+    }
+}
+
+/// The reader passed to the closure of [`decode_with`], limited to the
+/// extension payload.
+pub struct PayloadReader<'a, R> {
+    reader: &'a mut R,
+    remaining: usize,
+}
+
+impl<'de, R: IoRead<'de>> IoRead<'de> for PayloadReader<'_, R> {
+    type Error = PayloadError<R::Error>;
+
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a>, Self::Error> {
+        if len > self.remaining {
+            return Err(PayloadError::Overrun);
+        }
+        self.remaining -= len;
+        self.reader.read_slice(len).map_err(PayloadError::Io)
+    }
+
+    fn remaining_slice(&self) -> Option<&'de [u8]> {
+        let rest = self.reader.remaining_slice()?;
+        Some(&rest[..rest.len().min(self.remaining)])
+    }
+}
+
+/// Error of [`PayloadReader`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PayloadError<E> {
+    /// The underlying reader failed
+    Io(E),
+    /// Tried to read past the end of the payload
+    Overrun,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for PayloadError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PayloadError::Io(err) => err.fmt(f),
+            PayloadError::Overrun => write!(f, "read past the end of the extension payload"),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for PayloadError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode::DecodeBorrowed,
+        encode::Encode,
+        io::{RError, SliceReader},
+    };
+    use rstest::rstest;
+
+    type Pair<'a> = Result<(u8, &'a str), TryFromExtensionError>;
+
+    fn decode_pair<'a>(reader: &mut SliceReader<'a>) -> Result<Pair<'a>, DecodeError<RError>> {
+        let format = Format::decode_borrowed(reader)?;
+        decode_with(format, reader, 5, |reader| {
+            <(u8, &str)>::decode_borrowed(reader)
+        })
+    }
+
+    #[rstest]
+    fn round_trip() {
+        let mut buf = vec![];
+        let n = encode_with(5, &mut buf, |writer| (1u8, "a").encode(writer)).unwrap();
+        assert_eq!(n, buf.len());
+        assert_eq!(buf, [0xd6, 5, 0x92, 0x01, 0xa1, b'a']);
+
+        assert_eq!(decode_pair(&mut SliceReader::new(&buf)), Ok(Ok((1, "a"))));
+    }
+
+    #[rstest]
+    fn nested_closure_runs_once_more_per_level() {
+        use core::cell::Cell;
+
+        let calls = [Cell::new(0), Cell::new(0), Cell::new(0)];
+        let mut buf = vec![];
+        encode_with(1, &mut buf, |writer| {
+            calls[0].set(calls[0].get() + 1);
+            encode_with(2, writer, |writer| {
+                calls[1].set(calls[1].get() + 1);
+                encode_with(3, writer, |writer| {
+                    calls[2].set(calls[2].get() + 1);
+                    1u8.encode(writer)
+                })
+            })
+        })
+        .unwrap();
+
+        assert_eq!(buf, [0xc7, 6, 1, 0xc7, 3, 2, 0xd4, 3, 0x01]);
+        // Without skipping while measuring, the innermost closure would run
+        // 2^3 times
+        assert_eq!(calls.map(|c| c.get()), [2, 3, 4]);
+    }
+
+    #[rstest]
+    fn picks_header_by_payload_length() {
+        let mut buf = vec![];
+        encode_with(-3, &mut buf, |writer| "abcd".encode(writer)).unwrap();
+        assert_eq!(buf[..3], [0xc7, 5, 0xfd]);
+    }
+
+    #[rstest]
+    fn wrong_type_is_skipped() {
+        let buf = [0xd6, 6, 0x92, 0x01, 0xa1, b'a', 0xc0];
+        let mut reader = SliceReader::new(&buf);
+        assert_eq!(
+            decode_pair(&mut reader),
+            Ok(Err(TryFromExtensionError::InvalidType(6)))
+        );
+        assert_eq!(reader.rest(), [0xc0]);
+    }
+
+    #[rstest]
+    #[case::trailing_bytes(&[0xc7, 5, 5, 0x92, 0x01, 0xa1, b'a', 0xc0])]
+    #[case::overrun(&[0xd4, 5, 0x92, 0x01, 0xa1, b'a'])]
+    fn invalid(#[case] buf: &[u8]) {
+        let result = decode_pair(&mut SliceReader::new(buf));
+        assert_eq!(result.unwrap_err(), DecodeError::InvalidData);
+    }
+}
@@ -0,0 +1,321 @@
+//! Rust types stored as MessagePack extensions with a fixed type code.
+
+use core::marker::PhantomData;
+
+use super::{ExtensionRef, encode::write_ext_header, read_ext_header};
+use crate::{
+    Format,
+    decode::{DecodeBorrowed, Error as DecodeError},
+    encode::{self, Encode},
+    io::{IoRead, IoWrite, private::Token},
+    timestamp::{TIMESTAMP_EXTENSION_TYPE, Timestamp},
+};
+
+/// A type stored as the payload of an extension with type code
+/// [`Extension::TYPE`].
+///
+/// [`ExtensionEncoder`] and [`ExtensionDecoder`] add the header and check the
+/// type code, so an implementation only deals with the payload bytes. Use
+/// [`decode_extension`] to learn which type code was found on a mismatch.
+///
+/// These are wrapper types rather than blanket `impl<T: Extension> Encode for T`
+/// and `DecodeBorrowed` impls: a blanket impl would overlap with every other
+/// `Encode` impl in the crate under coherence rules, starting with
+/// [`Timestamp`], which implements both `Encode` and `Extension`.
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_core::{
+///     Decode, Encode,
+///     encode,
+///     extension::{Extension, ExtensionDecoder, ExtensionEncoder, TryFromExtensionError},
+///     io::{IoWrite, SliceReader},
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i16,
+///     y: i16,
+/// }
+///
+/// impl Extension for Point {
+///     const TYPE: i8 = 7;
+///
+///     fn encode_payload<W: IoWrite>(&self, writer: &mut W) -> Result<(), encode::Error<W::Error>> {
+///         writer.write(&self.x.to_be_bytes())?;
+///         writer.write(&self.y.to_be_bytes())?;
+///         Ok(())
+///     }
+///
+///     fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+///         let [x0, x1, y0, y1] = data.try_into().map_err(|_| TryFromExtensionError::InvalidData)?;
+///         Ok(Point {
+///             x: i16::from_be_bytes([x0, x1]),
+///             y: i16::from_be_bytes([y0, y1]),
+///         })
+///     }
+/// }
+///
+/// let mut buf = [0u8; 6];
+/// let n = ExtensionEncoder(&Point { x: 1, y: -1 }).encode(&mut buf.as_mut_slice()).unwrap();
+/// assert_eq!(buf[..n], [0xd6, 7, 0x00, 0x01, 0xff, 0xff]);
+///
+/// let point = ExtensionDecoder::<Point>::decode(&mut SliceReader::new(&buf)).unwrap();
+/// assert_eq!(point, Point { x: 1, y: -1 });
+/// ```
+pub trait Extension: Sized {
+    /// Application‑defined extension type code.
+    const TYPE: i8;
+
+    /// Write the payload bytes, without the extension header.
+    fn encode_payload<W: IoWrite>(&self, writer: &mut W) -> Result<(), encode::Error<W::Error>>;
+
+    /// Read the value back from its payload bytes.
+    fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError>;
+
+    /// Check the type code of `ext` and decode its payload.
+    fn from_extension(ext: ExtensionRef<'_>) -> Result<Self, TryFromExtensionError> {
+        if ext.r#type != Self::TYPE {
+            return Err(TryFromExtensionError::InvalidType(ext.r#type));
+        }
+        Self::decode_payload(ext.data)
+    }
+}
+
+/// The error type returned when an extension cannot be read as an
+/// [`Extension`] type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TryFromExtensionError {
+    /// The extension has this type code instead of [`Extension::TYPE`]
+    InvalidType(i8),
+    /// The payload is not valid for the type
+    InvalidData,
+}
+
+impl core::fmt::Display for TryFromExtensionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TryFromExtensionError::InvalidType(found) => {
+                write!(f, "unexpected extension type {found}")
+            }
+            TryFromExtensionError::InvalidData => write!(f, "invalid extension payload"),
+        }
+    }
+}
+
+impl core::error::Error for TryFromExtensionError {}
+
+/// Encode an [`Extension`] type with its extension header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExtensionEncoder<'a, T>(pub &'a T);
+
+impl<T: Extension> Encode for ExtensionEncoder<'_, T> {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        let len = payload_len(self.0)?;
+        let header = write_ext_header(len, T::TYPE, writer)?;
+        if !writer.measure(len, Token(())) {
+            self.0.encode_payload(writer)?;
+        }
+        Ok(header + len)
+    }
+}
+
+/// Count the payload bytes by encoding once without writing them anywhere.
+fn payload_len<T: Extension, E>(value: &T) -> Result<usize, encode::Error<E>> {
+    struct Counter(usize);
+
+    impl IoWrite for Counter {
+        type Error = core::convert::Infallible;
+
+        fn write(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+            self.0 += buf.len();
+            Ok(())
+        }
+
+        fn measure(&mut self, len: usize, _: Token) -> bool {
+            self.0 += len;
+            true
+        }
+    }
+
+    let mut counter = Counter(0);
+    match value.encode_payload(&mut counter) {
+        Ok(()) => Ok(counter.0),
+        Err(_) => Err(encode::Error::InvalidFormat),
+    }
+}
+
+/// Read an extension with a previously read `format` and decode it as `T`.
+///
+/// The outer error reports a malformed or truncated extension. The inner
+/// result reports a type code other than [`Extension::TYPE`], with the code
+/// found, or a payload `T` rejects.
+pub fn decode_extension<'de, T, R>(
+    format: Format,
+    reader: &mut R,
+) -> core::result::Result<Result<T, TryFromExtensionError>, DecodeError<R::Error>>
+where
+    T: Extension,
+    R: IoRead<'de>,
+{
+    let (len, r#type) = read_ext_header(format, reader)?;
+    let payload = reader.read_slice(len).map_err(DecodeError::Io)?;
+    let data = payload.as_bytes();
+    if data.len() != len {
+        return Err(DecodeError::UnexpectedEof);
+    }
+    Ok(T::from_extension(ExtensionRef::new(r#type, data)))
+}
+
+/// Decode an [`Extension`] type, checking its type code.
+///
+/// Any [`TryFromExtensionError`] is reported as [`DecodeError::InvalidData`];
+/// see [`decode_extension`] to keep it.
+pub struct ExtensionDecoder<T>(PhantomData<T>);
+
+impl<'de, T: Extension> DecodeBorrowed<'de> for ExtensionDecoder<T> {
+    type Value = T;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        decode_extension::<T, R>(format, reader)?.map_err(|_| DecodeError::InvalidData)
+    }
+}
+
+impl Extension for Timestamp {
+    const TYPE: i8 = TIMESTAMP_EXTENSION_TYPE;
+
+    fn encode_payload<W: IoWrite>(&self, writer: &mut W) -> Result<(), encode::Error<W::Error>> {
+        let ext = self.to_extension();
+        writer.write(ext.as_slice())?;
+        Ok(())
+    }
+
+    fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+        Timestamp::try_from(ExtensionRef::new(Self::TYPE, data))
+            .map_err(|_| TryFromExtensionError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode::Decode, io::SliceReader};
+    use rstest::rstest;
+
+    /// A payload made of `len` copies of `byte`.
+    #[derive(Debug, PartialEq)]
+    struct Filled {
+        byte: u8,
+        len: usize,
+    }
+
+    impl Extension for Filled {
+        const TYPE: i8 = 3;
+
+        fn encode_payload<W: IoWrite>(
+            &self,
+            writer: &mut W,
+        ) -> Result<(), encode::Error<W::Error>> {
+            for _ in 0..self.len {
+                writer.write(&[self.byte])?;
+            }
+            Ok(())
+        }
+
+        fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+            match data {
+                [byte, rest @ ..] if rest.iter().all(|b| b == byte) => Ok(Filled {
+                    byte: *byte,
+                    len: data.len(),
+                }),
+                _ => Err(TryFromExtensionError::InvalidData),
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(1, vec![0xd4, 3])]
+    #[case(4, vec![0xd6, 3])]
+    #[case(5, vec![0xc7, 5, 3])]
+    #[case(256, vec![0xc8, 0x01, 0x00, 3])]
+    fn round_trip(#[case] len: usize, #[case] header: Vec<u8>) {
+        let value = Filled { byte: 0xab, len };
+        let mut buf = vec![];
+        let n = ExtensionEncoder(&value).encode(&mut buf).unwrap();
+        assert_eq!(n, buf.len());
+        assert_eq!(buf[..header.len()], header);
+        assert_eq!(buf.len(), header.len() + len);
+
+        let mut r = SliceReader::new(&buf);
+        assert_eq!(ExtensionDecoder::<Filled>::decode(&mut r).unwrap(), value);
+        assert!(r.rest().is_empty());
+    }
+
+    #[rstest]
+    fn mismatched_type() {
+        let buf = [0xd4, 9, 0xab];
+        let mut r = SliceReader::new(&buf);
+        assert_eq!(
+            ExtensionDecoder::<Filled>::decode(&mut r).unwrap_err(),
+            DecodeError::InvalidData
+        );
+
+        let ext = ExtensionRef::new(9, &[0xab]);
+        assert_eq!(
+            Filled::from_extension(ext),
+            Err(TryFromExtensionError::InvalidType(9))
+        );
+    }
+
+    #[rstest]
+    #[case(&[0xd4, 9, 0xab], Err(TryFromExtensionError::InvalidType(9)))]
+    #[case(&[0xd4, -2i8 as u8, 0xab], Err(TryFromExtensionError::InvalidType(-2)))]
+    #[case(&[0xd5, 3, 0xab, 0xcd], Err(TryFromExtensionError::InvalidData))]
+    #[case(&[0xd5, 3, 0xab, 0xab], Ok(Filled { byte: 0xab, len: 2 }))]
+    fn decode_extension_reports_found_type(
+        #[case] buf: &[u8],
+        #[case] expected: Result<Filled, TryFromExtensionError>,
+    ) {
+        let mut r = SliceReader::new(buf);
+        let format = <Format as Decode>::decode(&mut r).unwrap();
+        assert_eq!(
+            decode_extension::<Filled, _>(format, &mut r).unwrap(),
+            expected
+        );
+        assert!(r.rest().is_empty());
+    }
+
+    #[rstest]
+    fn invalid_payload() {
+        let buf = [0xd5, 3, 0xab, 0xcd];
+        let mut r = SliceReader::new(&buf);
+        assert_eq!(
+            ExtensionDecoder::<Filled>::decode(&mut r).unwrap_err(),
+            DecodeError::InvalidData
+        );
+    }
+
+    #[rstest]
+    fn timestamp_matches_its_own_encoding() {
+        let ts = Timestamp::new(-1, 5).unwrap();
+        let mut expected = vec![];
+        ts.encode(&mut expected).unwrap();
+
+        let mut buf = vec![];
+        ExtensionEncoder(&ts).encode(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+
+        let mut r = SliceReader::new(&buf);
+        assert_eq!(ExtensionDecoder::<Timestamp>::decode(&mut r).unwrap(), ts);
+    }
+}
@@ -165,6 +165,49 @@ impl Format {
         }
     }
 
+    /// Short name of the format family, such as `uint16` or `fixstr`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Format::PositiveFixInt(_) => "positive fixint",
+            Format::FixMap(_) => "fixmap",
+            Format::FixArray(_) => "fixarray",
+            Format::FixStr(_) => "fixstr",
+            Format::Nil => "nil",
+            Format::NeverUsed => "(never used)",
+            Format::False => "false",
+            Format::True => "true",
+            Format::Bin8 => "bin8",
+            Format::Bin16 => "bin16",
+            Format::Bin32 => "bin32",
+            Format::Ext8 => "ext8",
+            Format::Ext16 => "ext16",
+            Format::Ext32 => "ext32",
+            Format::Float32 => "float32",
+            Format::Float64 => "float64",
+            Format::Uint8 => "uint8",
+            Format::Uint16 => "uint16",
+            Format::Uint32 => "uint32",
+            Format::Uint64 => "uint64",
+            Format::Int8 => "int8",
+            Format::Int16 => "int16",
+            Format::Int32 => "int32",
+            Format::Int64 => "int64",
+            Format::FixExt1 => "fixext1",
+            Format::FixExt2 => "fixext2",
+            Format::FixExt4 => "fixext4",
+            Format::FixExt8 => "fixext8",
+            Format::FixExt16 => "fixext16",
+            Format::Str8 => "str8",
+            Format::Str16 => "str16",
+            Format::Str32 => "str32",
+            Format::Array16 => "array16",
+            Format::Array32 => "array32",
+            Format::Map16 => "map16",
+            Format::Map32 => "map32",
+            Format::NegativeFixInt(_) => "negative fixint",
+        }
+    }
+
     /// Parse a marker byte into a [`Format`] value.
     pub const fn from_byte(byte: u8) -> Self {
         match byte {
@@ -7,6 +7,23 @@ pub trait IoWrite {
     type Error: core::error::Error;
     /// Write all bytes from `buf`.
     fn write(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+
+    /// Account for `len` bytes without producing them, if this writer only
+    /// measures its output.
+    ///
+    /// Only the crate's own counting writers return `true`; the token keeps
+    /// the method from being called or overridden elsewhere.
+    #[doc(hidden)]
+    fn measure(&mut self, len: usize, _: private::Token) -> bool {
+        let _ = len;
+        false
+    }
+}
+
+pub(crate) mod private {
+    /// Proof that [`IoWrite::measure`](super::IoWrite::measure) is called
+    /// from within this crate.
+    pub struct Token(pub(crate) ());
 }
 
 /// `SliceWriter` Error
@@ -166,6 +183,13 @@ pub trait IoRead<'de> {
     type Error: core::error::Error + 'static;
     /// read exactly `len` bytes and consume
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a>, Self::Error>;
+
+    /// Return the unread input if the reader borrows from one contiguous buffer.
+    ///
+    /// Readers which copy data into a transient buffer return `None`.
+    fn remaining_slice(&self) -> Option<&'de [u8]> {
+        None
+    }
 }
 
 /// Simple reader that reads from a byte slice.
@@ -214,6 +238,11 @@ impl<'de> IoRead<'de> for SliceReader<'de> {
         self.cursor = rest;
         Ok(Reference::Borrowed(read))
     }
+
+    #[inline]
+    fn remaining_slice(&self) -> Option<&'de [u8]> {
+        Some(self.cursor)
+    }
 }
 
 #[cfg(feature = "alloc")]
@@ -340,6 +369,15 @@ mod tests {
         assert_eq!(reader.rest(), &[]);
     }
 
+    #[test]
+    fn slice_reader_remaining_slice_tracks_cursor() {
+        let input: &[u8] = &[1, 2, 3];
+        let mut reader = SliceReader::new(input);
+        assert_eq!(reader.remaining_slice(), Some(input));
+        reader.read_slice(1).unwrap();
+        assert_eq!(reader.remaining_slice(), Some(&input[1..]));
+    }
+
     #[test]
     fn slice_reader_returns_error_on_overshoot() {
         // Arrange
@@ -7,12 +7,15 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+pub mod debug;
 pub mod decode;
 pub mod encode;
 pub mod extension;
 mod formats;
 pub mod io;
+pub mod notation;
 pub mod timestamp;
+pub mod value;
 
 pub use decode::Decode;
 pub use encode::Encode;
@@ -0,0 +1,77 @@
+//! Human readable text notation for MessagePack.
+//!
+//! The notation is a superset of JSON which keeps the distinctions JSON
+//! cannot express:
+//!
+//! | MessagePack          | Notation                               |
+//! |----------------------|----------------------------------------|
+//! | nil                  | `nil`                                  |
+//! | bool                 | `true`, `false`                        |
+//! | int                  | `1`, `-32`                             |
+//! | float 64             | `1.0`, `1.5e300`, `NaN`, `-Infinity`   |
+//! | float 32             | `f32(1.5)`                             |
+//! | str                  | `"text"` with JSON escapes             |
+//! | bin                  | `h'01ff'`                              |
+//! | ext                  | `ext(5, h'01ff')`                      |
+//! | timestamp            | `timestamp32(1700000000)`, `timestamp64(1700000000, 5)`, `timestamp96(-1, 0)` |
+//! | array                | `[1, 2]`                               |
+//! | map                  | `{"a": 1, 2: nil}`, keys of any type   |
+//!
+//! Floats are always printed with a fraction or exponent so they never read
+//! as ints. Timestamps are printed only for well formed ext `-1` payloads and
+//! name their layout, so printing and parsing again reproduces the bytes up to
+//! int and length minimization.
+//!
+//! [`write()`] prints encoded bytes without allocating, and
+//! [`Value`](crate::value::Value) and [`ValueRef`](crate::value::ValueRef)
+//! implement [`Display`](core::fmt::Display) using this notation. The
+//! alternate flag (`{:#}`) selects [`Style::Pretty`].
+//!
+//! With the `alloc` feature, [`parse`] and [`to_bytes`] read the notation back.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")]
+//! # fn main() {
+//! use messagepack_core::{io::SliceReader, notation};
+//!
+//! let buf = notation::to_bytes(r#"{"id": 1, "blob": h'00ff', "at": timestamp32(0)}"#).unwrap();
+//!
+//! let mut text = String::new();
+//! notation::write(&mut SliceReader::new(&buf), &mut text, notation::Style::Compact).unwrap();
+//! assert_eq!(text, r#"{"id": 1, "blob": h'00ff', "at": timestamp32(0)}"#);
+//! # }
+//! # #[cfg(not(feature = "alloc"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(feature = "alloc")]
+mod parse;
+mod print;
+
+#[cfg(feature = "alloc")]
+pub use parse::{ParseError, parse, to_bytes};
+pub use print::{Emitter, PrintError, write};
+
+/// Layout of the printed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// Everything on one line, e.g. `{"a": [1, 2]}`.
+    #[default]
+    Compact,
+    /// One element per line, indented by two spaces.
+    Pretty,
+}
+
+impl Style {
+    /// [`Style::Pretty`] when the formatter has the alternate flag (`{:#}`),
+    /// [`Style::Compact`] otherwise.
+    pub fn of(f: &core::fmt::Formatter<'_>) -> Self {
+        if f.alternate() {
+            Style::Pretty
+        } else {
+            Style::Compact
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use core::fmt;
+
+use crate::{
+    Encode,
+    extension::{ExtensionOwned, FixedExtension},
+    timestamp::{Timestamp32, Timestamp64, Timestamp96},
+    value::{Number, Value},
+};
+use alloc::{string::String, vec::Vec};
+
+/// Maximum nesting depth of arrays and maps accepted while parsing.
+const MAX_DEPTH: usize = 256;
+
+/// Error returned when text is not valid [notation](crate::notation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    offset: usize,
+    message: &'static str,
+}
+
+impl ParseError {
+    /// Byte offset into the input where the error was detected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Parse one value written in the notation.
+///
+/// Surrounding whitespace is allowed, anything else after the value is an
+/// error. Arrays and maps may end with a trailing comma.
+///
+/// ```rust
+/// use messagepack_core::{notation, value::Value};
+///
+/// let v = notation::parse(r#"[1, "a", h'ff']"#).unwrap();
+/// assert_eq!(
+///     v,
+///     Value::Array(vec![Value::from(1u8), Value::from("a"), Value::Bin(vec![0xff])])
+/// );
+/// ```
+pub fn parse(text: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser { text, pos: 0 };
+    let v = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != text.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(v)
+}
+
+/// Parse one value written in the notation and encode it.
+///
+/// Ints and lengths use their smallest encoding.
+///
+/// ```rust
+/// use messagepack_core::notation;
+///
+/// let buf = notation::to_bytes("{1: ext(5, h'00')}").unwrap();
+/// assert_eq!(buf, [0x81, 0x01, 0xd4, 0x05, 0x00]);
+/// ```
+pub fn to_bytes(text: &str) -> Result<Vec<u8>, ParseError> {
+    let v = parse(text)?;
+    let mut buf = Vec::new();
+    v.encode(&mut buf).map_err(|_| ParseError {
+        offset: 0,
+        message: "value cannot be encoded",
+    })?;
+    Ok(buf)
+}
+
+impl core::str::FromStr for Value {
+    type Err = ParseError;
+
+    /// Parse the [notation](crate::notation), see [`parse`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `c` after optional whitespace.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8, message: &'static str) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    /// Take the longest run of bytes matching `f`.
+    fn take_while(&mut self, f: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.pos += 1;
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'[') => {
+                let depth = self.enter(depth)?;
+                self.pos += 1;
+                let mut items = Vec::new();
+                while !self.eat(b']') {
+                    items.push(self.value(depth)?);
+                    if !self.eat(b',') {
+                        self.expect(b']', "expected `,` or `]`")?;
+                        break;
+                    }
+                }
+                Ok(Value::Array(items))
+            }
+            Some(b'{') => {
+                let depth = self.enter(depth)?;
+                self.pos += 1;
+                let mut entries = Vec::new();
+                while !self.eat(b'}') {
+                    let k = self.value(depth)?;
+                    self.expect(b':', "expected `:`")?;
+                    let v = self.value(depth)?;
+                    entries.push((k, v));
+                    if !self.eat(b',') {
+                        self.expect(b'}', "expected `,` or `}`")?;
+                        break;
+                    }
+                }
+                Ok(Value::Map(entries))
+            }
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.number().map(Value::Number),
+            Some(c) if c.is_ascii_alphabetic() => self.word(),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn enter(&self, depth: usize) -> Result<usize, ParseError> {
+        if depth >= MAX_DEPTH {
+            Err(self.error("nesting too deep"))
+        } else {
+            Ok(depth + 1)
+        }
+    }
+
+    /// Keywords and the function-like forms.
+    fn word(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        let word = self.take_while(|c| c.is_ascii_alphanumeric());
+        let v = match word {
+            "nil" => Value::Nil,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "NaN" => Value::Number(Number::Float64(f64::NAN)),
+            "Infinity" => Value::Number(Number::Float64(f64::INFINITY)),
+            "h" => Value::Bin(self.hex()?),
+            "f32" => {
+                self.expect(b'(', "expected `(`")?;
+                let v = self.float::<f32>()?;
+                self.expect(b')', "expected `)`")?;
+                Value::Number(Number::Float32(v))
+            }
+            "ext" => {
+                self.expect(b'(', "expected `(`")?;
+                let r#type = self.int::<i8>()?;
+                self.expect(b',', "expected `,`")?;
+                self.skip_whitespace();
+                if self.take_while(|c| c.is_ascii_alphabetic()) != "h" {
+                    return Err(self.error("expected ext data as `h'..'`"));
+                }
+                let data = self.hex()?;
+                self.expect(b')', "expected `)`")?;
+                Value::Extension(ExtensionOwned::new(r#type, data))
+            }
+            "timestamp32" => {
+                self.expect(b'(', "expected `(`")?;
+                let seconds = self.int::<u32>()?;
+                self.expect(b')', "expected `)`")?;
+                ExtensionOwned::from(FixedExtension::from(Timestamp32::new(seconds))).into()
+            }
+            "timestamp64" => {
+                self.expect(b'(', "expected `(`")?;
+                let seconds = self.int::<u64>()?;
+                self.expect(b',', "expected `,`")?;
+                let nanos = self.int::<u32>()?;
+                self.expect(b')', "expected `)`")?;
+                let ts = Timestamp64::new(seconds, nanos).map_err(|_| ParseError {
+                    offset: start,
+                    message: "timestamp out of range",
+                })?;
+                ExtensionOwned::from(FixedExtension::from(ts)).into()
+            }
+            "timestamp96" => {
+                self.expect(b'(', "expected `(`")?;
+                let seconds = self.int::<i64>()?;
+                self.expect(b',', "expected `,`")?;
+                let nanos = self.int::<u32>()?;
+                self.expect(b')', "expected `)`")?;
+                let ts = Timestamp96::new(seconds, nanos).map_err(|_| ParseError {
+                    offset: start,
+                    message: "timestamp out of range",
+                })?;
+                ExtensionOwned::from(FixedExtension::from(ts)).into()
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error("unknown keyword"));
+            }
+        };
+        Ok(v)
+    }
+
+    /// Body of `h'..'`, after the `h`.
+    fn hex(&mut self) -> Result<Vec<u8>, ParseError> {
+        if self.peek() != Some(b'\'') {
+            return Err(self.error("expected `'`"));
+        }
+        self.pos += 1;
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_hexdigit());
+        if !digits.len().is_multiple_of(2) {
+            self.pos = start;
+            return Err(self.error("odd number of hex digits"));
+        }
+        if self.peek() != Some(b'\'') {
+            return Err(self.error("expected hex digit or `'`"));
+        }
+        self.pos += 1;
+        Ok(digits
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                let hi = (pair[0] as char).to_digit(16).unwrap_or_default();
+                let lo = (pair[1] as char).to_digit(16).unwrap_or_default();
+                (hi * 16 + lo) as u8
+            })
+            .collect())
+    }
+
+    /// Numeric token, including a leading `-` and the `Infinity` form.
+    fn numeric_token(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, b'.' | b'+' | b'-'));
+        &self.text[start..self.pos]
+    }
+
+    fn number(&mut self) -> Result<Number, ParseError> {
+        let start = self.pos;
+        let token = self.numeric_token();
+        let is_float = token.contains(['.', 'e', 'E']) || token.ends_with("Infinity");
+        let parsed = if is_float {
+            parse_float::<f64>(token).map(Number::Float64)
+        } else if token.starts_with('-') {
+            token.parse::<i64>().ok().map(Number::from)
+        } else {
+            token.parse::<u64>().ok().map(Number::from)
+        };
+        parsed.ok_or(ParseError {
+            offset: start,
+            message: "invalid number",
+        })
+    }
+
+    fn int<T: core::str::FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        self.numeric_token().parse().map_err(|_| ParseError {
+            offset: start,
+            message: "invalid integer",
+        })
+    }
+
+    fn float<T: core::str::FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        parse_float(self.numeric_token()).ok_or(ParseError {
+            offset: start,
+            message: "invalid float",
+        })
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(c) = rest.chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    out.push(self.escape()?);
+                }
+                c if u32::from(c) < 0x20 => return Err(self.error("control character in string")),
+                c => {
+                    self.pos += c.len_utf8();
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    /// Escape sequence after the backslash.
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("unterminated string"));
+        };
+        self.pos += 1;
+        let c = match c {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let hi = self.hex4()?;
+                let code = if (0xd800..0xdc00).contains(&hi) {
+                    if !self.text[self.pos..].starts_with("\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let lo = self.hex4()?;
+                    if !(0xdc00..0xe000).contains(&lo) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+                } else {
+                    hi
+                };
+                char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("invalid escape"));
+            }
+        };
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|s| s.bytes().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected 4 hex digits"))?;
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| self.error("expected 4 hex digits"))
+    }
+}
+
+/// Parse a float token, mapping the notation's special values.
+fn parse_float<T: core::str::FromStr>(token: &str) -> Option<T> {
+    let token = match token {
+        "NaN" => "NaN",
+        "Infinity" => "inf",
+        "-Infinity" => "-inf",
+        // Rust also accepts words such as `inf`, which are not notation.
+        t if t
+            .bytes()
+            .any(|c| c.is_ascii_alphabetic() && !matches!(c, b'e' | b'E')) =>
+        {
+            return None;
+        }
+        t => t,
+    };
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decode, io::SliceReader, notation};
+    use alloc::format;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("nil", &[0xc0])]
+    #[case(" true ", &[0xc3])]
+    #[case("256", &[0xcd, 0x01, 0x00])]
+    #[case("-1", &[0xff])]
+    #[case("18446744073709551615", &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])]
+    #[case("1.0", &[0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0])]
+    #[case("1e0", &[0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0])]
+    #[case("-Infinity", &[0xcb, 0xff, 0xf0, 0, 0, 0, 0, 0, 0])]
+    #[case("f32(1.5)", &[0xca, 0x3f, 0xc0, 0x00, 0x00])]
+    #[case("f32(NaN)", &[0xca, 0x7f, 0xc0, 0x00, 0x00])]
+    #[case(r#""a\"é😀""#, &[0xa8, 0x61, 0x22, 0xc3, 0xa9, 0xf0, 0x9f, 0x98, 0x80])]
+    #[case("h'0AfF'", &[0xc4, 0x02, 0x0a, 0xff])]
+    #[case("ext(-5, h'')", &[0xc7, 0x00, 0xfb])]
+    #[case("timestamp32(1)", &[0xd6, 0xff, 0x00, 0x00, 0x00, 0x01])]
+    #[case("timestamp64(1, 1)", &[0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01])]
+    #[case("[1, [], {},]", &[0x93, 0x01, 0x90, 0x80])]
+    #[case("{nil: [true], \"k\": h'00'}", &[0x82, 0xc0, 0x91, 0xc3, 0xa1, 0x6b, 0xc4, 0x01, 0x00])]
+    fn parse_to_bytes(#[case] text: &str, #[case] expected: &[u8]) {
+        assert_eq!(to_bytes(text).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("", 0)]
+    #[case("[1 2]", 3)]
+    #[case("{1}", 2)]
+    #[case("nul", 0)]
+    #[case("h'abc'", 2)]
+    #[case("h'zz'", 2)]
+    #[case("256 1", 4)]
+    #[case("18446744073709551616", 0)]
+    #[case("ext(128, h'')", 4)]
+    #[case("timestamp64(1, 1000000000)", 0)]
+    #[case("\"abc", 4)]
+    #[case(r#""\x""#, 2)]
+    #[case("inf", 0)]
+    fn parse_errors(#[case] text: &str, #[case] offset: usize) {
+        assert_eq!(parse(text).unwrap_err().offset(), offset);
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0xce, 0x00, 0x00, 0x00, 0x05, 0xca, 0x3f, 0xc0, 0x00, 0x00])]
+    #[case(&[0x81, 0xa2, 0x0a, 0x09, 0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a])]
+    #[case(&[0xc7, 0x0c, 0xff, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2])]
+    #[case(&[0xcb, 0x7e, 0x37, 0xe4, 0x3c, 0x88, 0x00, 0x75, 0x9c])]
+    fn print_then_parse(#[case] buf: &[u8]) {
+        let mut text = String::new();
+        notation::write(
+            &mut SliceReader::new(buf),
+            &mut text,
+            notation::Style::Pretty,
+        )
+        .unwrap();
+        let v: Value = text.parse().unwrap();
+
+        let expected = Value::decode(&mut SliceReader::new(buf)).unwrap();
+        assert_eq!(v, expected, "{text}");
+        assert_eq!(format!("{v}"), format!("{expected}"));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let text = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(parse(&text).unwrap_err().offset(), MAX_DEPTH);
+        assert!(
+            parse(&format!(
+                "{}{}",
+                "[".repeat(MAX_DEPTH),
+                "]".repeat(MAX_DEPTH)
+            ))
+            .is_ok()
+        );
+    }
+}
@@ -0,0 +1,567 @@
+use core::fmt::{self, Write};
+
+use super::Style;
+use crate::{
+    Format,
+    decode::{
+        Decode, DecodeBorrowed, Error as DecodeError, NbyteReader, ReferenceDecoder,
+        ReferenceStrDecoder,
+    },
+    extension::{ExtensionRef, read_ext_header},
+    io::IoRead,
+    timestamp::{Timestamp32, Timestamp64, Timestamp96},
+};
+
+/// Maximum nesting depth of arrays and maps accepted while printing bytes.
+const MAX_DEPTH: usize = 256;
+
+/// Error returned by [`write()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrintError<E> {
+    /// The input is not valid MessagePack.
+    Input(DecodeError<E>),
+    /// The output rejected the text.
+    Fmt(fmt::Error),
+}
+
+impl<E> From<DecodeError<E>> for PrintError<E> {
+    fn from(e: DecodeError<E>) -> Self {
+        PrintError::Input(e)
+    }
+}
+
+impl<E> From<fmt::Error> for PrintError<E> {
+    fn from(e: fmt::Error) -> Self {
+        PrintError::Fmt(e)
+    }
+}
+
+impl<E> fmt::Display for PrintError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintError::Input(e) => e.fmt(f),
+            PrintError::Fmt(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E> core::error::Error for PrintError<E>
+where
+    E: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            PrintError::Input(e) => Some(e),
+            PrintError::Fmt(e) => Some(e),
+        }
+    }
+}
+
+/// Writes notation tokens and tracks indentation.
+///
+/// This is the building block behind [`write()`] and the
+/// [`Display`](core::fmt::Display) impls of the dynamic values. Other value
+/// trees can drive it directly to print themselves without first converting
+/// to [`ValueRef`](crate::value::ValueRef).
+///
+/// Containers are written as [`open`](Self::open), then
+/// [`element`](Self::element) before each item (and
+/// [`key_separator`](Self::key_separator) between a map key and its value),
+/// then [`close`](Self::close) with the same length.
+///
+/// ```rust
+/// use messagepack_core::notation::{Emitter, Style};
+///
+/// let mut text = String::new();
+/// let mut emitter = Emitter::new(&mut text, Style::Compact);
+/// emitter.open('{', 1).unwrap();
+/// emitter.element(0).unwrap();
+/// emitter.str("a").unwrap();
+/// emitter.key_separator().unwrap();
+/// emitter.bin(&[0x01]).unwrap();
+/// emitter.close('}', 1).unwrap();
+/// assert_eq!(text, r#"{"a": h'01'}"#);
+/// ```
+pub struct Emitter<'w, W> {
+    out: &'w mut W,
+    style: Style,
+    indent: usize,
+}
+
+impl<'w, W: Write> Emitter<'w, W> {
+    /// Create an emitter writing into `out`.
+    pub fn new(out: &'w mut W, style: Style) -> Self {
+        Self {
+            out,
+            style,
+            indent: 0,
+        }
+    }
+
+    /// Write `nil`.
+    pub fn nil(&mut self) -> fmt::Result {
+        self.out.write_str("nil")
+    }
+
+    /// Write `true` or `false`.
+    pub fn bool(&mut self, v: bool) -> fmt::Result {
+        self.out.write_str(if v { "true" } else { "false" })
+    }
+
+    /// Write an unsigned int.
+    pub fn uint(&mut self, v: u64) -> fmt::Result {
+        write!(self.out, "{v}")
+    }
+
+    /// Write a signed int.
+    pub fn int(&mut self, v: i64) -> fmt::Result {
+        write!(self.out, "{v}")
+    }
+
+    /// Write a float 32 as `f32(..)`.
+    pub fn f32(&mut self, v: f32) -> fmt::Result {
+        self.out.write_str("f32(")?;
+        self.float(v.into(), v.is_finite(), |out| write!(out, "{v:?}"))?;
+        self.out.write_char(')')
+    }
+
+    /// Write a float 64, always with a fraction or exponent.
+    pub fn f64(&mut self, v: f64) -> fmt::Result {
+        self.float(v, v.is_finite(), |out| write!(out, "{v:?}"))
+    }
+
+    fn float(
+        &mut self,
+        v: f64,
+        finite: bool,
+        finite_repr: impl FnOnce(&mut W) -> fmt::Result,
+    ) -> fmt::Result {
+        // `Debug` always prints a fraction or exponent, unlike `Display`.
+        if finite {
+            finite_repr(self.out)
+        } else if v.is_nan() {
+            self.out.write_str("NaN")
+        } else if v.is_sign_positive() {
+            self.out.write_str("Infinity")
+        } else {
+            self.out.write_str("-Infinity")
+        }
+    }
+
+    /// Write a quoted string with JSON escapes.
+    pub fn str(&mut self, v: &str) -> fmt::Result {
+        self.out.write_char('"')?;
+        for c in v.chars() {
+            match c {
+                '"' => self.out.write_str("\\\"")?,
+                '\\' => self.out.write_str("\\\\")?,
+                '\n' => self.out.write_str("\\n")?,
+                '\r' => self.out.write_str("\\r")?,
+                '\t' => self.out.write_str("\\t")?,
+                c if u32::from(c) < 0x20 || c == '\u{7f}' => {
+                    write!(self.out, "\\u{:04x}", u32::from(c))?
+                }
+                c => self.out.write_char(c)?,
+            }
+        }
+        self.out.write_char('"')
+    }
+
+    /// Write bytes as `h'..'`.
+    pub fn bin(&mut self, v: &[u8]) -> fmt::Result {
+        self.out.write_str("h'")?;
+        for b in v {
+            write!(self.out, "{b:02x}")?;
+        }
+        self.out.write_char('\'')
+    }
+
+    /// Write an ext, or a `timestamp..(..)` for well formed timestamps.
+    pub fn ext(&mut self, ext: ExtensionRef<'_>) -> fmt::Result {
+        if ext.r#type == crate::timestamp::TIMESTAMP_EXTENSION_TYPE {
+            match ext.data.len() {
+                4 => {
+                    if let Ok(ts) = Timestamp32::try_from(ext) {
+                        return write!(self.out, "timestamp32({})", ts.seconds());
+                    }
+                }
+                8 => {
+                    if let Ok(ts) = Timestamp64::try_from(ext) {
+                        return write!(self.out, "timestamp64({}, {})", ts.seconds(), ts.nanos());
+                    }
+                }
+                12 => {
+                    if let Ok(ts) = Timestamp96::try_from(ext) {
+                        return write!(self.out, "timestamp96({}, {})", ts.seconds(), ts.nanos());
+                    }
+                }
+                _ => {}
+            }
+        }
+        write!(self.out, "ext({}, ", ext.r#type)?;
+        self.bin(ext.data)?;
+        self.out.write_char(')')
+    }
+
+    /// Open a container which will hold `len` elements.
+    pub fn open(&mut self, bracket: char, len: usize) -> fmt::Result {
+        self.out.write_char(bracket)?;
+        if len > 0 {
+            self.indent += 1;
+        }
+        Ok(())
+    }
+
+    /// Separator written before the `index`-th element.
+    pub fn element(&mut self, index: usize) -> fmt::Result {
+        if index > 0 {
+            self.out.write_char(',')?;
+        }
+        match self.style {
+            Style::Compact if index > 0 => self.out.write_char(' '),
+            Style::Compact => Ok(()),
+            Style::Pretty => self.newline(),
+        }
+    }
+
+    /// Separator written between a map key and its value.
+    pub fn key_separator(&mut self) -> fmt::Result {
+        self.out.write_str(": ")
+    }
+
+    /// Close a container which held `len` elements.
+    pub fn close(&mut self, bracket: char, len: usize) -> fmt::Result {
+        if len > 0 {
+            self.indent -= 1;
+            if self.style == Style::Pretty {
+                self.newline()?;
+            }
+        }
+        self.out.write_char(bracket)
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.out.write_char('\n')?;
+        for _ in 0..self.indent {
+            self.out.write_str("  ")?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the next encoded value from `reader` as text into `out`.
+///
+/// Exactly one value is consumed. Strings must be valid UTF-8.
+///
+/// ```rust
+/// use messagepack_core::{io::SliceReader, notation::{self, Style}};
+///
+/// let buf: &[u8] = &[0x82, 0xa1, 0x61, 0x92, 0x01, 0xc0, 0xa1, 0x62, 0xc4, 0x01, 0xff];
+///
+/// let mut text = String::new();
+/// notation::write(&mut SliceReader::new(buf), &mut text, Style::Pretty).unwrap();
+/// assert_eq!(text, "{\n  \"a\": [\n    1,\n    nil\n  ],\n  \"b\": h'ff'\n}");
+/// ```
+pub fn write<'de, R, W>(
+    reader: &mut R,
+    out: &mut W,
+    style: Style,
+) -> Result<(), PrintError<R::Error>>
+where
+    R: IoRead<'de>,
+    W: Write,
+{
+    let mut emitter = Emitter::new(out, style);
+    let format = Format::decode_borrowed(reader)?;
+    print_encoded(format, reader, &mut emitter, 0)
+}
+
+fn print_encoded<'de, R, W>(
+    format: Format,
+    reader: &mut R,
+    emitter: &mut Emitter<'_, W>,
+    depth: usize,
+) -> Result<(), PrintError<R::Error>>
+where
+    R: IoRead<'de>,
+    W: Write,
+{
+    macro_rules! number {
+        ($ty:ty, $emit:ident) => {{
+            let v = <$ty>::decode_borrowed_with_format(format, reader)?;
+            emitter.$emit(v.into())?
+        }};
+    }
+
+    match format {
+        Format::Nil => emitter.nil()?,
+        Format::False => emitter.bool(false)?,
+        Format::True => emitter.bool(true)?,
+        Format::PositiveFixInt(v) => emitter.uint(v.into())?,
+        Format::Uint8 => number!(u8, uint),
+        Format::Uint16 => number!(u16, uint),
+        Format::Uint32 => number!(u32, uint),
+        Format::Uint64 => number!(u64, uint),
+        Format::NegativeFixInt(v) => emitter.int(v.into())?,
+        Format::Int8 => number!(i8, int),
+        Format::Int16 => number!(i16, int),
+        Format::Int32 => number!(i32, int),
+        Format::Int64 => number!(i64, int),
+        Format::Float32 => number!(f32, f32),
+        Format::Float64 => number!(f64, f64),
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+            let s = ReferenceStrDecoder::decode_with_format(format, reader)?;
+            emitter.str(s.as_str())?
+        }
+        Format::Bin8 | Format::Bin16 | Format::Bin32 => {
+            let b = ReferenceDecoder::decode_with_format(format, reader)?;
+            emitter.bin(b.as_bytes())?
+        }
+        Format::FixExt1
+        | Format::FixExt2
+        | Format::FixExt4
+        | Format::FixExt8
+        | Format::FixExt16
+        | Format::Ext8
+        | Format::Ext16
+        | Format::Ext32 => {
+            let (len, r#type) = read_ext_header(format, reader)?;
+            let data = reader.read_slice(len).map_err(DecodeError::Io)?;
+            emitter.ext(ExtensionRef::new(r#type, data.as_bytes()))?
+        }
+        Format::FixArray(_) | Format::Array16 | Format::Array32 => {
+            let depth = enter(depth)?;
+            let len = match format {
+                Format::FixArray(n) => n.into(),
+                Format::Array16 => NbyteReader::<2>::read(reader)?,
+                _ => NbyteReader::<4>::read(reader)?,
+            };
+            emitter.open('[', len)?;
+            for i in 0..len {
+                emitter.element(i)?;
+                let format = Format::decode_borrowed(reader)?;
+                print_encoded(format, reader, emitter, depth)?;
+            }
+            emitter.close(']', len)?
+        }
+        Format::FixMap(_) | Format::Map16 | Format::Map32 => {
+            let depth = enter(depth)?;
+            let len = match format {
+                Format::FixMap(n) => n.into(),
+                Format::Map16 => NbyteReader::<2>::read(reader)?,
+                _ => NbyteReader::<4>::read(reader)?,
+            };
+            emitter.open('{', len)?;
+            for i in 0..len {
+                emitter.element(i)?;
+                let format = Format::decode_borrowed(reader)?;
+                print_encoded(format, reader, emitter, depth)?;
+                emitter.key_separator()?;
+                let format = Format::decode_borrowed(reader)?;
+                print_encoded(format, reader, emitter, depth)?;
+            }
+            emitter.close('}', len)?
+        }
+        Format::NeverUsed => return Err(DecodeError::UnexpectedFormat.into()),
+    };
+    Ok(())
+}
+
+fn enter<E>(depth: usize) -> Result<usize, PrintError<E>> {
+    if depth >= MAX_DEPTH {
+        Err(DecodeError::InvalidData.into())
+    } else {
+        Ok(depth + 1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod value {
+    use super::*;
+    use crate::value::{Number, Value, ValueRef};
+
+    /// One level of a value tree, so [`emit_value`] can walk [`Value`] and
+    /// [`ValueRef`] without converting one into the other.
+    enum Node<'a, T> {
+        Nil,
+        Bool(bool),
+        Bin(&'a [u8]),
+        Extension(ExtensionRef<'a>),
+        Number(Number),
+        String(&'a str),
+        Array(&'a [T]),
+        Map(&'a [(T, T)]),
+    }
+
+    trait Tree: Sized {
+        fn node(&self) -> Node<'_, Self>;
+    }
+
+    impl Tree for ValueRef<'_> {
+        fn node(&self) -> Node<'_, Self> {
+            match self {
+                ValueRef::Nil => Node::Nil,
+                ValueRef::Bool(b) => Node::Bool(*b),
+                ValueRef::Bin(b) => Node::Bin(b),
+                ValueRef::Extension(ext) => Node::Extension(*ext),
+                ValueRef::Number(n) => Node::Number(*n),
+                ValueRef::String(s) => Node::String(s),
+                ValueRef::Array(items) => Node::Array(items),
+                ValueRef::Map(entries) => Node::Map(entries),
+            }
+        }
+    }
+
+    impl Tree for Value {
+        fn node(&self) -> Node<'_, Self> {
+            match self {
+                Value::Nil => Node::Nil,
+                Value::Bool(b) => Node::Bool(*b),
+                Value::Bin(b) => Node::Bin(b),
+                Value::Extension(ext) => Node::Extension(ext.as_ref()),
+                Value::Number(n) => Node::Number(*n),
+                Value::String(s) => Node::String(s),
+                Value::Array(items) => Node::Array(items),
+                Value::Map(entries) => Node::Map(entries),
+            }
+        }
+    }
+
+    fn emit_value<T: Tree, W: Write>(v: &T, emitter: &mut Emitter<'_, W>) -> fmt::Result {
+        match v.node() {
+            Node::Nil => emitter.nil(),
+            Node::Bool(b) => emitter.bool(b),
+            Node::Bin(b) => emitter.bin(b),
+            Node::Extension(ext) => emitter.ext(ext),
+            Node::Number(Number::PositiveInt(n)) => emitter.uint(n),
+            Node::Number(Number::NegativeInt(n)) => emitter.int(n),
+            Node::Number(Number::Float32(n)) => emitter.f32(n),
+            Node::Number(Number::Float64(n)) => emitter.f64(n),
+            Node::String(s) => emitter.str(s),
+            Node::Array(items) => {
+                emitter.open('[', items.len())?;
+                for (i, item) in items.iter().enumerate() {
+                    emitter.element(i)?;
+                    emit_value(item, emitter)?;
+                }
+                emitter.close(']', items.len())
+            }
+            Node::Map(entries) => {
+                emitter.open('{', entries.len())?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    emitter.element(i)?;
+                    emit_value(k, emitter)?;
+                    emitter.key_separator()?;
+                    emit_value(v, emitter)?;
+                }
+                emitter.close('}', entries.len())
+            }
+        }
+    }
+
+    /// Formats the value in the [notation](crate::notation). Use `{:#}` for
+    /// the pretty printed layout.
+    impl fmt::Display for ValueRef<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let style = Style::of(f);
+            emit_value(self, &mut Emitter::new(f, style))
+        }
+    }
+
+    /// Formats the value in the [notation](crate::notation). Use `{:#}` for
+    /// the pretty printed layout.
+    impl fmt::Display for Value {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let style = Style::of(f);
+            emit_value(self, &mut Emitter::new(f, style))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::SliceReader;
+    use rstest::rstest;
+
+    fn print(buf: &[u8], style: Style) -> String {
+        let mut reader = SliceReader::new(buf);
+        let mut out = String::new();
+        write(&mut reader, &mut out, style).unwrap();
+        assert!(reader.rest().is_empty());
+        out
+    }
+
+    #[rstest]
+    #[case(&[0xc0], "nil")]
+    #[case(&[0xc2], "false")]
+    #[case(&[0xcd, 0x01, 0x00], "256")]
+    #[case(&[0xe0], "-32")]
+    #[case(&[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0], "-9223372036854775808")]
+    #[case(&[0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0], "1.0")]
+    #[case(&[0xca, 0x3f, 0xc0, 0x00, 0x00], "f32(1.5)")]
+    #[case(&[0xcb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0], "NaN")]
+    #[case(&[0xcb, 0xff, 0xf0, 0, 0, 0, 0, 0, 0], "-Infinity")]
+    #[case(&[0xa4, 0x61, 0x22, 0x0a, 0x01], r#""a\"\n\u0001""#)]
+    #[case(&[0xc4, 0x00], "h''")]
+    #[case(&[0xc4, 0x02, 0x0a, 0xff], "h'0aff'")]
+    #[case(&[0xd5, 0x05, 0x01, 0x02], "ext(5, h'0102')")]
+    #[case(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x01], "timestamp32(1)")]
+    #[case(&[0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01], "timestamp64(1, 1)")]
+    #[case(&[0xc7, 0x0c, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], "timestamp96(-1, 0)")]
+    #[case(&[0xd4, 0xff, 0x00], "ext(-1, h'00')")]
+    #[case(&[0x90], "[]")]
+    #[case(&[0x80], "{}")]
+    #[case(&[0x92, 0x01, 0x91, 0x02], "[1, [2]]")]
+    #[case(&[0x82, 0x01, 0xc0, 0xa1, 0x6b, 0x80], r#"{1: nil, "k": {}}"#)]
+    fn compact(#[case] buf: &[u8], #[case] expected: &str) {
+        assert_eq!(print(buf, Style::Compact), expected);
+    }
+
+    #[test]
+    fn pretty() {
+        let buf: &[u8] = &[0x82, 0xa1, 0x61, 0x92, 0x01, 0x90, 0xa1, 0x62, 0x80];
+        assert_eq!(
+            print(buf, Style::Pretty),
+            "{\n  \"a\": [\n    1,\n    []\n  ],\n  \"b\": {}\n}"
+        );
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0x01])]
+    #[case(&[0xa1, 0xff])]
+    #[case(&[0xc1])]
+    fn invalid_input(#[case] buf: &[u8]) {
+        let mut out = String::new();
+        assert!(matches!(
+            write(&mut SliceReader::new(buf), &mut out, Style::Compact),
+            Err(PrintError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let mut buf = std::vec![0x91; MAX_DEPTH + 1];
+        buf.push(0xc0);
+        let mut out = String::new();
+        assert!(write(&mut SliceReader::new(&buf), &mut out, Style::Compact).is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn display_value() {
+        use crate::value::{Number, Value};
+        use alloc::{format, vec};
+
+        let v = Value::Map(vec![(
+            Value::from("a"),
+            Value::Array(vec![Value::Number(Number::Float64(2.0)), Value::Nil]),
+        )]);
+        assert_eq!(format!("{v}"), r#"{"a": [2.0, nil]}"#);
+        assert_eq!(format!("{v:#}"), "{\n  \"a\": [\n    2.0,\n    nil\n  ]\n}");
+        assert_eq!(format!("{}", v.as_value_ref()), r#"{"a": [2.0, nil]}"#);
+    }
+}
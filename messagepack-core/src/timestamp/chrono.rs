@@ -0,0 +1,79 @@
+//! Timestamp conversions for [`chrono::DateTime<Utc>`](::chrono::DateTime).
+
+use ::chrono::{DateTime, Utc};
+
+use super::{ConstructTimestampError, TIMESTAMP_NANO_MAX, Timestamp};
+use crate::{
+    Format,
+    decode::{DecodeBorrowed, Error as DecodeError},
+    encode::{self, Encode},
+    io::{IoRead, IoWrite},
+};
+
+/// A leap second, which chrono stores as nanoseconds past 999_999_999, rolls
+/// over into the next second.
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(value: DateTime<Utc>) -> Self {
+        let secs = value.timestamp();
+        let nanos = value.timestamp_subsec_nanos();
+        if nanos > TIMESTAMP_NANO_MAX {
+            Self {
+                secs: secs + 1,
+                nanos: nanos - (TIMESTAMP_NANO_MAX + 1),
+            }
+        } else {
+            Self { secs, nanos }
+        }
+    }
+}
+
+impl TryFrom<Timestamp> for DateTime<Utc> {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        DateTime::from_timestamp(value.seconds(), value.nanos())
+            .ok_or(ConstructTimestampError::ExceedSeconds)
+    }
+}
+
+impl Encode for DateTime<Utc> {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        Timestamp::from(*self).encode(writer)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for DateTime<Utc> {
+    type Value = DateTime<Utc>;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        let timestamp = Timestamp::decode_borrowed_with_format(format, reader)?;
+        timestamp.try_into().map_err(|_| DecodeError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::chrono::NaiveDate;
+    use rstest::rstest;
+
+    #[rstest]
+    fn leap_second_rolls_over() {
+        let dt = NaiveDate::from_ymd_opt(2016, 12, 31)
+            .unwrap()
+            .and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+            .unwrap()
+            .and_utc();
+        let ts = Timestamp::from(dt);
+        assert_eq!((ts.seconds(), ts.nanos()), (1_483_228_800, 500_000_000));
+    }
+}
@@ -1,6 +1,6 @@
 //! Timestamp decoding implementations and tests.
 
-use super::{Timestamp32, Timestamp64, Timestamp96};
+use super::{Timestamp, Timestamp32, Timestamp64, Timestamp96};
 use crate::{
     Format,
     decode::{DecodeBorrowed, Error as DecodeError},
@@ -70,6 +70,26 @@ impl<'de> DecodeBorrowed<'de> for Timestamp96 {
     }
 }
 
+impl<'de> DecodeBorrowed<'de> for Timestamp {
+    type Value = Timestamp;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        match format {
+            Format::FixExt4 | Format::FixExt8 | Format::Ext8 => {}
+            _ => return Err(DecodeError::UnexpectedFormat),
+        }
+        let ext = FixedExtension::<12>::decode_borrowed_with_format(format, reader)?;
+        let timestamp = ext.try_into().map_err(|_| DecodeError::InvalidData)?;
+        Ok(timestamp)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,4 +283,46 @@ mod tests {
         let err = Timestamp96::decode(&mut r).unwrap_err();
         assert_eq!(err, DecodeError::InvalidData);
     }
+
+    #[test]
+    fn decode_timestamp_from_any_layout() {
+        let mut buf = vec![0xd6, TIMESTAMP_EXT_TYPE_U8];
+        buf.extend_from_slice(&7u32.to_be_bytes());
+        buf.extend_from_slice(&[0xd7, TIMESTAMP_EXT_TYPE_U8]);
+        buf.extend_from_slice(&((789u64 << 34) | 123456).to_be_bytes());
+        buf.extend_from_slice(&[0xc7, 12, TIMESTAMP_EXT_TYPE_U8]);
+        buf.extend_from_slice(&42u32.to_be_bytes());
+        buf.extend_from_slice(&(-123i64).to_be_bytes());
+
+        let mut r = crate::io::SliceReader::new(&buf);
+        let decoded = [
+            Timestamp::decode(&mut r).unwrap(),
+            Timestamp::decode(&mut r).unwrap(),
+            Timestamp::decode(&mut r).unwrap(),
+        ];
+        assert_eq!(
+            decoded.map(|ts| (ts.seconds(), ts.nanos())),
+            [(7, 0), (123456, 789), (-123, 42)]
+        );
+        assert!(r.rest().is_empty());
+    }
+
+    #[test]
+    fn decode_failed_timestamp_invalid_length() {
+        let mut buf = vec![0xc7, 11, TIMESTAMP_EXT_TYPE_U8];
+        buf.extend_from_slice(&[0u8; 11]);
+
+        let mut r = crate::io::SliceReader::new(&buf);
+        let err = Timestamp::decode(&mut r).unwrap_err();
+        assert_eq!(err, DecodeError::InvalidData);
+    }
+
+    #[test]
+    fn decode_failed_timestamp_unexpected_format() {
+        let buf = [0xd5, TIMESTAMP_EXT_TYPE_U8, 0, 0];
+
+        let mut r = crate::io::SliceReader::new(&buf);
+        let err = Timestamp::decode(&mut r).unwrap_err();
+        assert_eq!(err, DecodeError::UnexpectedFormat);
+    }
 }
@@ -1,6 +1,6 @@
 //! Timestamp encoding implementations and tests.
 
-use super::{TIMESTAMP_EXTENSION_TYPE, Timestamp32, Timestamp64, Timestamp96};
+use super::{TIMESTAMP_EXTENSION_TYPE, Timestamp, Timestamp32, Timestamp64, Timestamp96};
 use crate::{
     encode::{self, Encode},
     extension::FixedExtension,
@@ -37,9 +37,19 @@ impl Encode for Timestamp96 {
     }
 }
 
+impl Encode for Timestamp {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        self.to_extension().encode(writer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     const TIMESTAMP_EXT_TYPE_U8: u8 = 255; // -1
 
@@ -86,4 +96,33 @@ mod tests {
         assert_eq!(buf, expected);
         assert_eq!(n, expected.len());
     }
+
+    #[rstest]
+    #[case(0, 0, Timestamp32::new(0).to_buf().to_vec())]
+    #[case(u32::MAX.into(), 0, Timestamp32::new(u32::MAX).to_buf().to_vec())]
+    #[case(1, 1, Timestamp64::new(1, 1).unwrap().to_buf().to_vec())]
+    #[case(u32::MAX as i64 + 1, 0, Timestamp64::new(u32::MAX as u64 + 1, 0).unwrap().to_buf().to_vec())]
+    #[case((1 << 34) - 1, 999_999_999, Timestamp64::new((1 << 34) - 1, 999_999_999).unwrap().to_buf().to_vec())]
+    #[case(1 << 34, 0, Timestamp96::new(1 << 34, 0).unwrap().to_buf().to_vec())]
+    #[case(-1, 0, Timestamp96::new(-1, 0).unwrap().to_buf().to_vec())]
+    fn encode_timestamp_picks_smallest_layout(
+        #[case] seconds: i64,
+        #[case] nanos: u32,
+        #[case] data: Vec<u8>,
+    ) {
+        let ts = Timestamp::new(seconds, nanos).unwrap();
+        let mut buf = vec![];
+
+        let n = Encode::encode(&ts, &mut buf).unwrap();
+
+        let mut expected = match data.len() {
+            4 => vec![0xd6, TIMESTAMP_EXT_TYPE_U8],
+            8 => vec![0xd7, TIMESTAMP_EXT_TYPE_U8],
+            _ => vec![0xc7, 12, TIMESTAMP_EXT_TYPE_U8],
+        };
+        expected.extend_from_slice(&data);
+
+        assert_eq!(buf, expected);
+        assert_eq!(n, expected.len());
+    }
 }
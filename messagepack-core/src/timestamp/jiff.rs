@@ -0,0 +1,73 @@
+//! Timestamp conversions for [`jiff::Timestamp`](::jiff::Timestamp).
+
+use super::{ConstructTimestampError, TIMESTAMP_NANO_MAX, Timestamp};
+use crate::{
+    Format,
+    decode::{DecodeBorrowed, Error as DecodeError},
+    encode::{self, Encode},
+    io::{IoRead, IoWrite},
+};
+
+/// jiff keeps the sign of the nanoseconds in step with the seconds, while the
+/// MessagePack timestamp always counts nanoseconds forward, so instants before
+/// the epoch borrow one second.
+impl From<::jiff::Timestamp> for Timestamp {
+    fn from(value: ::jiff::Timestamp) -> Self {
+        let secs = value.as_second();
+        let nanos = value.subsec_nanosecond();
+        match u32::try_from(nanos) {
+            Ok(nanos) => Self { secs, nanos },
+            Err(_) => Self {
+                secs: secs - 1,
+                nanos: (nanos + TIMESTAMP_NANO_MAX as i32 + 1) as u32,
+            },
+        }
+    }
+}
+
+impl TryFrom<Timestamp> for ::jiff::Timestamp {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        // nanos is at most 999_999_999 and so always fits in `i32`
+        ::jiff::Timestamp::new(value.seconds(), value.nanos() as i32)
+            .map_err(|_| ConstructTimestampError::ExceedSeconds)
+    }
+}
+
+impl Encode for ::jiff::Timestamp {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        Timestamp::from(*self).encode(writer)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for ::jiff::Timestamp {
+    type Value = ::jiff::Timestamp;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        let timestamp = Timestamp::decode_borrowed_with_format(format, reader)?;
+        timestamp.try_into().map_err(|_| DecodeError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn before_epoch_borrows_a_second() {
+        let jiff = ::jiff::Timestamp::new(0, -1).unwrap();
+        let ts = Timestamp::from(jiff);
+        assert_eq!((ts.seconds(), ts.nanos()), (-1, 999_999_999));
+    }
+}
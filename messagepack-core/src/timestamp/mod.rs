@@ -1,7 +1,15 @@
 //! MessagePack timestamp extension values.
 
+#[cfg(feature = "chrono")]
+mod chrono;
 mod decode;
 mod encode;
+#[cfg(feature = "jiff")]
+mod jiff;
+#[cfg(feature = "std")]
+mod system_time;
+#[cfg(feature = "time")]
+mod time;
 use crate::extension::{ExtensionRef, FixedExtension};
 
 pub(crate) const TIMESTAMP_EXTENSION_TYPE: i8 = -1;
@@ -360,6 +368,169 @@ impl TryFrom<core::time::Duration> for Timestamp96 {
     }
 }
 
+impl From<Timestamp32> for Timestamp96 {
+    fn from(value: Timestamp32) -> Self {
+        Self {
+            nanos: 0,
+            secs: value.seconds().into(),
+        }
+    }
+}
+
+impl From<Timestamp64> for Timestamp96 {
+    fn from(value: Timestamp64) -> Self {
+        Self {
+            nanos: value.nanos(),
+            // timestamp 64 seconds are limited to 34 bits
+            secs: value.seconds() as i64,
+        }
+    }
+}
+
+/// A timestamp of any width.
+///
+/// Encodes to the smallest layout which holds it: timestamp 32 when there are
+/// no nanoseconds and the seconds fit in `u32`, timestamp 64 when the seconds
+/// fit in 34 bits, otherwise timestamp 96. Decodes from any of the three.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    secs: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// The UNIX epoch.
+    pub const UNIX_EPOCH: Self = Self { secs: 0, nanos: 0 };
+
+    /// Create a timestamp from signed seconds and nanoseconds.
+    pub fn new(seconds: i64, nanos: u32) -> Result<Self, ConstructTimestampError> {
+        if nanos > TIMESTAMP_NANO_MAX {
+            return Err(ConstructTimestampError::ExceedNanos);
+        }
+        Ok(Self {
+            secs: seconds,
+            nanos,
+        })
+    }
+
+    /// Get seconds since the UNIX epoch.
+    pub fn seconds(&self) -> i64 {
+        self.secs
+    }
+
+    /// Get the nanoseconds component.
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    pub(crate) fn to_extension(self) -> FixedExtension<12> {
+        if self.nanos == 0
+            && let Ok(secs) = u32::try_from(self.secs)
+        {
+            return extension(Timestamp32::new(secs).to_buf());
+        }
+        if let Ok(secs) = u64::try_from(self.secs)
+            && let Ok(ts) = Timestamp64::new(secs, self.nanos)
+        {
+            return extension(ts.to_buf());
+        }
+        extension(Timestamp96::from(self).to_buf())
+    }
+}
+
+fn extension<const N: usize>(data: [u8; N]) -> FixedExtension<12> {
+    let mut buf = [0u8; 12];
+    buf[..N].copy_from_slice(&data);
+    FixedExtension::new_fixed_with_prefix(TIMESTAMP_EXTENSION_TYPE, N, buf)
+        .expect("timestamp payloads are at most 12 bytes")
+}
+
+impl TryFrom<ExtensionRef<'_>> for Timestamp {
+    type Error = TryFromTimestampError;
+
+    fn try_from(value: ExtensionRef<'_>) -> Result<Self, Self::Error> {
+        match value.data.len() {
+            4 => Timestamp32::try_from(value).map(Into::into),
+            8 => Timestamp64::try_from(value).map(Into::into),
+            12 => Timestamp96::try_from(value).map(Into::into),
+            _ if value.r#type != TIMESTAMP_EXTENSION_TYPE => {
+                Err(TryFromTimestampError::InvalidType)
+            }
+            _ => Err(TryFromTimestampError::InvalidDataLength),
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<FixedExtension<N>> for Timestamp {
+    type Error = TryFromTimestampError;
+
+    fn try_from(value: FixedExtension<N>) -> Result<Self, Self::Error> {
+        value.as_ref().try_into()
+    }
+}
+
+impl From<Timestamp> for FixedExtension<12> {
+    fn from(value: Timestamp) -> Self {
+        value.to_extension()
+    }
+}
+
+impl From<Timestamp32> for Timestamp {
+    fn from(value: Timestamp32) -> Self {
+        Self {
+            secs: value.seconds().into(),
+            nanos: 0,
+        }
+    }
+}
+
+impl From<Timestamp64> for Timestamp {
+    fn from(value: Timestamp64) -> Self {
+        Self {
+            // timestamp 64 seconds are limited to 34 bits
+            secs: value.seconds() as i64,
+            nanos: value.nanos(),
+        }
+    }
+}
+
+impl From<Timestamp96> for Timestamp {
+    fn from(value: Timestamp96) -> Self {
+        Self {
+            secs: value.seconds(),
+            nanos: value.nanos(),
+        }
+    }
+}
+
+impl From<Timestamp> for Timestamp96 {
+    fn from(value: Timestamp) -> Self {
+        Self {
+            nanos: value.nanos,
+            secs: value.secs,
+        }
+    }
+}
+
+impl TryFrom<Timestamp> for core::time::Duration {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        let secs = u64::try_from(value.seconds())?;
+        Ok(core::time::Duration::new(secs, value.nanos()))
+    }
+}
+
+impl TryFrom<core::time::Duration> for Timestamp {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: core::time::Duration) -> Result<Self, Self::Error> {
+        let secs =
+            i64::try_from(value.as_secs()).map_err(|_| ConstructTimestampError::ExceedSeconds)?;
+        Self::new(secs, value.subsec_nanos())
+    }
+}
+
 #[cfg(test)]
 mod duration_tests {
     use super::*;
@@ -409,11 +580,57 @@ mod duration_tests {
         assert_eq!(err, ConstructTimestampError::ExceedNanos);
     }
 
+    #[rstest]
+    fn narrower_timestamps_widen_to_timestamp96() {
+        let ts32 = Timestamp96::from(Timestamp32::new(u32::MAX));
+        assert_eq!((ts32.seconds(), ts32.nanos()), (u32::MAX.into(), 0));
+
+        let ts64 = Timestamp96::from(Timestamp64::new((1 << 34) - 1, 999_999_999).unwrap());
+        assert_eq!((ts64.seconds(), ts64.nanos()), ((1 << 34) - 1, 999_999_999));
+    }
+
     #[rstest]
     fn timestamp96_new_rejects_invalid_nanos() {
         let err = Timestamp96::new(0, 1_000_000_000).unwrap_err();
         assert_eq!(err, ConstructTimestampError::ExceedNanos);
     }
+
+    #[rstest]
+    fn duration_to_timestamp_roundtrip() {
+        let d = core::time::Duration::new(1 << 40, 5);
+        let ts = Timestamp::try_from(d).unwrap();
+        assert_eq!((ts.seconds(), ts.nanos()), (1 << 40, 5));
+        assert_eq!(core::time::Duration::try_from(ts).unwrap(), d);
+        assert!(core::time::Duration::try_from(Timestamp::new(-1, 0).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn timestamp_converts_through_extensions() {
+        let ts = Timestamp::new(-7, 42).unwrap();
+        let ext = FixedExtension::<12>::from(ts);
+        assert_eq!(ext.len(), 12);
+        assert_eq!(Timestamp::try_from(ext.as_ref()), Ok(ts));
+
+        let ext: FixedExtension<4> = Timestamp32::new(9).into();
+        assert_eq!(Timestamp::try_from(ext), Ok(Timestamp::new(9, 0).unwrap()));
+
+        let other = FixedExtension::new_fixed(5, [0u8; 4]);
+        assert_eq!(
+            Timestamp::try_from(other.as_ref()),
+            Err(TryFromTimestampError::InvalidType)
+        );
+        let short = FixedExtension::new_fixed(-1, [0u8; 5]);
+        assert_eq!(
+            Timestamp::try_from(short.as_ref()),
+            Err(TryFromTimestampError::InvalidDataLength)
+        );
+    }
+
+    #[rstest]
+    fn timestamp_new_rejects_invalid_nanos() {
+        let err = Timestamp::new(0, 1_000_000_000).unwrap_err();
+        assert_eq!(err, ConstructTimestampError::ExceedNanos);
+    }
 }
 
 // Submodules provide Encode/Decode impls and their tests.
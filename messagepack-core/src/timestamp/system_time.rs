@@ -0,0 +1,175 @@
+//! Timestamp conversions for [`std::time::SystemTime`].
+
+use std::time::{Duration, SystemTime};
+
+use super::{ConstructTimestampError, Timestamp, Timestamp32, Timestamp64, Timestamp96};
+use crate::{
+    Format,
+    decode::{DecodeBorrowed, Error as DecodeError},
+    encode::{self, Encode},
+    io::{IoRead, IoWrite},
+};
+
+/// Times before the epoch have negative seconds.
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(after) => after.try_into(),
+            Err(before) => {
+                let before = before.duration();
+                let secs = i64::try_from(before.as_secs())
+                    .map_err(|_| ConstructTimestampError::ExceedSeconds)?;
+                match before.subsec_nanos() {
+                    0 => Self::new(-secs, 0),
+                    nanos => Self::new(
+                        (-secs)
+                            .checked_sub(1)
+                            .ok_or(ConstructTimestampError::ExceedSeconds)?,
+                        1_000_000_000 - nanos,
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Fails when the platform's `SystemTime` cannot hold the instant.
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        let nanos = Duration::from_nanos(value.nanos().into());
+        let time = match u64::try_from(value.seconds()) {
+            Ok(secs) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs)),
+            Err(_) => SystemTime::UNIX_EPOCH
+                .checked_sub(Duration::from_secs(value.seconds().unsigned_abs())),
+        };
+        time.and_then(|time| time.checked_add(nanos))
+            .ok_or(ConstructTimestampError::ExceedSeconds)
+    }
+}
+
+/// Fails on times before the epoch. Nanoseconds are dropped, as with
+/// `Duration`.
+impl TryFrom<SystemTime> for Timestamp32 {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        let since = value
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|_| ConstructTimestampError::ExceedSeconds)?;
+        since
+            .try_into()
+            .map_err(|_| ConstructTimestampError::ExceedSeconds)
+    }
+}
+
+/// Fails on times before the epoch.
+impl TryFrom<SystemTime> for Timestamp64 {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        let since = value
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|_| ConstructTimestampError::ExceedSeconds)?;
+        since.try_into()
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp96 {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        Timestamp::try_from(value).map(Into::into)
+    }
+}
+
+impl From<Timestamp32> for SystemTime {
+    fn from(value: Timestamp32) -> Self {
+        SystemTime::UNIX_EPOCH + Duration::from(value)
+    }
+}
+
+impl From<Timestamp64> for SystemTime {
+    fn from(value: Timestamp64) -> Self {
+        SystemTime::UNIX_EPOCH + Duration::from(value)
+    }
+}
+
+impl TryFrom<Timestamp96> for SystemTime {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: Timestamp96) -> Result<Self, Self::Error> {
+        Timestamp::from(value).try_into()
+    }
+}
+
+impl Encode for SystemTime {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        let timestamp = Timestamp::try_from(*self).map_err(|_| encode::Error::InvalidFormat)?;
+        timestamp.encode(writer)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for SystemTime {
+    type Value = SystemTime;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        let timestamp = Timestamp::decode_borrowed_with_format(format, reader)?;
+        timestamp.try_into().map_err(|_| DecodeError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn at(seconds: i64, nanos: u32) -> SystemTime {
+        let offset = Duration::new(seconds.unsigned_abs(), 0);
+        let whole = if seconds < 0 {
+            SystemTime::UNIX_EPOCH - offset
+        } else {
+            SystemTime::UNIX_EPOCH + offset
+        };
+        whole + Duration::from_nanos(nanos.into())
+    }
+
+    #[rstest]
+    fn pre_epoch_goes_through_timestamp96() {
+        let time = at(-10, 1);
+        let ts96 = Timestamp96::try_from(time).unwrap();
+        assert_eq!((ts96.seconds(), ts96.nanos()), (-10, 1));
+        assert_eq!(SystemTime::try_from(ts96).unwrap(), time);
+
+        assert_eq!(
+            Timestamp32::try_from(time),
+            Err(ConstructTimestampError::ExceedSeconds)
+        );
+        assert_eq!(
+            Timestamp64::try_from(time),
+            Err(ConstructTimestampError::ExceedSeconds)
+        );
+    }
+
+    #[rstest]
+    fn narrow_timestamps_convert_directly() {
+        let time = at(1_700_000_000, 5);
+        assert_eq!(SystemTime::from(Timestamp64::try_from(time).unwrap()), time);
+        assert_eq!(
+            SystemTime::from(Timestamp32::try_from(time).unwrap()),
+            at(1_700_000_000, 0)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Timestamp conversions for [`time::OffsetDateTime`](::time::OffsetDateTime).
+
+use ::time::OffsetDateTime;
+
+use super::{ConstructTimestampError, Timestamp};
+use crate::{
+    Format,
+    decode::{DecodeBorrowed, Error as DecodeError},
+    encode::{self, Encode},
+    io::{IoRead, IoWrite},
+};
+
+/// The offset is dropped; only the instant is kept.
+impl From<OffsetDateTime> for Timestamp {
+    fn from(value: OffsetDateTime) -> Self {
+        Self {
+            secs: value.unix_timestamp(),
+            nanos: value.nanosecond(),
+        }
+    }
+}
+
+/// The result is in UTC.
+impl TryFrom<Timestamp> for OffsetDateTime {
+    type Error = ConstructTimestampError;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        let nanos = i128::from(value.seconds()) * 1_000_000_000 + i128::from(value.nanos());
+        OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map_err(|_| ConstructTimestampError::ExceedSeconds)
+    }
+}
+
+impl Encode for OffsetDateTime {
+    fn encode<W: IoWrite>(
+        &self,
+        writer: &mut W,
+    ) -> core::result::Result<usize, encode::Error<W::Error>> {
+        Timestamp::from(*self).encode(writer)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for OffsetDateTime {
+    type Value = OffsetDateTime;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> core::result::Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        let timestamp = Timestamp::decode_borrowed_with_format(format, reader)?;
+        timestamp.try_into().map_err(|_| DecodeError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::time::{Date, Month, UtcOffset};
+    use rstest::rstest;
+
+    fn utc(year: i32, month: Month, day: u8, nanos: u32) -> OffsetDateTime {
+        Date::from_calendar_date(year, month, day)
+            .unwrap()
+            .with_hms_nano(0, 0, 0, nanos)
+            .unwrap()
+            .assume_utc()
+    }
+
+    #[rstest]
+    fn offset_is_normalized_to_utc() {
+        let dt = utc(2024, Month::February, 29, 0).to_offset(UtcOffset::from_hms(9, 0, 0).unwrap());
+        let back = OffsetDateTime::try_from(Timestamp::from(dt)).unwrap();
+        assert_eq!(back, dt);
+        assert_eq!(back.offset(), UtcOffset::UTC);
+    }
+}
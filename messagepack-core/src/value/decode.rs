@@ -0,0 +1,347 @@
+use super::{MAX_DEPTH, Number, Value, ValueRef};
+use crate::{
+    Format,
+    decode::{
+        Decode, DecodeBorrowed, Error, NbyteReader, ReferenceDecoder, ReferenceStr,
+        ReferenceStrDecoder,
+    },
+    extension::{ExtensionOwned, ExtensionRef, read_ext_header},
+    io::{IoRead, Reference},
+};
+use alloc::{string::ToString, vec::Vec};
+
+/// Upper bound for preallocating containers from an untrusted length.
+pub(super) const MAX_PREALLOCATE: usize = 4096;
+
+pub(super) fn decode_number<'de, R>(
+    format: Format,
+    reader: &mut R,
+) -> Result<Number, Error<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let n = match format {
+        Format::PositiveFixInt(v) => Number::PositiveInt(v.into()),
+        Format::Uint8 => {
+            Number::PositiveInt(u8::decode_borrowed_with_format(format, reader)?.into())
+        }
+        Format::Uint16 => {
+            Number::PositiveInt(u16::decode_borrowed_with_format(format, reader)?.into())
+        }
+        Format::Uint32 => {
+            Number::PositiveInt(u32::decode_borrowed_with_format(format, reader)?.into())
+        }
+        Format::Uint64 => Number::PositiveInt(u64::decode_borrowed_with_format(format, reader)?),
+        Format::NegativeFixInt(v) => Number::from(i64::from(v)),
+        Format::Int8 => Number::from(i64::from(i8::decode_borrowed_with_format(format, reader)?)),
+        Format::Int16 => Number::from(i64::from(i16::decode_borrowed_with_format(format, reader)?)),
+        Format::Int32 => Number::from(i64::from(i32::decode_borrowed_with_format(format, reader)?)),
+        Format::Int64 => Number::from(i64::decode_borrowed_with_format(format, reader)?),
+        Format::Float32 => Number::Float32(f32::decode_borrowed_with_format(format, reader)?),
+        Format::Float64 => Number::Float64(f64::decode_borrowed_with_format(format, reader)?),
+        _ => return Err(Error::UnexpectedFormat),
+    };
+    Ok(n)
+}
+
+pub(super) fn array_len<'de, R>(format: Format, reader: &mut R) -> Result<usize, Error<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    match format {
+        Format::FixArray(n) => Ok(n.into()),
+        Format::Array16 => NbyteReader::<2>::read(reader),
+        Format::Array32 => NbyteReader::<4>::read(reader),
+        _ => Err(Error::UnexpectedFormat),
+    }
+}
+
+pub(super) fn map_len<'de, R>(format: Format, reader: &mut R) -> Result<usize, Error<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    match format {
+        Format::FixMap(n) => Ok(n.into()),
+        Format::Map16 => NbyteReader::<2>::read(reader),
+        Format::Map32 => NbyteReader::<4>::read(reader),
+        _ => Err(Error::UnexpectedFormat),
+    }
+}
+
+fn decode_value<'de, R>(
+    format: Format,
+    reader: &mut R,
+    depth: usize,
+) -> Result<Value, Error<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let v = match format {
+        Format::Nil => Value::Nil,
+        Format::False => Value::Bool(false),
+        Format::True => Value::Bool(true),
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+            let s = ReferenceStrDecoder::decode_with_format(format, reader)?;
+            Value::String(s.as_str().to_string())
+        }
+        Format::Bin8 | Format::Bin16 | Format::Bin32 => {
+            let b = ReferenceDecoder::decode_with_format(format, reader)?;
+            Value::Bin(b.as_bytes().to_vec())
+        }
+        Format::FixExt1
+        | Format::FixExt2
+        | Format::FixExt4
+        | Format::FixExt8
+        | Format::FixExt16
+        | Format::Ext8
+        | Format::Ext16
+        | Format::Ext32 => {
+            Value::Extension(ExtensionOwned::decode_borrowed_with_format(format, reader)?)
+        }
+        Format::FixArray(_) | Format::Array16 | Format::Array32 => {
+            let depth = enter(depth)?;
+            let len = array_len(format, reader)?;
+            let mut items = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                items.push(decode_value(format, reader, depth)?);
+            }
+            Value::Array(items)
+        }
+        Format::FixMap(_) | Format::Map16 | Format::Map32 => {
+            let depth = enter(depth)?;
+            let len = map_len(format, reader)?;
+            let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                let k = decode_value(format, reader, depth)?;
+                let format = Format::decode_borrowed(reader)?;
+                let v = decode_value(format, reader, depth)?;
+                entries.push((k, v));
+            }
+            Value::Map(entries)
+        }
+        Format::NeverUsed => return Err(Error::UnexpectedFormat),
+        _ => Value::Number(decode_number(format, reader)?),
+    };
+    Ok(v)
+}
+
+fn decode_value_ref<'de, R>(
+    format: Format,
+    reader: &mut R,
+    depth: usize,
+) -> Result<ValueRef<'de>, Error<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let v = match format {
+        Format::Nil => ValueRef::Nil,
+        Format::False => ValueRef::Bool(false),
+        Format::True => ValueRef::Bool(true),
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+            match ReferenceStrDecoder::decode_with_format(format, reader)? {
+                ReferenceStr::Borrowed(s) => ValueRef::String(s),
+                ReferenceStr::Copied(_) => return Err(Error::InvalidData),
+            }
+        }
+        Format::Bin8 | Format::Bin16 | Format::Bin32 => {
+            match ReferenceDecoder::decode_with_format(format, reader)? {
+                Reference::Borrowed(b) => ValueRef::Bin(b),
+                Reference::Copied(_) => return Err(Error::InvalidData),
+            }
+        }
+        Format::FixExt1
+        | Format::FixExt2
+        | Format::FixExt4
+        | Format::FixExt8
+        | Format::FixExt16
+        | Format::Ext8
+        | Format::Ext16
+        | Format::Ext32 => {
+            let (len, r#type) = read_ext_header(format, reader)?;
+            match reader.read_slice(len).map_err(Error::Io)? {
+                Reference::Borrowed(data) => ValueRef::Extension(ExtensionRef::new(r#type, data)),
+                Reference::Copied(_) => return Err(Error::InvalidData),
+            }
+        }
+        Format::FixArray(_) | Format::Array16 | Format::Array32 => {
+            let depth = enter(depth)?;
+            let len = array_len(format, reader)?;
+            let mut items = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                items.push(decode_value_ref(format, reader, depth)?);
+            }
+            ValueRef::Array(items)
+        }
+        Format::FixMap(_) | Format::Map16 | Format::Map32 => {
+            let depth = enter(depth)?;
+            let len = map_len(format, reader)?;
+            let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                let k = decode_value_ref(format, reader, depth)?;
+                let format = Format::decode_borrowed(reader)?;
+                let v = decode_value_ref(format, reader, depth)?;
+                entries.push((k, v));
+            }
+            ValueRef::Map(entries)
+        }
+        Format::NeverUsed => return Err(Error::UnexpectedFormat),
+        _ => ValueRef::Number(decode_number(format, reader)?),
+    };
+    Ok(v)
+}
+
+/// Step one level deeper, failing once [`MAX_DEPTH`] is exceeded.
+pub(super) fn enter<E>(depth: usize) -> Result<usize, Error<E>> {
+    if depth >= MAX_DEPTH {
+        Err(Error::InvalidData)
+    } else {
+        Ok(depth + 1)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for Number {
+    type Value = Number;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> Result<Self::Value, Error<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        decode_number(format, reader)
+    }
+}
+
+impl<'de> DecodeBorrowed<'de> for Value {
+    type Value = Value;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> Result<Self::Value, Error<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        decode_value(format, reader, 0)
+    }
+}
+
+/// Strings, binaries and extension payloads are borrowed from the reader.
+/// Readers which copy data fail with [`Error::InvalidData`].
+impl<'de> DecodeBorrowed<'de> for ValueRef<'de> {
+    type Value = ValueRef<'de>;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> Result<Self::Value, Error<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        decode_value_ref(format, reader, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encode, io::SliceReader};
+    use alloc::vec;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[0xc0], Value::Nil)]
+    #[case(&[0xc3], Value::Bool(true))]
+    #[case(&[0x7f], Value::Number(Number::PositiveInt(127)))]
+    #[case(&[0xd0, 0x05], Value::Number(Number::PositiveInt(5)))]
+    #[case(&[0xe0], Value::Number(Number::NegativeInt(-32)))]
+    #[case(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], Value::Number(Number::PositiveInt(u64::MAX)))]
+    #[case(&[0xca, 0x3f, 0xc0, 0x00, 0x00], Value::Number(Number::Float32(1.5)))]
+    #[case(&[0xa1, 0x61], Value::from("a"))]
+    #[case(&[0xc4, 0x01, 0x02], Value::Bin(vec![0x02]))]
+    #[case(&[0xd4, 0x05, 0x01], Value::Extension(ExtensionOwned::new(5, vec![0x01])))]
+    #[case(&[0x91, 0x90], Value::Array(vec![Value::Array(vec![])]))]
+    #[case(&[0x81, 0x01, 0x80], Value::Map(vec![(Value::from(1u8), Value::Map(vec![]))]))]
+    fn decode_owned(#[case] buf: &[u8], #[case] expected: Value) {
+        let mut reader = SliceReader::new(buf);
+        let v = Value::decode(&mut reader).unwrap();
+        assert_eq!(v, expected);
+        assert!(reader.rest().is_empty());
+
+        let mut reader = SliceReader::new(buf);
+        let r = ValueRef::decode(&mut reader).unwrap();
+        assert_eq!(Value::from(r), expected);
+    }
+
+    #[test]
+    fn value_ref_borrows_input() {
+        let buf: &[u8] = &[0x92, 0xa2, 0x68, 0x69, 0xc4, 0x01, 0xff];
+        let mut reader = SliceReader::new(buf);
+        let v = ValueRef::decode(&mut reader).unwrap();
+        assert_eq!(
+            v,
+            ValueRef::Array(vec![ValueRef::String("hi"), ValueRef::Bin(&[0xff])])
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn owned_from_copying_reader() {
+        let buf: &[u8] = &[0x92, 0xa2, 0x68, 0x69, 0xd4, 0x01, 0x02];
+        let mut reader = crate::io::StdReader::new(buf);
+        let v = Value::decode(&mut reader).unwrap();
+        assert_eq!(
+            v,
+            Value::Array(vec![
+                Value::from("hi"),
+                Value::Extension(ExtensionOwned::new(1, vec![0x02]))
+            ])
+        );
+
+        let mut reader = crate::io::StdReader::new(buf);
+        assert!(matches!(
+            ValueRef::decode(&mut reader),
+            Err(Error::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let mut ok = vec![0x91; MAX_DEPTH];
+        ok.push(0xc0);
+        assert!(Value::decode(&mut SliceReader::new(&ok)).is_ok());
+
+        let mut too_deep = vec![0x91; MAX_DEPTH + 1];
+        too_deep.push(0xc0);
+        assert_eq!(
+            Value::decode(&mut SliceReader::new(&too_deep)),
+            Err(Error::InvalidData)
+        );
+        assert_eq!(
+            ValueRef::decode(&mut SliceReader::new(&too_deep)),
+            Err(Error::InvalidData)
+        );
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0x01])]
+    #[case(&[0x81, 0x01])]
+    #[case(&[0xa2, 0x61])]
+    #[case(&[0xc1])]
+    fn decode_invalid(#[case] buf: &[u8]) {
+        assert!(Value::decode(&mut SliceReader::new(buf)).is_err());
+    }
+
+    #[test]
+    fn roundtrip_keeps_float_width() {
+        let v = Value::Array(vec![Value::from(1.5f32), Value::from(1.5f64)]);
+        let mut buf = vec![];
+        v.encode(&mut buf).unwrap();
+        assert_eq!(Value::decode(&mut SliceReader::new(&buf)).unwrap(), v);
+    }
+}
@@ -0,0 +1,373 @@
+use super::Number;
+use crate::extension::{ExtensionOwned, ExtensionRef};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Maximum nesting depth of arrays and maps accepted while decoding a
+/// [`Value`], [`ValueRef`] or [`PreservedValue`](super::PreservedValue).
+///
+/// Decoding is recursive, so deeper input is rejected with
+/// [`InvalidData`](crate::decode::Error::InvalidData) rather than risking a
+/// stack overflow.
+pub const MAX_DEPTH: usize = 256;
+
+/// Owned representation of any MessagePack value.
+///
+/// `Value` implements [`Eq`], [`Ord`] and [`Hash`](core::hash::Hash).
+/// Values of different variants are ordered by variant in declaration order,
+/// and values of the same variant by their contents, with maps and arrays
+/// compared element by element. See [`Number`] for numeric semantics.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {
+    /// Represents nil format.
+    Nil,
+    /// Represents bool format family.
+    Bool(bool),
+    /// Represents `bin 8`, `bin 16` and `bin 32`.
+    Bin(Vec<u8>),
+    /// Represents ext format family.
+    Extension(ExtensionOwned),
+    /// Represents int format family and float format family.
+    Number(Number),
+    /// Represents str format family.
+    String(String),
+    /// Represents array format family.
+    Array(Vec<Value>),
+    /// Represents map format family.
+    Map(Vec<(Value, Value)>),
+}
+
+/// Borrowed representation of any MessagePack value.
+///
+/// Decoding requires a reader which can lend data for `'a`, such as
+/// [`SliceReader`](crate::io::SliceReader).
+///
+/// Equality, ordering and hashing follow the same rules as [`Value`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValueRef<'a> {
+    /// Represents nil format.
+    Nil,
+    /// Represents bool format family.
+    Bool(bool),
+    /// Represents `bin 8`, `bin 16` and `bin 32`.
+    Bin(&'a [u8]),
+    /// Represents ext format family.
+    Extension(ExtensionRef<'a>),
+    /// Represents int format family and float format family.
+    Number(Number),
+    /// Represents str format family.
+    String(&'a str),
+    /// Represents array format family.
+    Array(Vec<ValueRef<'a>>),
+    /// Represents map format family.
+    Map(Vec<(ValueRef<'a>, ValueRef<'a>)>),
+}
+
+impl Value {
+    /// Returns true if the `Value` is nil.
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Value::Nil)
+    }
+
+    /// If the `Value` is boolean, returns contained value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is bin, returns contained slice.
+    pub fn as_bin(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bin(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is ext, returns contained value.
+    pub fn as_extension(&self) -> Option<ExtensionRef<'_>> {
+        match self {
+            Value::Extension(ext) => Some(ext.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is number, returns contained value.
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is str, returns contained slice.
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is array, returns contained slice.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is map, returns contained slice of pairs.
+    pub fn as_map(&self) -> Option<&[(Value, Value)]> {
+        match self {
+            Value::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Borrow as [`ValueRef`].
+    pub fn as_value_ref(&self) -> ValueRef<'_> {
+        match self {
+            Value::Nil => ValueRef::Nil,
+            Value::Bool(v) => ValueRef::Bool(*v),
+            Value::Bin(v) => ValueRef::Bin(v),
+            Value::Extension(ext) => ValueRef::Extension(ext.as_ref()),
+            Value::Number(n) => ValueRef::Number(*n),
+            Value::String(s) => ValueRef::String(s),
+            Value::Array(items) => ValueRef::Array(items.iter().map(Value::as_value_ref).collect()),
+            Value::Map(entries) => ValueRef::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.as_value_ref(), v.as_value_ref()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> ValueRef<'a> {
+    /// Returns true if the `ValueRef` is nil.
+    pub fn is_nil(&self) -> bool {
+        matches!(self, ValueRef::Nil)
+    }
+
+    /// If the `ValueRef` is boolean, returns contained value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ValueRef::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is bin, returns contained slice.
+    pub fn as_bin(&self) -> Option<&'a [u8]> {
+        match self {
+            ValueRef::Bin(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is ext, returns contained value.
+    pub fn as_extension(&self) -> Option<ExtensionRef<'a>> {
+        match self {
+            ValueRef::Extension(ext) => Some(*ext),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is number, returns contained value.
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            ValueRef::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is str, returns contained slice.
+    pub fn as_string(&self) -> Option<&'a str> {
+        match self {
+            ValueRef::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is array, returns contained slice.
+    pub fn as_array(&self) -> Option<&[ValueRef<'a>]> {
+        match self {
+            ValueRef::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is map, returns contained slice of pairs.
+    pub fn as_map(&self) -> Option<&[(ValueRef<'a>, ValueRef<'a>)]> {
+        match self {
+            ValueRef::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(v: ValueRef<'_>) -> Self {
+        match v {
+            ValueRef::Nil => Value::Nil,
+            ValueRef::Bool(b) => Value::Bool(b),
+            ValueRef::Bin(b) => Value::Bin(b.to_vec()),
+            ValueRef::Extension(ext) => Value::Extension(ext.into()),
+            ValueRef::Number(n) => Value::Number(n),
+            ValueRef::String(s) => Value::String(s.to_string()),
+            ValueRef::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            ValueRef::Map(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Value::from(k), Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    fn from(v: &'a Value) -> Self {
+        v.as_value_ref()
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($ty:ty => $via:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::Number(Number::from(<$via>::from(v)))
+                }
+            }
+
+            impl From<$ty> for ValueRef<'_> {
+                fn from(v: $ty) -> Self {
+                    ValueRef::Number(Number::from(<$via>::from(v)))
+                }
+            }
+        )*
+    };
+}
+impl_from_number!(
+    u8 => u64, u16 => u64, u32 => u64, u64 => u64,
+    i8 => i64, i16 => i64, i32 => i64, i64 => i64,
+    f32 => f32, f64 => f64,
+);
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Nil
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(v: Number) -> Self {
+        Value::Number(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(v: &[u8]) -> Self {
+        Value::Bin(v.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(v: Vec<u8>) -> Self {
+        Value::Bin(v)
+    }
+}
+
+impl From<ExtensionOwned> for Value {
+    fn from(v: ExtensionOwned) -> Self {
+        Value::Extension(v)
+    }
+}
+
+impl From<()> for ValueRef<'_> {
+    fn from(_: ()) -> Self {
+        ValueRef::Nil
+    }
+}
+
+impl From<bool> for ValueRef<'_> {
+    fn from(v: bool) -> Self {
+        ValueRef::Bool(v)
+    }
+}
+
+impl From<Number> for ValueRef<'_> {
+    fn from(v: Number) -> Self {
+        ValueRef::Number(v)
+    }
+}
+
+impl<'a> From<&'a str> for ValueRef<'a> {
+    fn from(v: &'a str) -> Self {
+        ValueRef::String(v)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ValueRef<'a> {
+    fn from(v: &'a [u8]) -> Self {
+        ValueRef::Bin(v)
+    }
+}
+
+impl<'a> From<ExtensionRef<'a>> for ValueRef<'a> {
+    fn from(v: ExtensionRef<'a>) -> Self {
+        ValueRef::Extension(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::cmp::Ordering;
+
+    fn hash_of<T: core::hash::Hash>(v: &T) -> u64 {
+        use core::hash::Hasher;
+        let mut hasher = std::hash::DefaultHasher::new();
+        v.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn values_as_set_keys() {
+        let mut set = std::collections::BTreeSet::new();
+        set.insert(Value::from(f64::NAN));
+        set.insert(Value::from(f64::NAN));
+        set.insert(Value::Array(vec![Value::from(1u64)]));
+        set.insert(Value::from("a"));
+        assert_eq!(set.len(), 3);
+
+        let a = ValueRef::Map(vec![(ValueRef::String("k"), ValueRef::Nil)]);
+        let b = ValueRef::Map(vec![(ValueRef::String("k"), ValueRef::Nil)]);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Number, Value, ValueRef};
+use crate::{
+    encode::{
+        BinaryEncoder, Encode, Error, MapFormatEncoder, NilEncoder, array::ArrayFormatEncoder,
+        int::EncodeMinimizeInt,
+    },
+    io::IoWrite,
+};
+
+impl Encode for Number {
+    fn encode<W: IoWrite>(&self, writer: &mut W) -> Result<usize, Error<W::Error>> {
+        match self {
+            Number::PositiveInt(v) => EncodeMinimizeInt(*v).encode(writer),
+            Number::NegativeInt(v) => EncodeMinimizeInt(*v).encode(writer),
+            Number::Float32(v) => v.encode(writer),
+            Number::Float64(v) => v.encode(writer),
+        }
+    }
+}
+
+impl Encode for Value {
+    fn encode<W: IoWrite>(&self, writer: &mut W) -> Result<usize, Error<W::Error>> {
+        match self {
+            Value::Nil => NilEncoder.encode(writer),
+            Value::Bool(v) => v.encode(writer),
+            Value::Bin(v) => BinaryEncoder(v).encode(writer),
+            Value::Extension(ext) => ext.encode(writer),
+            Value::Number(n) => n.encode(writer),
+            Value::String(s) => s.encode(writer),
+            Value::Array(items) => {
+                let header = ArrayFormatEncoder(items.len()).encode(writer)?;
+                items
+                    .iter()
+                    .try_fold(header, |acc, v| Ok(acc + v.encode(writer)?))
+            }
+            Value::Map(entries) => {
+                let header = MapFormatEncoder::new(entries.len()).encode(writer)?;
+                entries.iter().try_fold(header, |acc, (k, v)| {
+                    Ok(acc + k.encode(writer)? + v.encode(writer)?)
+                })
+            }
+        }
+    }
+}
+
+impl Encode for ValueRef<'_> {
+    fn encode<W: IoWrite>(&self, writer: &mut W) -> Result<usize, Error<W::Error>> {
+        match self {
+            ValueRef::Nil => NilEncoder.encode(writer),
+            ValueRef::Bool(v) => v.encode(writer),
+            ValueRef::Bin(v) => BinaryEncoder(v).encode(writer),
+            ValueRef::Extension(ext) => ext.encode(writer),
+            ValueRef::Number(n) => n.encode(writer),
+            ValueRef::String(s) => s.encode(writer),
+            ValueRef::Array(items) => {
+                let header = ArrayFormatEncoder(items.len()).encode(writer)?;
+                items
+                    .iter()
+                    .try_fold(header, |acc, v| Ok(acc + v.encode(writer)?))
+            }
+            ValueRef::Map(entries) => {
+                let header = MapFormatEncoder::new(entries.len()).encode(writer)?;
+                entries.iter().try_fold(header, |acc, (k, v)| {
+                    Ok(acc + k.encode(writer)? + v.encode(writer)?)
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Number::PositiveInt(1), &[0x01])]
+    #[case(Number::PositiveInt(256), &[0xcd, 0x01, 0x00])]
+    #[case(Number::NegativeInt(-1), &[0xff])]
+    #[case(Number::NegativeInt(-129), &[0xd1, 0xff, 0x7f])]
+    #[case(Number::Float32(1.5), &[0xca, 0x3f, 0xc0, 0x00, 0x00])]
+    #[case(Number::Float64(1.5), &[0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0])]
+    fn encode_number(#[case] n: Number, #[case] expected: &[u8]) {
+        let mut buf = vec![];
+        let len = n.encode(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(len, expected.len());
+    }
+
+    #[test]
+    fn encode_nested() {
+        let v = Value::Map(vec![
+            (
+                Value::from("a"),
+                Value::Array(vec![Value::Nil, Value::from(true)]),
+            ),
+            (Value::from(1u8), Value::from(&[0x01u8][..])),
+        ]);
+        let expected: &[u8] = &[0x82, 0xa1, 0x61, 0x92, 0xc0, 0xc3, 0x01, 0xc4, 0x01, 0x01];
+
+        let mut buf = vec![];
+        let len = v.encode(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(len, expected.len());
+
+        let mut buf = vec![];
+        v.as_value_ref().encode(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+    }
+}
@@ -0,0 +1,51 @@
+//! Dynamic MessagePack values.
+//!
+//! [`Value`] owns its data and can be decoded from any reader, while
+//! [`ValueRef`] borrows strings, binaries and extension payloads from the
+//! input. Both implement [`Encode`](crate::Encode) and
+//! [`DecodeBorrowed`](crate::decode::DecodeBorrowed) directly.
+//!
+//! Ints are stored by value only and re-encoded in the smallest format, while
+//! the float width is kept. [`PreservedValue`] additionally records the wire
+//! format of each node, so that decoding and re-encoding reproduces the input
+//! byte for byte.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")]
+//! # fn main() {
+//! use messagepack_core::{Decode, Encode, io::SliceReader, value::{Value, ValueRef}};
+//!
+//! // {"a": [1, -1, 1.5]}
+//! let buf: &[u8] = &[0x81, 0xa1, 0x61, 0x93, 0x01, 0xff, 0xca, 0x3f, 0xc0, 0x00, 0x00];
+//!
+//! let mut reader = SliceReader::new(buf);
+//! let v = ValueRef::decode(&mut reader).unwrap();
+//! assert_eq!(v.as_map().unwrap()[0].0, ValueRef::String("a"));
+//!
+//! let owned = Value::from(v);
+//! let mut out = vec![];
+//! owned.encode(&mut out).unwrap();
+//! assert_eq!(out, buf);
+//! # }
+//! # #[cfg(not(feature = "alloc"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(feature = "alloc")]
+mod decode;
+#[cfg(feature = "alloc")]
+mod dynamic;
+#[cfg(feature = "alloc")]
+mod encode;
+mod number;
+#[cfg(feature = "alloc")]
+mod preserved;
+
+pub use number::Number;
+
+#[cfg(feature = "alloc")]
+pub use dynamic::{MAX_DEPTH, Value, ValueRef};
+#[cfg(feature = "alloc")]
+pub use preserved::{PreservedKind, PreservedValue};
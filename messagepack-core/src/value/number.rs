@@ -0,0 +1,151 @@
+/// Represents any number, it could be int or float.
+///
+/// The float width is kept so that `float 32` values are re-encoded as
+/// `float 32`. Ints are not: `uint 32` holding `5` decodes to
+/// `PositiveInt(5)` and is re-encoded as a positive fixint. Use
+/// [`PreservedValue`](super::PreservedValue) when the exact int format matters.
+///
+/// ## Equality and ordering
+///
+/// `Number` implements [`Eq`], [`Ord`] and [`Hash`](core::hash::Hash), so it
+/// can be used as a map key or set element.
+///
+/// - Numbers of different variants are never equal, so `1`, `f32(1.0)` and
+///   `1.0` are distinct. Ordering across variants is
+///   `NegativeInt < PositiveInt < Float32 < Float64`.
+/// - Floats are compared with `total_cmp` and are equal only when their bit
+///   patterns match. `NaN` equals itself and `-0.0` differs from `0.0`.
+#[derive(Debug, Clone, Copy)]
+pub enum Number {
+    /// Non-negative integer
+    PositiveInt(u64),
+    /// Negative integer
+    NegativeInt(i64),
+    /// Represents `float 32`
+    Float32(f32),
+    /// Represents `float 64`
+    Float64(f64),
+}
+
+impl Number {
+    /// Rank of the variant in the total order.
+    fn rank(&self) -> u8 {
+        match self {
+            Number::NegativeInt(_) => 0,
+            Number::PositiveInt(_) => 1,
+            Number::Float32(_) => 2,
+            Number::Float64(_) => 3,
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        match (self, other) {
+            (Number::PositiveInt(a), Number::PositiveInt(b)) => a.cmp(b),
+            (Number::NegativeInt(a), Number::NegativeInt(b)) => a.cmp(b),
+            (Number::Float32(a), Number::Float32(b)) => a.total_cmp(b),
+            (Number::Float64(a), Number::Float64(b)) => a.total_cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl core::hash::Hash for Number {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Number::PositiveInt(v) => v.hash(state),
+            Number::NegativeInt(v) => v.hash(state),
+            Number::Float32(v) => v.to_bits().hash(state),
+            Number::Float64(v) => v.to_bits().hash(state),
+        }
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        Number::PositiveInt(value)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        u64::try_from(value)
+            .map(Number::PositiveInt)
+            .unwrap_or(Number::NegativeInt(value))
+    }
+}
+
+impl From<f32> for Number {
+    fn from(value: f32) -> Self {
+        Number::Float32(value)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float64(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Ordering;
+    use rstest::rstest;
+
+    fn hash_of<T: core::hash::Hash>(v: &T) -> u64 {
+        use core::hash::Hasher;
+        let mut hasher = std::hash::DefaultHasher::new();
+        v.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[rstest]
+    #[case(Number::NegativeInt(-2), Number::NegativeInt(-1), Ordering::Less)]
+    #[case(Number::NegativeInt(-1), Number::PositiveInt(0), Ordering::Less)]
+    #[case(
+        Number::PositiveInt(u64::MAX),
+        Number::Float32(f32::NEG_INFINITY),
+        Ordering::Less
+    )]
+    #[case(
+        Number::Float32(f32::NAN),
+        Number::Float64(f64::NEG_INFINITY),
+        Ordering::Less
+    )]
+    #[case(Number::PositiveInt(1), Number::Float64(1.0), Ordering::Less)]
+    #[case(Number::Float32(-0.0), Number::Float32(0.0), Ordering::Less)]
+    #[case(
+        Number::Float64(f64::INFINITY),
+        Number::Float64(f64::NAN),
+        Ordering::Less
+    )]
+    #[case(Number::Float32(f32::NAN), Number::Float32(f32::NAN), Ordering::Equal)]
+    #[case(Number::Float64(f64::NAN), Number::Float64(f64::NAN), Ordering::Equal)]
+    #[case(Number::PositiveInt(3), Number::PositiveInt(3), Ordering::Equal)]
+    fn total_order(#[case] a: Number, #[case] b: Number, #[case] expected: Ordering) {
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(b.cmp(&a), expected.reverse());
+        assert_eq!(a.partial_cmp(&b), Some(expected));
+        assert_eq!(a == b, expected.is_eq());
+        if expected.is_eq() {
+            assert_eq!(hash_of(&a), hash_of(&b));
+        }
+    }
+}
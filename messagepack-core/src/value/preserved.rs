@@ -0,0 +1,569 @@
+use super::{
+    Number, Value,
+    decode::{MAX_PREALLOCATE, array_len, decode_number, enter, map_len},
+};
+use crate::{
+    Format,
+    decode::{Decode, DecodeBorrowed, Error as DecodeError, ReferenceDecoder, ReferenceStrDecoder},
+    encode::{Encode, Error as EncodeError},
+    extension::{ExtensionOwned, read_ext_header},
+    io::{IoRead, IoWrite},
+};
+use alloc::{string::String, vec::Vec};
+
+/// A dynamic value which remembers the wire format of every node.
+///
+/// Decoding records the [`Format`] each node was read from, and encoding
+/// re-emits that format, so an unmodified tree is written back byte for byte.
+/// This holds even for non-minimal encodings such as `uint 32` holding `5` or
+/// `str 16` holding a short string.
+///
+/// A recorded format is used as long as it can still represent the node.
+/// When it cannot, e.g. a `fixstr` whose string grew past 31 bytes or a
+/// `uint 8` set to `-1`, the node falls back to the minimal encoding.
+/// Nodes created with [`PreservedValue::new`] or replaced via
+/// [`PreservedValue::set`] have no recorded format and are always minimized.
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_core::{Decode, Encode, io::SliceReader, value::{Number, PreservedKind, PreservedValue}};
+///
+/// // [uint32 5, str16 "a"]
+/// let buf: &[u8] = &[0x92, 0xce, 0x00, 0x00, 0x00, 0x05, 0xda, 0x00, 0x01, 0x61];
+/// let mut v = PreservedValue::decode(&mut SliceReader::new(buf)).unwrap();
+///
+/// let mut out = vec![];
+/// v.encode(&mut out).unwrap();
+/// assert_eq!(out, buf);
+///
+/// // Editing a node keeps its format while the new value still fits.
+/// if let PreservedKind::Array(items) = v.kind_mut() {
+///     *items[0].kind_mut() = PreservedKind::Number(Number::PositiveInt(7));
+/// }
+/// let mut out = vec![];
+/// v.encode(&mut out).unwrap();
+/// assert_eq!(out, &[0x92, 0xce, 0x00, 0x00, 0x00, 0x07, 0xda, 0x00, 0x01, 0x61]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreservedValue {
+    kind: PreservedKind,
+    format: Option<Format>,
+}
+
+/// Content of a [`PreservedValue`] node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreservedKind {
+    /// Represents nil format.
+    Nil,
+    /// Represents bool format family.
+    Bool(bool),
+    /// Represents `bin 8`, `bin 16` and `bin 32`.
+    Bin(Vec<u8>),
+    /// Represents ext format family.
+    Extension(ExtensionOwned),
+    /// Represents int format family and float format family.
+    Number(Number),
+    /// Represents str format family.
+    String(String),
+    /// Represents array format family.
+    Array(Vec<PreservedValue>),
+    /// Represents map format family.
+    Map(Vec<(PreservedValue, PreservedValue)>),
+}
+
+impl PreservedValue {
+    /// Create a node without a recorded format.
+    pub fn new(kind: PreservedKind) -> Self {
+        Self { kind, format: None }
+    }
+
+    /// Create a node which should be encoded as `format`.
+    ///
+    /// The length or value carried by fix formats is ignored.
+    pub fn with_format(kind: PreservedKind, format: Format) -> Self {
+        Self {
+            kind,
+            format: Some(format),
+        }
+    }
+
+    /// The format this node was decoded from, if any.
+    pub fn format(&self) -> Option<Format> {
+        self.format
+    }
+
+    /// Borrow the content of this node.
+    pub fn kind(&self) -> &PreservedKind {
+        &self.kind
+    }
+
+    /// Mutably borrow the content of this node.
+    ///
+    /// The recorded format is kept, so that editing children of a container
+    /// does not change the container header.
+    pub fn kind_mut(&mut self) -> &mut PreservedKind {
+        &mut self.kind
+    }
+
+    /// Replace the content and forget the recorded format.
+    pub fn set(&mut self, kind: PreservedKind) {
+        self.kind = kind;
+        self.format = None;
+    }
+
+    /// Forget the recorded format, so this node is minimized on encoding.
+    /// Children keep their formats.
+    pub fn clear_format(&mut self) {
+        self.format = None;
+    }
+
+    /// Consume the node and return its content.
+    pub fn into_kind(self) -> PreservedKind {
+        self.kind
+    }
+}
+
+impl From<PreservedKind> for PreservedValue {
+    fn from(kind: PreservedKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl From<Value> for PreservedValue {
+    fn from(v: Value) -> Self {
+        let kind = match v {
+            Value::Nil => PreservedKind::Nil,
+            Value::Bool(b) => PreservedKind::Bool(b),
+            Value::Bin(b) => PreservedKind::Bin(b),
+            Value::Extension(ext) => PreservedKind::Extension(ext),
+            Value::Number(n) => PreservedKind::Number(n),
+            Value::String(s) => PreservedKind::String(s),
+            Value::Array(items) => {
+                PreservedKind::Array(items.into_iter().map(Into::into).collect())
+            }
+            Value::Map(entries) => PreservedKind::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        };
+        Self::new(kind)
+    }
+}
+
+/// Drops the recorded formats.
+impl From<PreservedValue> for Value {
+    fn from(v: PreservedValue) -> Self {
+        match v.kind {
+            PreservedKind::Nil => Value::Nil,
+            PreservedKind::Bool(b) => Value::Bool(b),
+            PreservedKind::Bin(b) => Value::Bin(b),
+            PreservedKind::Extension(ext) => Value::Extension(ext),
+            PreservedKind::Number(n) => Value::Number(n),
+            PreservedKind::String(s) => Value::String(s),
+            PreservedKind::Array(items) => {
+                Value::Array(items.into_iter().map(Into::into).collect())
+            }
+            PreservedKind::Map(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Encoded marker plus any length, type or scalar bytes that follow it.
+struct Header {
+    buf: [u8; 9],
+    len: usize,
+}
+
+impl Header {
+    fn marker(format: Format) -> Self {
+        let mut buf = [0u8; 9];
+        buf[0] = format.as_byte();
+        Self { buf, len: 1 }
+    }
+
+    fn with(mut self, bytes: &[u8]) -> Self {
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        self
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Header for a length-prefixed family, if `len` fits into `format`.
+fn len_header(format: Format, len: usize) -> Option<Header> {
+    let fixed = |n: usize| (len == n).then(|| Header::marker(format));
+    match format {
+        Format::FixStr(_) => (len < 0x20).then(|| Header::marker(Format::FixStr(len as u8))),
+        Format::FixArray(_) => (len < 0x10).then(|| Header::marker(Format::FixArray(len as u8))),
+        Format::FixMap(_) => (len < 0x10).then(|| Header::marker(Format::FixMap(len as u8))),
+        Format::FixExt1 => fixed(1),
+        Format::FixExt2 => fixed(2),
+        Format::FixExt4 => fixed(4),
+        Format::FixExt8 => fixed(8),
+        Format::FixExt16 => fixed(16),
+        Format::Str8 | Format::Bin8 | Format::Ext8 => u8::try_from(len)
+            .ok()
+            .map(|n| Header::marker(format).with(&n.to_be_bytes())),
+        Format::Str16 | Format::Bin16 | Format::Array16 | Format::Map16 | Format::Ext16 => {
+            u16::try_from(len)
+                .ok()
+                .map(|n| Header::marker(format).with(&n.to_be_bytes()))
+        }
+        Format::Str32 | Format::Bin32 | Format::Array32 | Format::Map32 | Format::Ext32 => {
+            u32::try_from(len)
+                .ok()
+                .map(|n| Header::marker(format).with(&n.to_be_bytes()))
+        }
+        _ => None,
+    }
+}
+
+/// Complete encoding of a number, if it can be written as `format`.
+fn number_header(format: Format, n: Number) -> Option<Header> {
+    let v = match n {
+        Number::PositiveInt(v) => i128::from(v),
+        Number::NegativeInt(v) => i128::from(v),
+        Number::Float32(v) => {
+            return (format == Format::Float32)
+                .then(|| Header::marker(format).with(&v.to_be_bytes()));
+        }
+        Number::Float64(v) => {
+            return (format == Format::Float64)
+                .then(|| Header::marker(format).with(&v.to_be_bytes()));
+        }
+    };
+
+    macro_rules! int {
+        ($ty:ty) => {
+            <$ty>::try_from(v)
+                .ok()
+                .map(|v| Header::marker(format).with(&v.to_be_bytes()))
+        };
+    }
+    match format {
+        Format::PositiveFixInt(_) => u8::try_from(v)
+            .ok()
+            .filter(|v| *v <= 0x7f)
+            .map(|v| Header::marker(Format::PositiveFixInt(v))),
+        Format::NegativeFixInt(_) => i8::try_from(v)
+            .ok()
+            .filter(|v| (-32..=-1).contains(v))
+            .map(|v| Header::marker(Format::NegativeFixInt(v))),
+        Format::Uint8 => int!(u8),
+        Format::Uint16 => int!(u16),
+        Format::Uint32 => int!(u32),
+        Format::Uint64 => int!(u64),
+        Format::Int8 => int!(i8),
+        Format::Int16 => int!(i16),
+        Format::Int32 => int!(i32),
+        Format::Int64 => int!(i64),
+        _ => None,
+    }
+}
+
+/// Header for `kind` written as `format`, if the format can represent it.
+fn header_for(format: Format, kind: &PreservedKind) -> Option<Header> {
+    match (format, kind) {
+        (Format::Nil, PreservedKind::Nil) => Some(Header::marker(Format::Nil)),
+        (Format::False | Format::True, PreservedKind::Bool(b)) => Some(Header::marker(if *b {
+            Format::True
+        } else {
+            Format::False
+        })),
+        (_, PreservedKind::Number(n)) => number_header(format, *n),
+        (
+            Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32,
+            PreservedKind::String(s),
+        ) => len_header(format, s.len()),
+        (Format::Bin8 | Format::Bin16 | Format::Bin32, PreservedKind::Bin(b)) => {
+            len_header(format, b.len())
+        }
+        (Format::FixArray(_) | Format::Array16 | Format::Array32, PreservedKind::Array(items)) => {
+            len_header(format, items.len())
+        }
+        (Format::FixMap(_) | Format::Map16 | Format::Map32, PreservedKind::Map(entries)) => {
+            len_header(format, entries.len())
+        }
+        (
+            Format::FixExt1
+            | Format::FixExt2
+            | Format::FixExt4
+            | Format::FixExt8
+            | Format::FixExt16
+            | Format::Ext8
+            | Format::Ext16
+            | Format::Ext32,
+            PreservedKind::Extension(ext),
+        ) => len_header(format, ext.data.len()).map(|h| h.with(&ext.r#type.to_be_bytes())),
+        _ => None,
+    }
+}
+
+/// Candidate formats for `kind`, smallest first.
+fn candidates(kind: &PreservedKind) -> &'static [Format] {
+    match kind {
+        PreservedKind::Nil => &[Format::Nil],
+        PreservedKind::Bool(_) => &[Format::True],
+        PreservedKind::Number(_) => &[
+            Format::PositiveFixInt(0),
+            Format::NegativeFixInt(-1),
+            Format::Uint8,
+            Format::Int8,
+            Format::Uint16,
+            Format::Int16,
+            Format::Uint32,
+            Format::Int32,
+            Format::Uint64,
+            Format::Int64,
+            Format::Float32,
+            Format::Float64,
+        ],
+        PreservedKind::String(_) => &[
+            Format::FixStr(0),
+            Format::Str8,
+            Format::Str16,
+            Format::Str32,
+        ],
+        PreservedKind::Bin(_) => &[Format::Bin8, Format::Bin16, Format::Bin32],
+        PreservedKind::Extension(_) => &[
+            Format::FixExt1,
+            Format::FixExt2,
+            Format::FixExt4,
+            Format::FixExt8,
+            Format::FixExt16,
+            Format::Ext8,
+            Format::Ext16,
+            Format::Ext32,
+        ],
+        PreservedKind::Array(_) => &[Format::FixArray(0), Format::Array16, Format::Array32],
+        PreservedKind::Map(_) => &[Format::FixMap(0), Format::Map16, Format::Map32],
+    }
+}
+
+impl Encode for PreservedValue {
+    fn encode<W: IoWrite>(&self, writer: &mut W) -> Result<usize, EncodeError<W::Error>> {
+        let header = self
+            .format
+            .and_then(|f| header_for(f, &self.kind))
+            .or_else(|| {
+                candidates(&self.kind)
+                    .iter()
+                    .find_map(|f| header_for(*f, &self.kind))
+            })
+            .ok_or(EncodeError::InvalidFormat)?;
+        writer.write(header.as_slice())?;
+
+        let body = match &self.kind {
+            PreservedKind::String(s) => {
+                writer.write(s.as_bytes())?;
+                s.len()
+            }
+            PreservedKind::Bin(b) => {
+                writer.write(b)?;
+                b.len()
+            }
+            PreservedKind::Extension(ext) => {
+                writer.write(&ext.data)?;
+                ext.data.len()
+            }
+            PreservedKind::Array(items) => items.iter().try_fold(0, |acc, v| {
+                Ok::<_, EncodeError<W::Error>>(acc + v.encode(writer)?)
+            })?,
+            PreservedKind::Map(entries) => entries.iter().try_fold(0, |acc, (k, v)| {
+                Ok::<_, EncodeError<W::Error>>(acc + k.encode(writer)? + v.encode(writer)?)
+            })?,
+            PreservedKind::Nil | PreservedKind::Bool(_) | PreservedKind::Number(_) => 0,
+        };
+        Ok(header.len + body)
+    }
+}
+
+fn decode_preserved<'de, R>(
+    format: Format,
+    reader: &mut R,
+    depth: usize,
+) -> Result<PreservedValue, DecodeError<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let kind = match format {
+        Format::Nil => PreservedKind::Nil,
+        Format::False => PreservedKind::Bool(false),
+        Format::True => PreservedKind::Bool(true),
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+            let s = ReferenceStrDecoder::decode_with_format(format, reader)?;
+            PreservedKind::String(s.as_str().into())
+        }
+        Format::Bin8 | Format::Bin16 | Format::Bin32 => {
+            let b = ReferenceDecoder::decode_with_format(format, reader)?;
+            PreservedKind::Bin(b.as_bytes().to_vec())
+        }
+        Format::FixExt1
+        | Format::FixExt2
+        | Format::FixExt4
+        | Format::FixExt8
+        | Format::FixExt16
+        | Format::Ext8
+        | Format::Ext16
+        | Format::Ext32 => {
+            let (len, r#type) = read_ext_header(format, reader)?;
+            let data = reader.read_slice(len).map_err(DecodeError::Io)?;
+            PreservedKind::Extension(ExtensionOwned::new(r#type, data.as_bytes().to_vec()))
+        }
+        Format::FixArray(_) | Format::Array16 | Format::Array32 => {
+            let depth = enter(depth)?;
+            let len = array_len(format, reader)?;
+            let mut items = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                items.push(decode_preserved(format, reader, depth)?);
+            }
+            PreservedKind::Array(items)
+        }
+        Format::FixMap(_) | Format::Map16 | Format::Map32 => {
+            let depth = enter(depth)?;
+            let len = map_len(format, reader)?;
+            let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+            for _ in 0..len {
+                let format = Format::decode_borrowed(reader)?;
+                let k = decode_preserved(format, reader, depth)?;
+                let format = Format::decode_borrowed(reader)?;
+                let v = decode_preserved(format, reader, depth)?;
+                entries.push((k, v));
+            }
+            PreservedKind::Map(entries)
+        }
+        Format::NeverUsed => return Err(DecodeError::UnexpectedFormat),
+        _ => PreservedKind::Number(decode_number(format, reader)?),
+    };
+    Ok(PreservedValue::with_format(kind, format))
+}
+
+impl<'de> DecodeBorrowed<'de> for PreservedValue {
+    type Value = PreservedValue;
+
+    fn decode_borrowed_with_format<R>(
+        format: Format,
+        reader: &mut R,
+    ) -> Result<Self::Value, DecodeError<R::Error>>
+    where
+        R: IoRead<'de>,
+    {
+        decode_preserved(format, reader, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::SliceReader;
+    use alloc::vec;
+    use rstest::rstest;
+
+    fn roundtrip(buf: &[u8]) -> Vec<u8> {
+        let v = PreservedValue::decode(&mut SliceReader::new(buf)).unwrap();
+        let mut out = vec![];
+        let n = v.encode(&mut out).unwrap();
+        assert_eq!(n, out.len());
+        out
+    }
+
+    #[rstest]
+    #[case(&[0xc0])]
+    #[case(&[0xc2])]
+    #[case(&[0x05])]
+    #[case(&[0xcc, 0x05])]
+    #[case(&[0xce, 0x00, 0x00, 0x00, 0x05])]
+    #[case(&[0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05])]
+    #[case(&[0xd0, 0x05])]
+    #[case(&[0xd1, 0xff, 0xff])]
+    #[case(&[0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xe0])]
+    #[case(&[0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00])]
+    #[case(&[0xd9, 0x01, 0x61])]
+    #[case(&[0xda, 0x00, 0x01, 0x61])]
+    #[case(&[0xdb, 0x00, 0x00, 0x00, 0x01, 0x61])]
+    #[case(&[0xc5, 0x00, 0x01, 0xff])]
+    #[case(&[0xc7, 0x01, 0x05, 0xaa])]
+    #[case(&[0xd4, 0x05, 0xaa])]
+    #[case(&[0xdc, 0x00, 0x01, 0xc0])]
+    #[case(&[0xdf, 0x00, 0x00, 0x00, 0x01, 0xd9, 0x01, 0x6b, 0xcd, 0x00, 0x01])]
+    fn roundtrip_is_byte_identical(#[case] buf: &[u8]) {
+        assert_eq!(roundtrip(buf), buf);
+    }
+
+    #[test]
+    fn modified_node_falls_back_when_format_cannot_hold_it() {
+        // [uint8 5, fixstr "a", int16 1]
+        let buf: &[u8] = &[0x93, 0xcc, 0x05, 0xa1, 0x61, 0xd1, 0x00, 0x01];
+        let mut v = PreservedValue::decode(&mut SliceReader::new(buf)).unwrap();
+        let PreservedKind::Array(items) = v.kind_mut() else {
+            panic!("expected array");
+        };
+        *items[0].kind_mut() = PreservedKind::Number(Number::NegativeInt(-1));
+        *items[1].kind_mut() = PreservedKind::String("a".repeat(32));
+        items[2].set(PreservedKind::Number(Number::PositiveInt(1)));
+
+        let mut out = vec![];
+        v.encode(&mut out).unwrap();
+        let mut expected = vec![0x93, 0xff, 0xd9, 0x20];
+        expected.extend_from_slice("a".repeat(32).as_bytes());
+        expected.push(0x01);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn container_header_survives_child_edits() {
+        let buf: &[u8] = &[0xdc, 0x00, 0x02, 0x01, 0x02];
+        let mut v = PreservedValue::decode(&mut SliceReader::new(buf)).unwrap();
+        if let PreservedKind::Array(items) = v.kind_mut() {
+            items.pop();
+        }
+        let mut out = vec![];
+        v.encode(&mut out).unwrap();
+        assert_eq!(out, &[0xdc, 0x00, 0x01, 0x01]);
+
+        v.clear_format();
+        let mut out = vec![];
+        v.encode(&mut out).unwrap();
+        assert_eq!(out, &[0x91, 0x01]);
+    }
+
+    #[test]
+    fn new_nodes_are_minimized() {
+        let v = PreservedValue::from(Value::Array(vec![
+            Value::from(200u16),
+            Value::from(-100i64),
+            Value::from(false),
+            Value::Extension(ExtensionOwned::new(1, vec![0; 3])),
+        ]));
+        let mut out = vec![];
+        v.encode(&mut out).unwrap();
+        assert_eq!(
+            out,
+            &[
+                0x94, 0xcc, 0xc8, 0xd0, 0x9c, 0xc2, 0xc7, 0x03, 0x01, 0, 0, 0
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_to_value() {
+        let buf: &[u8] = &[0x81, 0xd9, 0x01, 0x6b, 0xcd, 0x00, 0x01];
+        let v = PreservedValue::decode(&mut SliceReader::new(buf)).unwrap();
+        assert_eq!(
+            Value::from(v),
+            Value::Map(vec![(Value::from("k"), Value::from(1u8))])
+        );
+    }
+}
@@ -0,0 +1,7 @@
+/// Encode `value` into a fresh buffer, checking the reported size.
+pub fn encode<T: messagepack_core::Encode>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let size = value.encode(&mut buf).unwrap();
+    assert_eq!(size, buf.len());
+    buf
+}
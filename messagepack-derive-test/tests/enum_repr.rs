@@ -0,0 +1,203 @@
+use messagepack_core::{
+    Decode as _, Encode as _,
+    decode::Error,
+    io::{SliceReader, StdReader},
+};
+use messagepack_derive::{Decode, Encode};
+
+fn round_trip<T>(value: &T, expected: &[u8])
+where
+    T: messagepack_core::Encode
+        + messagepack_core::decode::DecodeOwned
+        + PartialEq
+        + core::fmt::Debug,
+{
+    let mut buf = Vec::new();
+    let size = value.encode(&mut buf).unwrap();
+    assert_eq!(buf, expected);
+    assert_eq!(size, buf.len());
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(&T::decode(&mut reader).unwrap(), value);
+    assert!(reader.rest().is_empty());
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+enum Command {
+    Stop,
+    Move(i8),
+    Jump(u8, u8),
+    #[msgpack(key = 10)]
+    Say {
+        text: String,
+    },
+}
+
+#[test]
+fn index_repr() {
+    round_trip(&Command::Stop, &[0x00]);
+    round_trip(&Command::Move(-1), &[0x92, 0x01, 0xff]);
+    round_trip(&Command::Jump(2, 3), &[0x92, 0x02, 0x92, 0x02, 0x03]);
+    round_trip(
+        &Command::Say {
+            text: "hi".to_string(),
+        },
+        &[
+            0x92, 0x0a, 0x81, 0xa4, b't', b'e', b'x', b't', 0xa2, b'h', b'i',
+        ],
+    );
+
+    // Unknown ids, and non-unit variants without payload, are rejected
+    let mut reader = SliceReader::new(&[0x05]);
+    assert_eq!(
+        Command::decode(&mut reader).unwrap_err(),
+        Error::InvalidData
+    );
+    let mut reader = SliceReader::new(&[0x01]);
+    assert_eq!(
+        Command::decode(&mut reader).unwrap_err(),
+        Error::InvalidData
+    );
+
+    // Unit variants may also be written with a nil payload
+    let mut reader = SliceReader::new(&[0x92, 0x00, 0xc0]);
+    assert_eq!(Command::decode(&mut reader).unwrap(), Command::Stop);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(name)]
+enum Shape {
+    Empty,
+    Circle(u8),
+    Rect { w: u8, h: u8 },
+}
+
+#[test]
+fn name_repr() {
+    round_trip(&Shape::Empty, &[0xa5, b'E', b'm', b'p', b't', b'y']);
+    round_trip(
+        &Shape::Circle(3),
+        &[0x81, 0xa6, b'C', b'i', b'r', b'c', b'l', b'e', 0x03],
+    );
+    round_trip(
+        &Shape::Rect { w: 1, h: 2 },
+        &[
+            0x81, 0xa4, b'R', b'e', b'c', b't', 0x82, 0xa1, b'w', 0x01, 0xa1, b'h', 0x02,
+        ],
+    );
+
+    let mut reader = SliceReader::new(&[0xa3, b'B', b'o', b'x']);
+    assert_eq!(Shape::decode(&mut reader).unwrap_err(), Error::InvalidData);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(tag = "type")]
+enum Event {
+    Ping,
+    Message { from: String, id: u8 },
+}
+
+#[test]
+fn tag_repr() {
+    round_trip(
+        &Event::Ping,
+        &[
+            0x81, 0xa4, b't', b'y', b'p', b'e', 0xa4, b'P', b'i', b'n', b'g',
+        ],
+    );
+    let message = Event::Message {
+        from: "a".to_string(),
+        id: 7,
+    };
+    round_trip(
+        &message,
+        &[
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0xa7, b'M', b'e', b's', b's', b'a', b'g', b'e',
+            0xa4, b'f', b'r', b'o', b'm', 0xa1, b'a', 0xa2, b'i', b'd', 0x07,
+        ],
+    );
+
+    // The tag may come after the fields when decoding from a slice
+    let buf = [
+        0x83, 0xa2, b'i', b'd', 0x07, 0xa4, b'f', b'r', b'o', b'm', 0xa1, b'a', 0xa4, b't', b'y',
+        b'p', b'e', 0xa7, b'M', b'e', b's', b's', b'a', b'g', b'e',
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Event::decode(&mut reader).unwrap(), message);
+    assert!(reader.rest().is_empty());
+
+    // Streaming readers need it first
+    let mut reader = StdReader::new(buf.as_slice());
+    assert!(matches!(
+        Event::decode(&mut reader).unwrap_err(),
+        Error::UnexpectedFormat
+    ));
+
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+    let mut reader = StdReader::new(buf.as_slice());
+    assert_eq!(Event::decode(&mut reader).unwrap(), message);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(untagged)]
+enum Loose<'a> {
+    Nothing,
+    Number(u8),
+    Text(&'a str),
+    Pair(u8, &'a str),
+}
+
+#[test]
+fn untagged_repr() {
+    let cases: [(Loose<'_>, &[u8]); 4] = [
+        (Loose::Nothing, &[0xc0]),
+        (Loose::Number(5), &[0x05]),
+        (Loose::Text("x"), &[0xa1, b'x']),
+        (Loose::Pair(1, "y"), &[0x92, 0x01, 0xa1, b'y']),
+    ];
+    for (value, expected) in cases {
+        let mut buf = Vec::new();
+        value.encode(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+
+        let mut reader = SliceReader::new(&buf);
+        assert_eq!(Loose::decode(&mut reader).unwrap(), value);
+        assert!(reader.rest().is_empty());
+    }
+
+    let mut reader = SliceReader::new(&[0xc3]);
+    assert_eq!(Loose::decode(&mut reader).unwrap_err(), Error::InvalidData);
+
+    // Streaming readers cannot retry variants
+    let mut reader = StdReader::new([0x05].as_slice());
+    assert!(matches!(
+        Loose::decode(&mut reader).unwrap_err(),
+        Error::UnexpectedFormat
+    ));
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+enum Maybe<T> {
+    Nothing,
+    Just(T),
+}
+
+#[test]
+fn generic_enum() {
+    round_trip(&Maybe::<u16>::Nothing, &[0x00]);
+    round_trip(&Maybe::Just(300u16), &[0x92, 0x01, 0xcd, 0x01, 0x2c]);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(ext = 3)]
+enum Wrapped {
+    A,
+    B(u8),
+}
+
+#[test]
+fn enum_in_ext() {
+    round_trip(&Wrapped::A, &[0xd4, 0x03, 0x00]);
+    round_trip(&Wrapped::B(9), &[0xc7, 0x03, 0x03, 0x92, 0x01, 0x09]);
+}
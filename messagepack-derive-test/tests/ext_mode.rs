@@ -0,0 +1,103 @@
+use messagepack_core::{
+    Decode as _, Encode as _,
+    decode::Error,
+    io::{SliceReader, StdReader},
+};
+use messagepack_derive::{Decode, Encode};
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(ext = 7, array)]
+struct Point {
+    #[msgpack(key = 0)]
+    x: u8,
+    #[msgpack(key = 1)]
+    y: u8,
+    #[msgpack(key = 2)]
+    z: u8,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(ext = -5)]
+struct Tagged<'a>(&'a str);
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Shape {
+    name: String,
+    origin: Point,
+}
+
+#[test]
+fn ext_mode_wraps_payload() {
+    let point = Point { x: 1, y: 2, z: 3 };
+
+    let mut buf = Vec::new();
+    let size = point.encode(&mut buf).unwrap();
+
+    let expected = [
+        0xd6, 0x07, // fixext4, type 7
+        0x93, // fixarray 3
+        0x01, 0x02, 0x03, // 1, 2, 3
+    ];
+    assert_eq!(buf, expected);
+    assert_eq!(size, buf.len());
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Point::decode(&mut reader).unwrap(), point);
+    assert!(reader.rest().is_empty());
+}
+
+#[test]
+fn ext_mode_picks_header_by_length() {
+    let tagged = Tagged("hello");
+
+    let mut buf = Vec::new();
+    tagged.encode(&mut buf).unwrap();
+
+    let expected = [
+        0xc7, 0x07, 0xfb, // ext8, length 7, type -5
+        0x91, // fixarray 1
+        0xa5, b'h', b'e', b'l', b'l', b'o', // "hello"
+    ];
+    assert_eq!(buf, expected);
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Tagged::decode(&mut reader).unwrap(), tagged);
+}
+
+#[test]
+fn ext_mode_nested_in_struct() {
+    let shape = Shape {
+        name: "dot".to_string(),
+        origin: Point { x: 255, y: 0, z: 9 },
+    };
+
+    let mut buf = Vec::new();
+    shape.encode(&mut buf).unwrap();
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Shape::decode(&mut reader).unwrap(), shape);
+
+    let mut reader = StdReader::new(buf.as_slice());
+    assert_eq!(Shape::decode(&mut reader).unwrap(), shape);
+}
+
+#[test]
+fn ext_mode_rejects_other_payloads() {
+    // Wrong extension type
+    let buf = [0xd6, 0x08, 0x93, 0x01, 0x02, 0x03];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Point::decode(&mut reader).unwrap_err(), Error::InvalidData);
+
+    // Not an extension at all
+    let buf = [0x93, 0x01, 0x02, 0x03];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Point::decode(&mut reader).unwrap_err(),
+        Error::UnexpectedFormat
+    );
+
+    // Payload longer than the struct
+    let buf = [0xc7, 0x05, 0x07, 0x93, 0x01, 0x02, 0x03, 0xc0];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Point::decode(&mut reader).unwrap_err(), Error::InvalidData);
+}
@@ -0,0 +1,13 @@
+use messagepack_derive::Decode;
+
+#[derive(Default, Decode)]
+#[msgpack(default)]
+struct S {
+    a: String,
+}
+
+impl Drop for S {
+    fn drop(&mut self) {}
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use messagepack_derive::Decode;
+
+#[derive(Decode)]
+#[msgpack(default)]
+enum E {
+    A,
+}
+
+fn main() {}
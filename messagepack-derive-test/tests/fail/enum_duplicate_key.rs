@@ -0,0 +1,11 @@
+// Test: variant ids must be unique
+use messagepack_derive::{Encode, Decode};
+
+#[derive(Encode, Decode)]
+enum Op {
+    Add,
+    #[msgpack(key = 0)]
+    Sub,
+}
+
+fn main() {}
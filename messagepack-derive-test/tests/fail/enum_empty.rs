@@ -1,10 +1,7 @@
-// Test: enum should fail to derive
+// Test: enums need at least one variant
 use messagepack_derive::{Encode, Decode};
 
 #[derive(Encode, Decode)]
-enum MyEnum {
-    A,
-    B,
-}
+enum MyEnum {}
 
 fn main() {}
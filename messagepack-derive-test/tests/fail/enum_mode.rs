@@ -0,0 +1,10 @@
+// Test: enums choose a representation instead of map/array
+use messagepack_derive::{Encode, Decode};
+
+#[derive(Encode, Decode)]
+#[msgpack(array)]
+enum Op {
+    Add,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Test: internally tagged enums cannot hold tuple variants
+use messagepack_derive::{Encode, Decode};
+
+#[derive(Encode, Decode)]
+#[msgpack(tag = "type")]
+enum Event {
+    Ping,
+    Data(u8, u8),
+}
+
+fn main() {}
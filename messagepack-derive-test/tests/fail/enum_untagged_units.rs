@@ -0,0 +1,11 @@
+// Test: untagged enums cannot tell unit variants apart
+use messagepack_derive::{Encode, Decode};
+
+#[derive(Encode, Decode)]
+#[msgpack(untagged)]
+enum Either {
+    Left,
+    Right,
+}
+
+fn main() {}
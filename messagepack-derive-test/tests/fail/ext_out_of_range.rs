@@ -0,0 +1,16 @@
+// Test: the extension type must fit in i8
+use messagepack_derive::{Encode, Decode};
+
+#[derive(Encode, Decode)]
+#[msgpack(ext = 128)]
+struct Big {
+    x: u32,
+}
+
+fn main() {}
+
+#[derive(Encode, Decode)]
+#[msgpack(ext = -129)]
+struct Small {
+    x: u32,
+}
@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+#[msgpack(array)]
+struct S {
+    #[msgpack(key = 0)]
+    a: u8,
+    #[msgpack(flatten)]
+    rest: HashMap<String, u8>,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+struct Header {
+    id: u8,
+}
+
+#[derive(Encode)]
+struct Request {
+    #[msgpack(flatten)]
+    header: Header,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+#[msgpack(array, flattenable)]
+struct S {
+    #[msgpack(key = 0)]
+    a: u8,
+}
+
+fn main() {}
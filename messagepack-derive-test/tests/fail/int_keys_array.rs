@@ -0,0 +1,10 @@
+use messagepack_derive::Decode;
+
+#[derive(Decode)]
+#[msgpack(array, int_keys)]
+struct S {
+    #[msgpack(key = 0)]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+#[msgpack(int_keys)]
+struct S {
+    #[msgpack(key = 0)]
+    a: u8,
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+#[msgpack(rename_all = "Title Case")]
+struct S {
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use messagepack_derive::Decode;
+
+#[derive(Decode)]
+struct S {
+    #[msgpack(rename = "b")]
+    a: u8,
+    #[msgpack(alias = "b")]
+    c: u8,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use messagepack_derive::Encode;
+
+#[derive(Encode)]
+#[msgpack(array)]
+struct S {
+    #[msgpack(key = 0)]
+    a: u8,
+    #[msgpack(key = 1, skip_encoding_if = "Option::is_none")]
+    b: Option<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,171 @@
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use messagepack_core::{
+    Decode as _,
+    decode::Error,
+    io::{SliceReader, StdReader},
+};
+use messagepack_derive::{Decode, Encode};
+
+use common::encode;
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(flattenable)]
+struct Header {
+    id: u8,
+    #[msgpack(skip_encoding_if = "Option::is_none")]
+    trace: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(flattenable)]
+struct Request {
+    #[msgpack(flatten)]
+    header: Header,
+    method: String,
+}
+
+#[test]
+fn flatten_inlines_entries() {
+    let request = Request {
+        header: Header { id: 1, trace: None },
+        method: "get".to_string(),
+    };
+    let buf = encode(&request);
+    assert_eq!(
+        buf,
+        [
+            0x82, // fixmap 2
+            0xa2, b'i', b'd', 0x01, // id: 1
+            0xa6, b'm', b'e', b't', b'h', b'o', b'd', 0xa3, b'g', b'e', b't', // method
+        ]
+    );
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Request::decode(&mut reader).unwrap(), request);
+
+    let request = Request {
+        header: Header {
+            id: 1,
+            trace: Some(9),
+        },
+        ..request
+    };
+    let buf = encode(&request);
+    assert_eq!(buf[0], 0x83);
+    let mut reader = StdReader::new(buf.as_slice());
+    assert_eq!(Request::decode(&mut reader).unwrap(), request);
+}
+
+#[test]
+fn flatten_keys_in_any_order() {
+    let buf = [
+        0x83, 0xa6, b'm', b'e', b't', b'h', b'o', b'd', 0xa1, b'x', 0xa5, b'e', b'x', b't', b'r',
+        b'a', 0xc0, 0xa2, b'i', b'd', 0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Request::decode(&mut reader).unwrap(),
+        Request {
+            header: Header { id: 2, trace: None },
+            method: "x".to_string(),
+        }
+    );
+    assert!(reader.rest().is_empty());
+
+    // Missing flattened fields are reported like any other
+    let buf = [0x81, 0xa6, b'm', b'e', b't', b'h', b'o', b'd', 0xa1, b'x'];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Request::decode(&mut reader).unwrap_err(),
+        Error::InvalidData
+    );
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Document {
+    title: String,
+    #[msgpack(flatten)]
+    extra: HashMap<String, u8>,
+}
+
+#[test]
+fn flatten_map_collects_unknown_keys() {
+    let buf = [
+        0x83, 0xa1, b'a', 0x01, 0xa5, b't', b'i', b't', b'l', b'e', 0xa1, b't', 0xa1, b'b', 0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    let document = Document::decode(&mut reader).unwrap();
+    assert_eq!(document.title, "t");
+    assert_eq!(
+        document.extra,
+        HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)])
+    );
+
+    let buf = encode(&document);
+    assert_eq!(buf[0], 0x83);
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Document::decode(&mut reader).unwrap(), document);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Nested {
+    #[msgpack(flatten)]
+    request: Request,
+    #[msgpack(flatten)]
+    rest: BTreeMap<String, bool>,
+}
+
+#[test]
+fn nested_flatten() {
+    let nested = Nested {
+        request: Request {
+            header: Header { id: 3, trace: None },
+            method: "put".to_string(),
+        },
+        rest: BTreeMap::from([("ok".to_string(), true)]),
+    };
+    let buf = encode(&nested);
+    assert_eq!(buf[0], 0x83);
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Nested::decode(&mut reader).unwrap(), nested);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Generic<T> {
+    #[msgpack(flatten)]
+    inner: T,
+    version: u8,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(name)]
+enum Envelope {
+    Wrapped {
+        #[msgpack(flatten)]
+        header: Header,
+        body: u8,
+    },
+}
+
+#[test]
+fn flatten_generic_and_variant() {
+    let value = Generic {
+        inner: Header { id: 4, trace: None },
+        version: 1,
+    };
+    let buf = encode(&value);
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Generic::<Header>::decode(&mut reader).unwrap(), value);
+
+    let envelope = Envelope::Wrapped {
+        header: Header { id: 5, trace: None },
+        body: 6,
+    };
+    let buf = encode(&envelope);
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Envelope::decode(&mut reader).unwrap(), envelope);
+}
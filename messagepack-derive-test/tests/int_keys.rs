@@ -0,0 +1,98 @@
+use messagepack_core::{Decode as _, Encode as _, decode::Error, io::SliceReader};
+use messagepack_derive::{Decode, Encode};
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(map, int_keys)]
+struct Item {
+    #[msgpack(key = 0)]
+    id: u16,
+    #[msgpack(key = 1)]
+    name: String,
+    #[msgpack(key = 200)]
+    note: Option<u8>,
+}
+
+#[test]
+fn int_keys_round_trip() {
+    let item = Item {
+        id: 300,
+        name: "a".to_string(),
+        note: Some(4),
+    };
+
+    let mut buf = Vec::new();
+    let size = item.encode(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [
+            0x83, // fixmap 3
+            0x00, 0xcd, 0x01, 0x2c, // 0: 300
+            0x01, 0xa1, b'a', // 1: "a"
+            0xcc, 0xc8, 0x04, // 200: 4
+        ]
+    );
+    assert_eq!(size, buf.len());
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Item::decode(&mut reader).unwrap(), item);
+    assert!(reader.rest().is_empty());
+}
+
+#[test]
+fn int_keys_any_order_and_unknown_keys() {
+    let buf = [
+        0x85, // fixmap 5
+        0x01, 0xa1, b'b', // 1: "b"
+        0x07, 0x92, 0x01, 0x02, // 7: [1, 2], unknown
+        0xa2, b'i', b'd', 0xc3, // "id": true, not an integer key
+        0xcd, 0x00, 0x00, 0xcd, 0x00, 0x05, // 0 as uint16: 5
+        0xcc, 0xc8, 0xc0, // 200: nil
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Item::decode(&mut reader).unwrap(),
+        Item {
+            id: 5,
+            name: "b".to_string(),
+            note: None,
+        }
+    );
+    assert!(reader.rest().is_empty());
+}
+
+#[test]
+fn int_keys_reject_duplicates_and_missing() {
+    let buf = [
+        0x83, 0x00, 0xcd, 0x00, 0x01, 0x01, 0xa1, b'a', 0x00, 0xcd, 0x00, 0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Item::decode(&mut reader).unwrap_err(), Error::InvalidData);
+
+    let buf = [0x81, 0x01, 0xa1, b'a'];
+    let mut reader = SliceReader::new(&buf);
+    assert!(Item::decode(&mut reader).is_err());
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(name)]
+enum Message {
+    #[msgpack(int_keys)]
+    Hello {
+        #[msgpack(key = 1)]
+        version: u8,
+    },
+}
+
+#[test]
+fn int_keys_on_variant() {
+    let message = Message::Hello { version: 2 };
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [0x81, 0xa5, b'H', b'e', b'l', b'l', b'o', 0x81, 0x01, 0x02]
+    );
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Message::decode(&mut reader).unwrap(), message);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use messagepack_core::{Decode as _, decode::Error, io::SliceReader};
+use messagepack_derive::{Decode, Encode};
+
+use common::encode;
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(rename_all = "camelCase")]
+struct User {
+    user_id: u8,
+    #[msgpack(rename = "mail", alias = "email", alias = "e_mail")]
+    email_address: String,
+    r#type: u8,
+}
+
+#[test]
+fn rename_all_and_rename() {
+    let user = User {
+        user_id: 1,
+        email_address: "a".to_string(),
+        r#type: 2,
+    };
+    let buf = encode(&user);
+    assert_eq!(
+        buf,
+        [
+            0x83, 0xa6, b'u', b's', b'e', b'r', b'I', b'd', 0x01, 0xa4, b'm', b'a', b'i', b'l',
+            0xa1, b'a', 0xa4, b't', b'y', b'p', b'e', 0x02,
+        ]
+    );
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(User::decode(&mut reader).unwrap(), user);
+}
+
+#[test]
+fn alias_is_accepted_on_decode() {
+    let buf = [
+        0x83, 0xa6, b'u', b's', b'e', b'r', b'I', b'd', 0x01, 0xa6, b'e', b'_', b'm', b'a', b'i',
+        b'l', 0xa1, b'a', 0xa4, b't', b'y', b'p', b'e', 0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        User::decode(&mut reader).unwrap().email_address,
+        "a".to_string()
+    );
+
+    // The Rust identifier is no longer a key
+    let buf = [
+        0x83, 0xa6, b'u', b's', b'e', b'r', b'I', b'd', 0x01, 0xad, b'e', b'm', b'a', b'i', b'l',
+        b'_', b'a', b'd', b'd', b'r', b'e', b's', b's', 0xa1, b'a', 0xa4, b't', b'y', b'p', b'e',
+        0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(User::decode(&mut reader).unwrap_err(), Error::InvalidData);
+
+    // A key and its alias together are a duplicate
+    let buf = [
+        0x84, 0xa6, b'u', b's', b'e', b'r', b'I', b'd', 0x01, 0xa4, b'm', b'a', b'i', b'l', 0xa1,
+        b'a', 0xa5, b'e', b'm', b'a', b'i', b'l', 0xa1, b'b', 0xa4, b't', b'y', b'p', b'e', 0x02,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(User::decode(&mut reader).unwrap_err(), Error::InvalidData);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(tag = "kind", rename_all = "snake_case")]
+enum Event {
+    #[msgpack(alias = "ping")]
+    HeartBeat,
+    #[msgpack(rename_all = "SCREAMING_SNAKE_CASE")]
+    UserJoined { user_id: u8 },
+}
+
+#[test]
+fn enum_rename_all() {
+    let buf = encode(&Event::HeartBeat);
+    assert_eq!(
+        buf,
+        [
+            0x81, 0xa4, b'k', b'i', b'n', b'd', 0xaa, b'h', b'e', b'a', b'r', b't', b'_', b'b',
+            b'e', b'a', b't',
+        ]
+    );
+
+    let joined = Event::UserJoined { user_id: 3 };
+    let buf = encode(&joined);
+    assert_eq!(
+        buf,
+        [
+            0x82, 0xa4, b'k', b'i', b'n', b'd', 0xab, b'u', b's', b'e', b'r', b'_', b'j', b'o',
+            b'i', b'n', b'e', b'd', 0xa7, b'U', b'S', b'E', b'R', b'_', b'I', b'D', 0x03,
+        ]
+    );
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Event::decode(&mut reader).unwrap(), joined);
+
+    let buf = [
+        0x81, 0xa4, b'k', b'i', b'n', b'd', 0xa4, b'p', b'i', b'n', b'g',
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Event::decode(&mut reader).unwrap(), Event::HeartBeat);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(name, rename_all = "kebab-case")]
+enum Level {
+    VeryHigh,
+    #[msgpack(rename = "lo")]
+    Low,
+}
+
+#[test]
+fn name_enum_rename() {
+    assert_eq!(
+        encode(&Level::VeryHigh),
+        [0xa9, b'v', b'e', b'r', b'y', b'-', b'h', b'i', b'g', b'h']
+    );
+    let buf = encode(&Level::Low);
+    assert_eq!(buf, [0xa2, b'l', b'o']);
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Level::decode(&mut reader).unwrap(), Level::Low);
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use messagepack_core::{Decode as _, io::SliceReader};
+use messagepack_derive::{Decode, Encode};
+
+use common::encode;
+
+fn seven() -> u8 {
+    7
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct Config {
+    name: String,
+    #[msgpack(skip)]
+    cache: Vec<u8>,
+    #[msgpack(skip_encoding_if = "Option::is_none")]
+    note: Option<String>,
+    #[msgpack(skip_encoding_if = "Vec::is_empty", default)]
+    tags: Vec<u8>,
+    #[msgpack(default = "seven")]
+    level: u8,
+}
+
+#[test]
+fn skip_fields_are_not_encoded() {
+    let config = Config {
+        name: "a".to_string(),
+        cache: vec![1, 2, 3],
+        note: None,
+        tags: Vec::new(),
+        level: 1,
+    };
+    let buf = encode(&config);
+    assert_eq!(
+        buf,
+        [
+            0x82, // fixmap 2
+            0xa4, b'n', b'a', b'm', b'e', 0xa1, b'a', // name
+            0xa5, b'l', b'e', b'v', b'e', b'l', 0x01, // level
+        ]
+    );
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Config::decode(&mut reader).unwrap(),
+        Config {
+            cache: Vec::new(),
+            ..config
+        }
+    );
+}
+
+#[test]
+fn skip_encoding_if_counts_present_fields() {
+    let config = Config {
+        name: "a".to_string(),
+        cache: Vec::new(),
+        note: Some("n".to_string()),
+        tags: vec![9],
+        level: 1,
+    };
+    let buf = encode(&config);
+    assert_eq!(buf[0], 0x84);
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Config::decode(&mut reader).unwrap(), config);
+}
+
+#[test]
+fn default_fn_fills_missing_field() {
+    let buf = [0x81, 0xa4, b'n', b'a', b'm', b'e', 0xa1, b'b'];
+    let mut reader = SliceReader::new(&buf);
+    let config = Config::decode(&mut reader).unwrap();
+    assert_eq!(config.level, 7);
+    assert_eq!(config.note, None);
+    assert!(config.tags.is_empty());
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(default)]
+struct Settings {
+    width: u16,
+    height: u16,
+    #[msgpack(default)]
+    depth: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 480,
+            depth: 32,
+        }
+    }
+}
+
+#[test]
+fn container_default_fills_missing_fields() {
+    let buf = [
+        0x81, 0xa6, b'h', b'e', b'i', b'g', b'h', b't', 0xcd, 0x02, 0x58,
+    ];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Settings::decode(&mut reader).unwrap(),
+        Settings {
+            width: 640,
+            height: 600,
+            depth: 0,
+        }
+    );
+}
+
+thread_local! {
+    /// Calls of `Counted::default` on this test thread.
+    static COUNTED_DEFAULTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(default)]
+struct Counted {
+    a: String,
+    b: String,
+    #[msgpack(skip)]
+    c: String,
+}
+
+impl Default for Counted {
+    fn default() -> Self {
+        COUNTED_DEFAULTS.set(COUNTED_DEFAULTS.get() + 1);
+        Self {
+            a: "a".into(),
+            b: "b".into(),
+            c: "c".into(),
+        }
+    }
+}
+
+#[test]
+fn container_default_is_built_once() {
+    let calls = || COUNTED_DEFAULTS.get();
+
+    let mut reader = SliceReader::new(&[0x80]);
+    assert_eq!(
+        Counted::decode(&mut reader).unwrap(),
+        Counted {
+            a: "a".into(),
+            b: "b".into(),
+            c: "c".into(),
+        }
+    );
+    assert_eq!(calls(), 1);
+
+    let buf = [0x82, 0xa1, b'a', 0xa1, b'x', 0xa1, b'b', 0xa1, b'y'];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Counted::decode(&mut reader).unwrap(),
+        Counted {
+            a: "x".into(),
+            b: "y".into(),
+            c: "c".into(),
+        }
+    );
+    assert_eq!(calls(), 2);
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(default)]
+struct Pair<T>(T, T);
+
+impl<T: From<u8>> Default for Pair<T> {
+    fn default() -> Self {
+        Self(T::from(1), T::from(2))
+    }
+}
+
+#[test]
+fn container_default_on_tuple_struct() {
+    let buf = [0x91, 0xcd, 0x01, 0x00];
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(Pair::<u16>::decode(&mut reader).unwrap(), Pair(256, 2));
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(array)]
+struct Skipped {
+    #[msgpack(key = 0)]
+    a: u8,
+    #[msgpack(skip)]
+    b: u8,
+    #[msgpack(key = 1)]
+    c: u8,
+}
+
+#[test]
+fn skip_in_array_mode() {
+    let value = Skipped { a: 1, b: 9, c: 2 };
+    let buf = encode(&value);
+    assert_eq!(buf, [0x92, 0x01, 0x02]);
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Skipped::decode(&mut reader).unwrap(),
+        Skipped { b: 0, ..value }
+    );
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+#[msgpack(name)]
+enum Message {
+    Note {
+        #[msgpack(skip_encoding_if = "Option::is_none")]
+        text: Option<u8>,
+        #[msgpack(skip)]
+        seen: bool,
+    },
+}
+
+#[test]
+fn skip_in_variant() {
+    let buf = encode(&Message::Note {
+        text: None,
+        seen: true,
+    });
+    assert_eq!(buf, [0x81, 0xa4, b'N', b'o', b't', b'e', 0x80]);
+
+    let mut reader = SliceReader::new(&buf);
+    assert_eq!(
+        Message::decode(&mut reader).unwrap(),
+        Message::Note {
+            text: None,
+            seen: false,
+        }
+    );
+}
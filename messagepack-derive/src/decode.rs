@@ -1,15 +1,16 @@
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use syn::DeriveInput;
 use syn::punctuated::Punctuated;
 
 use crate::shared::{
-    ContainerMode, DeriveKind, FieldInfo, StructStyle, add_type_bound, box_inner,
-    collect_bound_types, decode_lifetime, option_inner, parse_struct, replace_lifetimes,
+    ContainerMode, DeriveKind, EnumRepr, FieldDefault, FieldInfo, InputData, StructStyle,
+    VariantInfo, add_type_bound, box_inner, collect_bound_types, decode_lifetime, field_local,
+    int_key_fields, option_inner, parse_input, replace_lifetimes,
 };
 
 pub fn derive_decode(input: DeriveInput) -> syn::Result<TokenStream> {
-    let info = parse_struct(input, DeriveKind::Decode)?;
+    let info = parse_input(input, DeriveKind::Decode)?;
     let name = &info.ident;
 
     let de_lifetime = decode_lifetime();
@@ -49,20 +50,50 @@ pub fn derive_decode(input: DeriveInput) -> syn::Result<TokenStream> {
             .push(syn::parse_quote!(#ident: #de_lifetime));
     }
 
-    add_decode_bounds(&mut generics, &info.style, &de_lifetime);
+    add_decode_bounds(&mut generics, &info.data.fields(), &de_lifetime);
+    if info.container.default.is_some() {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#output_ty: ::core::default::Default));
+    }
 
-    let body = match &info.style {
-        StructStyle::Unit => decode_unit(),
-        StructStyle::Tuple(fields) => {
-            decode_tuple(fields, &de_lifetime, info.container.mode, &output_ty)?
-        }
-        StructStyle::Named(fields) => {
-            decode_named(fields, &de_lifetime, info.container.mode, &output_ty)?
+    let body = match &info.data {
+        InputData::Struct(StructStyle::Unit) => decode_unit(&quote! { Self }),
+        InputData::Struct(style) => {
+            decode_style(style, &de_lifetime, info.container.mode, &output_ty)?
         }
+        InputData::Enum(repr, variants) => decode_enum(repr, variants, &de_lifetime)?,
+    };
+
+    let body = match info.container.ext {
+        Some(ext_type) => quote! {
+            ::messagepack_core::extension::decode_with(__format, __reader, #ext_type, |__reader| {
+                let __format = <::messagepack_core::Format as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed(__reader)?;
+                #body
+            })?
+            .map_err(|_| ::messagepack_core::decode::Error::InvalidData)
+        },
+        None => body,
     };
 
     let (impl_generics, _, where_clause) = generics.split_for_impl();
 
+    let entries_impl = match &info.data {
+        InputData::Struct(StructStyle::Named(fields)) if info.container.flattenable.is_some() => {
+            let items = decode_map_entries_impl(fields, &de_lifetime, &output_ty)?;
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::messagepack_core::decode::DecodeMapEntries<#de_lifetime> for #output_ty
+                    #where_clause
+                {
+                    #items
+                }
+            }
+        }
+        _ => TokenStream::new(),
+    };
+
     Ok(quote! {
         #[automatically_derived]
         impl #impl_generics ::messagepack_core::decode::DecodeBorrowed<#de_lifetime> for #output_ty
@@ -80,25 +111,337 @@ pub fn derive_decode(input: DeriveInput) -> syn::Result<TokenStream> {
                 #body
             }
         }
+
+        #entries_impl
     })
 }
 
-fn decode_unit() -> TokenStream {
+fn decode_unit(output: &TokenStream) -> TokenStream {
     quote! {
         match __format {
-            ::messagepack_core::Format::Nil => Ok(Self),
+            ::messagepack_core::Format::Nil => Ok(#output),
             _ => Err(::messagepack_core::decode::Error::UnexpectedFormat),
         }
     }
 }
 
+/// Decode a struct or variant body, given `__format`.
+fn decode_style(
+    style: &StructStyle,
+    de_lifetime: &syn::Lifetime,
+    mode: Option<ContainerMode>,
+    output: &TokenStream,
+) -> syn::Result<TokenStream> {
+    match style {
+        StructStyle::Unit => Ok(decode_unit(output)),
+        StructStyle::Tuple(fields) => decode_tuple(fields, de_lifetime, mode, output),
+        StructStyle::Named(fields) => decode_named(fields, de_lifetime, mode, output),
+    }
+}
+
+fn decode_enum(
+    repr: &EnumRepr,
+    variants: &[VariantInfo],
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    match repr {
+        EnumRepr::Index => decode_index_enum(variants, de_lifetime),
+        EnumRepr::Name => decode_name_enum(variants, de_lifetime),
+        EnumRepr::Tag(tag) => decode_tag_enum(tag, variants, de_lifetime),
+        EnumRepr::Untagged => decode_untagged_enum(variants, de_lifetime),
+    }
+}
+
+/// Decode the payload of a variant which follows its id or name.
+fn decode_variant_payload(
+    variant: &VariantInfo,
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    let ident = &variant.ident;
+    if let Some(field) = variant.style.newtype().filter(|_| variant.is_newtype()) {
+        let expr = decode_field_expr(field, de_lifetime)?;
+        return Ok(quote! {
+            Ok(Self::#ident(#expr))
+        });
+    }
+    let body = decode_style(
+        &variant.style,
+        de_lifetime,
+        variant.attrs.mode,
+        &quote! { Self::#ident },
+    )?;
+    Ok(quote! {{
+        let __format = <::messagepack_core::Format as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed(__reader)?;
+        #body
+    }})
+}
+
+/// `b"name"`, or `b"name" | b"alias"` when the variant has aliases.
+fn variant_name_literal(variant: &VariantInfo) -> TokenStream {
+    let names = std::iter::once(&variant.name)
+        .chain(&variant.attrs.aliases)
+        .map(|name| syn::LitByteStr::new(name.as_bytes(), variant.span));
+    quote! { #(#names)|* }
+}
+
+fn decode_index_enum(
+    variants: &[VariantInfo],
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    let payload_arms = variants
+        .iter()
+        .map(|variant| {
+            let id = proc_macro2::Literal::u64_suffixed(variant.id);
+            let payload = decode_variant_payload(variant, de_lifetime)?;
+            Ok(quote! { #id => #payload, })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let unit_arms = variants
+        .iter()
+        .filter(|variant| matches!(variant.style, StructStyle::Unit))
+        .map(|variant| {
+            let id = proc_macro2::Literal::u64_suffixed(variant.id);
+            let ident = &variant.ident;
+            quote! { #id => Ok(Self::#ident), }
+        });
+
+    Ok(quote! {
+        fn __variant_id<'__de, __R>(
+            __format: ::messagepack_core::Format,
+            __reader: &mut __R,
+        ) -> ::core::result::Result<u64, ::messagepack_core::decode::Error<__R::Error>>
+        where
+            __R: ::messagepack_core::io::IoRead<'__de>,
+        {
+            match __format {
+                ::messagepack_core::Format::PositiveFixInt(__id) => Ok(__id.into()),
+                ::messagepack_core::Format::Uint8 => {
+                    <u8 as ::messagepack_core::decode::DecodeBorrowed<'__de>>::decode_borrowed_with_format(__format, __reader).map(u64::from)
+                }
+                ::messagepack_core::Format::Uint16 => {
+                    <u16 as ::messagepack_core::decode::DecodeBorrowed<'__de>>::decode_borrowed_with_format(__format, __reader).map(u64::from)
+                }
+                ::messagepack_core::Format::Uint32 => {
+                    <u32 as ::messagepack_core::decode::DecodeBorrowed<'__de>>::decode_borrowed_with_format(__format, __reader).map(u64::from)
+                }
+                ::messagepack_core::Format::Uint64 => {
+                    <u64 as ::messagepack_core::decode::DecodeBorrowed<'__de>>::decode_borrowed_with_format(__format, __reader)
+                }
+                _ => Err(::messagepack_core::decode::Error::UnexpectedFormat),
+            }
+        }
+
+        let __len = match __format {
+            ::messagepack_core::Format::FixArray(__len) => usize::from(__len),
+            ::messagepack_core::Format::Array16 => ::messagepack_core::decode::NbyteReader::<2>::read(__reader)?,
+            ::messagepack_core::Format::Array32 => ::messagepack_core::decode::NbyteReader::<4>::read(__reader)?,
+            __format => {
+                return match __variant_id(__format, __reader)? {
+                    #(#unit_arms)*
+                    _ => Err(::messagepack_core::decode::Error::InvalidData),
+                };
+            }
+        };
+        if __len != 2 {
+            return Err(::messagepack_core::decode::Error::InvalidData);
+        }
+        let __id_format = <::messagepack_core::Format as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed(__reader)?;
+        match __variant_id(__id_format, __reader)? {
+            #(#payload_arms)*
+            _ => Err(::messagepack_core::decode::Error::InvalidData),
+        }
+    })
+}
+
+fn decode_name_enum(
+    variants: &[VariantInfo],
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    let payload_arms = variants
+        .iter()
+        .map(|variant| {
+            let name = variant_name_literal(variant);
+            let payload = decode_variant_payload(variant, de_lifetime)?;
+            Ok(quote! { #name => #payload, })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let unit_arms = variants
+        .iter()
+        .filter(|variant| matches!(variant.style, StructStyle::Unit))
+        .map(|variant| {
+            let name = variant_name_literal(variant);
+            let ident = &variant.ident;
+            quote! { #name => Ok(Self::#ident), }
+        });
+
+    Ok(quote! {
+        let __len = match __format {
+            ::messagepack_core::Format::FixMap(__len) => usize::from(__len),
+            ::messagepack_core::Format::Map16 => ::messagepack_core::decode::NbyteReader::<2>::read(__reader)?,
+            ::messagepack_core::Format::Map32 => ::messagepack_core::decode::NbyteReader::<4>::read(__reader)?,
+            __format => {
+                let __name = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode_with_format(__format, __reader)?;
+                return match __name.as_bytes() {
+                    #(#unit_arms)*
+                    _ => Err(::messagepack_core::decode::Error::InvalidData),
+                };
+            }
+        };
+        if __len != 1 {
+            return Err(::messagepack_core::decode::Error::InvalidData);
+        }
+        let __name = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+        match __name.as_bytes() {
+            #(#payload_arms)*
+            _ => Err(::messagepack_core::decode::Error::InvalidData),
+        }
+    })
+}
+
+/// The tag is looked up across the whole map when the reader exposes its
+/// input, and must be the first entry otherwise.
+fn decode_tag_enum(
+    tag: &str,
+    variants: &[VariantInfo],
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    let tag = syn::LitByteStr::new(tag.as_bytes(), proc_macro2::Span::call_site());
+    let index_arms = variants.iter().enumerate().map(|(index, variant)| {
+        let name = variant_name_literal(variant);
+        quote! { #name => #index, }
+    });
+    let index_arms = quote! { #(#index_arms)* };
+    let body_arms = variants
+        .iter()
+        .enumerate()
+        .map(|(index, variant)| {
+            let ident = &variant.ident;
+            let body = match &variant.style {
+                StructStyle::Named(fields) => {
+                    decode_named_map(fields, de_lifetime, &quote! { Self::#ident })?
+                }
+                _ => quote! {
+                    for _ in 0..__len {
+                        let _ = <::messagepack_core::decode::Any<#de_lifetime> as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+                        let _ = <::messagepack_core::decode::Any<#de_lifetime> as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+                    }
+                    Ok(Self::#ident)
+                },
+            };
+            Ok(quote! { #index => { #body } })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        let __len = match __format {
+            ::messagepack_core::Format::FixMap(__len) => usize::from(__len),
+            ::messagepack_core::Format::Map16 => ::messagepack_core::decode::NbyteReader::<2>::read(__reader)?,
+            ::messagepack_core::Format::Map32 => ::messagepack_core::decode::NbyteReader::<4>::read(__reader)?,
+            _ => return Err(::messagepack_core::decode::Error::UnexpectedFormat),
+        };
+        let __find = |__rest: &#de_lifetime [u8]| -> ::core::result::Result<::core::option::Option<usize>, ::messagepack_core::decode::Error<::messagepack_core::io::RError>> {
+            let mut __peek = ::messagepack_core::io::SliceReader::new(__rest);
+            for _ in 0..__len {
+                let __key = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(&mut __peek)?;
+                if __key.as_bytes() == #tag {
+                    let __name = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(&mut __peek)?;
+                    return Ok(::core::option::Option::Some(match __name.as_bytes() {
+                        #index_arms
+                        _ => return Err(::messagepack_core::decode::Error::InvalidData),
+                    }));
+                }
+                let _ = <::messagepack_core::decode::Any<#de_lifetime> as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(&mut __peek)?;
+            }
+            Ok(::core::option::Option::None)
+        };
+        let (__variant, __len) = match ::messagepack_core::io::IoRead::remaining_slice(__reader) {
+            ::core::option::Option::Some(__rest) => {
+                match __find(__rest).map_err(|_| ::messagepack_core::decode::Error::InvalidData)? {
+                    ::core::option::Option::Some(__variant) => (__variant, __len),
+                    ::core::option::Option::None => return Err(::messagepack_core::decode::Error::InvalidData),
+                }
+            }
+            ::core::option::Option::None => {
+                if __len == 0 {
+                    return Err(::messagepack_core::decode::Error::InvalidData);
+                }
+                let __key = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+                // The tag may be further on, but cannot be looked for
+                // without the input
+                if __key.as_bytes() != #tag {
+                    return Err(::messagepack_core::decode::Error::UnexpectedFormat);
+                }
+                let __name = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+                let __variant = match __name.as_bytes() {
+                    #index_arms
+                    _ => return Err(::messagepack_core::decode::Error::InvalidData),
+                };
+                (__variant, __len - 1)
+            }
+        };
+        match __variant {
+            #(#body_arms)*
+            _ => unreachable!(),
+        }
+    })
+}
+
+/// Try each variant in order against the unread input, which requires a
+/// reader exposing it; other readers get `UnexpectedFormat`.
+fn decode_untagged_enum(
+    variants: &[VariantInfo],
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    let attempts = variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let body = match variant.style.newtype().filter(|_| variant.is_newtype()) {
+                Some(field) => {
+                    let expr = decode_field_with_format_expr(field, de_lifetime)?;
+                    quote! { Ok(Self::#ident(#expr)) }
+                }
+                None => decode_style(
+                    &variant.style,
+                    de_lifetime,
+                    variant.attrs.mode,
+                    &quote! { Self::#ident },
+                )?,
+            };
+            Ok(quote! {
+                let mut __attempt = ::messagepack_core::io::SliceReader::new(__rest);
+                let __result = (|| -> ::core::result::Result<Self, ::messagepack_core::decode::Error<::messagepack_core::io::RError>> {
+                    let __reader = &mut __attempt;
+                    #body
+                })();
+                if let Ok(__value) = __result {
+                    let __used = __rest.len() - __attempt.rest().len();
+                    ::messagepack_core::io::IoRead::read_slice(__reader, __used)
+                        .map_err(::messagepack_core::decode::Error::Io)?;
+                    return Ok(__value);
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        let ::core::option::Option::Some(__rest) = ::messagepack_core::io::IoRead::remaining_slice(__reader) else {
+            return Err(::messagepack_core::decode::Error::UnexpectedFormat);
+        };
+        #(
+            #attempts
+        )*
+        Err(::messagepack_core::decode::Error::InvalidData)
+    })
+}
+
 fn decode_tuple(
     fields: &[FieldInfo],
     de_lifetime: &syn::Lifetime,
     mode: Option<ContainerMode>,
     output_ty: &TokenStream,
 ) -> syn::Result<TokenStream> {
-    if matches!(mode, Some(ContainerMode::Map)) {
+    if matches!(mode, Some(ContainerMode::Map | ContainerMode::IntMap)) {
         return Err(syn::Error::new(
             proc_macro2::Span::call_site(),
             "tuple structs cannot use `#[msgpack(map)]`",
@@ -138,7 +481,8 @@ fn decode_tuple(
             })
         })
         .collect::<syn::Result<Vec<_>>>()?;
-    let build = fields.iter().map(tuple_field_build).collect::<Vec<_>>();
+    let fill = container_defaults(fields, output_ty);
+    let build = fields.iter().map(field_value).collect::<Vec<_>>();
 
     Ok(quote! {
         let __len = match __format {
@@ -161,6 +505,7 @@ fn decode_tuple(
                 _ => unreachable!(),
             }
         }
+        #fill
         Ok(#output_ty(
             #(#build),*
         ))
@@ -174,8 +519,17 @@ fn decode_named(
     output_ty: &TokenStream,
 ) -> syn::Result<TokenStream> {
     validate_decode_fields(fields)?;
-    let map_body = decode_named_map(fields, de_lifetime, output_ty)?;
-    let array_body = decode_named_array(fields, de_lifetime, mode, output_ty)?;
+    let map_body = if mode == Some(ContainerMode::IntMap) {
+        decode_int_key_map(fields, de_lifetime, output_ty)?
+    } else {
+        decode_named_map(fields, de_lifetime, output_ty)?
+    };
+    // Flattened fields only have a map form
+    let array_body = if fields.iter().any(|field| field.attrs.flatten) {
+        quote! { Err(::messagepack_core::decode::Error::UnexpectedFormat) }
+    } else {
+        decode_named_array(fields, de_lifetime, mode, output_ty)?
+    };
 
     Ok(quote! {
         enum FormatKind {
@@ -216,6 +570,8 @@ fn decode_named(
     })
 }
 
+/// Keys which match no field are offered to each `flatten` field in turn,
+/// and skipped when none accepts them.
 fn decode_named_map(
     fields: &[FieldInfo],
     de_lifetime: &syn::Lifetime,
@@ -229,20 +585,27 @@ fn decode_named_map(
         .iter()
         .map(|field| {
             let local = field_local(field);
-            Ok(quote! {
-                let mut #local = ::core::option::Option::None;
+            Ok(if field.attrs.flatten {
+                let entries = map_entries_trait(field, de_lifetime);
+                quote! {
+                    let mut #local = #entries::builder();
+                }
+            } else {
+                quote! {
+                    let mut #local = ::core::option::Option::None;
+                }
             })
         })
         .collect::<syn::Result<Vec<_>>>()?;
     let arms = active
         .iter()
+        .filter(|field| !field.attrs.flatten)
         .map(|field| {
             let local = field_local(field);
-            let key = field
-                .key_name
-                .as_ref()
-                .expect("named fields always have map keys");
-            let key = syn::LitByteStr::new(key.as_bytes(), field.span);
+            let keys = field
+                .wire_names()
+                .map(|key| syn::LitByteStr::new(key.as_bytes(), field.span));
+            let key = quote! { #(#keys)|* };
             let decode_expr = decode_field_expr(field, de_lifetime)?;
             Ok(quote! {
                 #key => {
@@ -254,6 +617,19 @@ fn decode_named_map(
             })
         })
         .collect::<syn::Result<Vec<_>>>()?;
+    let flattened = active
+        .iter()
+        .filter(|field| field.attrs.flatten)
+        .map(|field| {
+            let local = field_local(field);
+            let entries = map_entries_trait(field, de_lifetime);
+            quote! {
+                if #entries::accept_key(&mut #local, __key) {
+                    #entries::decode_value(&mut #local, __reader)?;
+                } else
+            }
+        });
+    let fill = container_defaults(fields, output_ty);
     let build = fields.iter().map(named_field_build).collect::<Vec<_>>();
 
     Ok(quote! {
@@ -263,6 +639,221 @@ fn decode_named_map(
         for _ in 0..__len {
             let __key = <::messagepack_core::decode::ReferenceStrBinDecoder as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
             match __key.as_bytes() {
+                #(
+                    #arms
+                )*
+                __key => {
+                    #(#flattened)* {
+                        let _ = <::messagepack_core::decode::Any<#de_lifetime> as ::messagepack_core::decode::Decode<#de_lifetime>>::decode(__reader)?;
+                    }
+                }
+            }
+        }
+        #fill
+        Ok(#output_ty {
+            #(#build),*
+        })
+    })
+}
+
+/// `<T as DecodeMapEntries<'de>>` for a `flatten` field of type `T`.
+fn map_entries_trait(field: &FieldInfo, de_lifetime: &syn::Lifetime) -> TokenStream {
+    let ty = replace_lifetimes(&field.ty, de_lifetime);
+    quote! { <#ty as ::messagepack_core::decode::DecodeMapEntries<#de_lifetime>> }
+}
+
+/// The `DecodeMapEntries` impl which lets a map-mode struct be flattened
+/// into another. The builder is a tuple of the slot of the last accepted key
+/// followed by one entry per decoded field.
+fn decode_map_entries_impl(
+    fields: &[FieldInfo],
+    de_lifetime: &syn::Lifetime,
+    output_ty: &TokenStream,
+) -> syn::Result<TokenStream> {
+    let active = fields
+        .iter()
+        .filter(|field| !field.is_skipped_for_decode())
+        .collect::<Vec<_>>();
+    let slots = (1..=active.len()).map(syn::Index::from).collect::<Vec<_>>();
+
+    let builder_tys = active.iter().map(|field| {
+        if field.attrs.flatten {
+            let entries = map_entries_trait(field, de_lifetime);
+            quote! { #entries::Builder }
+        } else {
+            let ty = replace_lifetimes(&field.ty, de_lifetime);
+            quote! { ::core::option::Option<#ty> }
+        }
+    });
+    let builder_inits = active.iter().map(|field| {
+        if field.attrs.flatten {
+            let entries = map_entries_trait(field, de_lifetime);
+            quote! { #entries::builder() }
+        } else {
+            quote! { ::core::option::Option::None }
+        }
+    });
+    let key_arms = active
+        .iter()
+        .zip(&slots)
+        .filter(|(field, _)| !field.attrs.flatten)
+        .map(|(field, slot)| {
+            let keys = field
+                .wire_names()
+                .map(|key| syn::LitByteStr::new(key.as_bytes(), field.span));
+            let slot = slot.index as usize;
+            quote! { #(#keys)|* => ::core::option::Option::Some(#slot), }
+        });
+    let flattened = active
+        .iter()
+        .zip(&slots)
+        .filter(|(field, _)| field.attrs.flatten)
+        .map(|(field, slot)| {
+            let entries = map_entries_trait(field, de_lifetime);
+            let index = slot.index as usize;
+            quote! {
+                if #entries::accept_key(&mut builder.#slot, __key) {
+                    ::core::option::Option::Some(#index)
+                } else
+            }
+        });
+    let value_arms = active
+        .iter()
+        .zip(&slots)
+        .map(|(field, slot)| {
+            let index = slot.index as usize;
+            if field.attrs.flatten {
+                let entries = map_entries_trait(field, de_lifetime);
+                return Ok(quote! {
+                    #index => #entries::decode_value(&mut builder.#slot, __reader),
+                });
+            }
+            let decode_expr = decode_field_expr(field, de_lifetime)?;
+            Ok(quote! {
+                #index => {
+                    if builder.#slot.is_some() {
+                        return Err(::messagepack_core::decode::Error::InvalidData);
+                    }
+                    builder.#slot = Some(#decode_expr);
+                    Ok(())
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let locals = active.iter().map(|field| field_local(field));
+    let fill = container_defaults(fields, output_ty);
+    let build = fields.iter().map(named_field_build).collect::<Vec<_>>();
+
+    Ok(quote! {
+        type Value = #output_ty;
+        type Builder = (usize, #(#builder_tys,)*);
+
+        fn builder() -> Self::Builder {
+            (0, #(#builder_inits,)*)
+        }
+
+        fn accept_key(builder: &mut Self::Builder, key: &[u8]) -> bool {
+            let __slot = match key {
+                #(#key_arms)*
+                __key => {
+                    #(#flattened)* {
+                        ::core::option::Option::None
+                    }
+                }
+            };
+            match __slot {
+                ::core::option::Option::Some(__slot) => {
+                    builder.0 = __slot;
+                    true
+                }
+                ::core::option::Option::None => false,
+            }
+        }
+
+        fn decode_value<__R>(
+            builder: &mut Self::Builder,
+            __reader: &mut __R,
+        ) -> ::core::result::Result<(), ::messagepack_core::decode::Error<__R::Error>>
+        where
+            __R: ::messagepack_core::io::IoRead<#de_lifetime>,
+        {
+            match builder.0 {
+                #(#value_arms)*
+                _ => Err(::messagepack_core::decode::Error::InvalidData),
+            }
+        }
+
+        fn finish<__E>(
+            builder: Self::Builder,
+        ) -> ::core::result::Result<Self::Value, ::messagepack_core::decode::Error<__E>> {
+            let (_, #(#locals,)*) = builder;
+            #fill
+            Ok(#output_ty {
+                #(#build),*
+            })
+        }
+    })
+}
+
+/// Like [`decode_named_map`], matching each field's `key = N` against
+/// unsigned integer keys. Keys of any other format are skipped.
+fn decode_int_key_map(
+    fields: &[FieldInfo],
+    de_lifetime: &syn::Lifetime,
+    output_ty: &TokenStream,
+) -> syn::Result<TokenStream> {
+    let active = int_key_fields(fields.iter().filter(|field| !field.is_skipped_for_decode()))?;
+    let declarations = active.iter().map(|field| {
+        let local = field_local(field);
+        quote! {
+            let mut #local = ::core::option::Option::None;
+        }
+    });
+    let arms = active
+        .iter()
+        .map(|field| {
+            let local = field_local(field);
+            let key = field.attrs.key.expect("int_key_fields checks keys") as u64;
+            let decode_expr = decode_field_expr(field, de_lifetime)?;
+            Ok(quote! {
+                ::core::option::Option::Some(#key) => {
+                    if #local.is_some() {
+                        return Err(::messagepack_core::decode::Error::InvalidData);
+                    }
+                    #local = Some(#decode_expr);
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let fill = container_defaults(fields, output_ty);
+    let build = fields.iter().map(named_field_build).collect::<Vec<_>>();
+
+    Ok(quote! {
+        #(
+            #declarations
+        )*
+        for _ in 0..__len {
+            let __key_format = <::messagepack_core::Format as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed(__reader)?;
+            let __key: ::core::option::Option<u64> = match __key_format {
+                ::messagepack_core::Format::PositiveFixInt(__key) => Some(__key.into()),
+                ::messagepack_core::Format::Uint8 => Some(
+                    <u8 as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed_with_format(__key_format, __reader)?.into(),
+                ),
+                ::messagepack_core::Format::Uint16 => Some(
+                    <u16 as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed_with_format(__key_format, __reader)?.into(),
+                ),
+                ::messagepack_core::Format::Uint32 => Some(
+                    <u32 as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed_with_format(__key_format, __reader)?.into(),
+                ),
+                ::messagepack_core::Format::Uint64 => Some(
+                    <u64 as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed_with_format(__key_format, __reader)?,
+                ),
+                __key_format => {
+                    let _ = <::messagepack_core::decode::Any<#de_lifetime> as ::messagepack_core::decode::DecodeBorrowed<#de_lifetime>>::decode_borrowed_with_format(__key_format, __reader)?;
+                    None
+                }
+            };
+            match __key {
                 #(
                     #arms
                 )*
@@ -271,6 +862,7 @@ fn decode_named_map(
                 }
             }
         }
+        #fill
         Ok(#output_ty {
             #(#build),*
         })
@@ -284,7 +876,7 @@ fn decode_named_array(
     output_ty: &TokenStream,
 ) -> syn::Result<TokenStream> {
     let active = match mode.unwrap_or(ContainerMode::Map) {
-        ContainerMode::Map => fields
+        ContainerMode::Map | ContainerMode::IntMap => fields
             .iter()
             .filter(|field| !field.is_skipped_for_decode())
             .collect::<Vec<_>>(),
@@ -303,6 +895,10 @@ fn decode_named_array(
                 })
             })
             .collect::<syn::Result<Vec<_>>>()?;
+        let fill = container_defaults(
+            fields.iter().filter(|field| field.is_skipped_for_decode()),
+            output_ty,
+        );
         let build = fields
             .iter()
             .map(named_field_build_direct)
@@ -315,6 +911,7 @@ fn decode_named_array(
             #(
                 #assignments
             )*
+            #fill
             Ok(#output_ty {
                 #(#build),*
             })
@@ -355,6 +952,7 @@ fn decode_named_array(
             }
         })
         .collect::<Vec<_>>();
+    let fill = container_defaults(fields, output_ty);
     let build = fields.iter().map(named_field_build).collect::<Vec<_>>();
 
     Ok(quote! {
@@ -372,6 +970,7 @@ fn decode_named_array(
                 _ => unreachable!(),
             }
         }
+        #fill
         Ok(#output_ty {
             #(#build),*
         })
@@ -418,7 +1017,7 @@ fn sorted_array_fields(fields: &[FieldInfo]) -> syn::Result<Vec<&FieldInfo>> {
 fn minimum_array_len(fields: &[&FieldInfo]) -> usize {
     fields
         .iter()
-        .rposition(|field| !field.attrs.default && option_inner(&field.ty).is_none())
+        .rposition(|field| field.attrs.default.is_none() && option_inner(&field.ty).is_none())
         .map(|index| index + 1)
         .unwrap_or(0)
 }
@@ -445,9 +1044,7 @@ fn decode_field_expr(field: &FieldInfo, de_lifetime: &syn::Lifetime) -> syn::Res
             attrs: crate::shared::FieldAttrs {
                 key: None,
                 bytes: field.attrs.bytes,
-                default: false,
-                encode_with: None,
-                decode_with: None,
+                ..Default::default()
             },
             span: field.span,
             name: field.name.clone(),
@@ -472,6 +1069,40 @@ fn decode_field_expr(field: &FieldInfo, de_lifetime: &syn::Lifetime) -> syn::Res
     decode_non_option_expr(field, de_lifetime)
 }
 
+/// Decode a field from an already read `__format`.
+fn decode_field_with_format_expr(
+    field: &FieldInfo,
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<TokenStream> {
+    if field.attrs.decode_with.is_some() {
+        return Err(syn::Error::new(
+            field.span,
+            "`decode_with` cannot be used on newtype variants of `untagged` enums",
+        ));
+    }
+    let target_ty = &field.ty;
+    if let Some(inner) = option_inner(target_ty) {
+        let inner_field = FieldInfo {
+            ty: inner.clone(),
+            ..field.clone()
+        };
+        let inner_decode_ty = replace_lifetimes(&inner, de_lifetime);
+        let inner_expr =
+            decode_non_option_with_format_expr(&inner_field, de_lifetime, quote!(__format))?;
+        return Ok(quote! {{
+            let __value: #target_ty = match __format {
+                ::messagepack_core::Format::Nil => ::core::option::Option::None,
+                __format => {
+                    let __inner: #inner_decode_ty = #inner_expr;
+                    ::core::option::Option::Some(__inner)
+                }
+            };
+            __value
+        }});
+    }
+    decode_non_option_with_format_expr(field, de_lifetime, quote!(__format))
+}
+
 fn decode_non_option_expr(
     field: &FieldInfo,
     de_lifetime: &syn::Lifetime,
@@ -543,51 +1174,112 @@ fn decode_non_option_with_format_expr(
     }})
 }
 
-fn field_local(field: &FieldInfo) -> syn::Ident {
-    match &field.member {
-        syn::Member::Named(name) => syn::Ident::new(&format!("__{}", name), name.span()),
-        syn::Member::Unnamed(index) => syn::Ident::new(
-            &format!("__field_{}", index.index),
-            proc_macro2::Span::call_site(),
-        ),
+/// The value of a skipped field, or of a field missing from the input.
+fn default_value(field: &FieldInfo) -> TokenStream {
+    match &field.attrs.default {
+        _ if field.is_phantom => quote! { ::core::default::Default::default() },
+        Some(FieldDefault::Path(path)) => quote! { #path() },
+        Some(FieldDefault::Container(_)) => {
+            // Already filled in by `container_defaults`.
+            let local = field_local(field);
+            quote! { #local }
+        }
+        Some(FieldDefault::Trait) | None => quote! { ::core::default::Default::default() },
+    }
+}
+
+/// Fills the locals of fields taking their default from the container.
+///
+/// `<T as Default>::default()` is built at most once, and only when one of
+/// those fields is missing or skipped, then its fields are moved out. Each
+/// non-skipped field's local must be an `Option` at this point.
+///
+/// Moving out needs no bounds on the field types, but is rejected for types
+/// which implement `Drop`. The destructuring carries the span of the
+/// container attribute so that error points at it.
+fn container_defaults<'a>(
+    fields: impl IntoIterator<Item = &'a FieldInfo>,
+    output_ty: &TokenStream,
+) -> TokenStream {
+    let fields = fields
+        .into_iter()
+        .filter(|field| {
+            matches!(field.attrs.default, Some(FieldDefault::Container(_)))
+                && !field.is_phantom
+                && !field.attrs.flatten
+        })
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        return TokenStream::new();
+    }
+    let locals = fields
+        .iter()
+        .map(|field| field_local(field))
+        .collect::<Vec<_>>();
+    let sources = fields.iter().map(|field| {
+        if field.is_skipped_for_decode() {
+            quote! { ::core::option::Option::None }
+        } else {
+            let local = field_local(field);
+            quote! { #local }
+        }
+    });
+    let members = fields.iter().map(|field| &field.member);
+    let defaults = locals
+        .iter()
+        .map(|local| quote::format_ident!("{}_default", local))
+        .collect::<Vec<_>>();
+    let Some(FieldDefault::Container(span)) = fields[0].attrs.default else {
+        unreachable!()
+    };
+    let destructure = quote_spanned! {span=>
+        let #output_ty { #(#members: #defaults,)* .. } =
+            <#output_ty as ::core::default::Default>::default();
+    };
+    quote! {
+        let (#(#locals,)*) = match (#(#sources,)*) {
+            (#(::core::option::Option::Some(#locals),)*) => (#(#locals,)*),
+            (#(#locals,)*) => {
+                #destructure
+                (#(#locals.unwrap_or(#defaults),)*)
+            }
+        };
     }
 }
 
-fn tuple_field_build(field: &FieldInfo) -> TokenStream {
-    if field.is_phantom {
-        return quote! { ::core::default::Default::default() };
+fn field_value(field: &FieldInfo) -> TokenStream {
+    if field.is_skipped_for_decode() {
+        return default_value(field);
     }
     let local = field_local(field);
-    if field.attrs.default {
-        quote! { #local.unwrap_or_default() }
-    } else if option_inner(&field.ty).is_some() {
-        quote! { #local.unwrap_or(::core::option::Option::None) }
-    } else {
-        quote! { #local.ok_or(::messagepack_core::decode::Error::InvalidData)? }
+    if field.attrs.flatten {
+        let entries = map_entries_trait(field, &decode_lifetime());
+        return quote! { #entries::finish(#local)? };
+    }
+    match &field.attrs.default {
+        Some(FieldDefault::Trait) => quote! { #local.unwrap_or_default() },
+        Some(FieldDefault::Container(_)) => quote! { #local },
+        Some(_) => {
+            let default = default_value(field);
+            quote! { #local.unwrap_or_else(|| #default) }
+        }
+        None if option_inner(&field.ty).is_some() => {
+            quote! { #local.unwrap_or(::core::option::Option::None) }
+        }
+        None => quote! { #local.ok_or(::messagepack_core::decode::Error::InvalidData)? },
     }
 }
 
 fn named_field_build(field: &FieldInfo) -> TokenStream {
     let member = &field.member;
-    let value = if field.is_phantom {
-        quote! { ::core::default::Default::default() }
-    } else {
-        let local = field_local(field);
-        if field.attrs.default {
-            quote! { #local.unwrap_or_default() }
-        } else if option_inner(&field.ty).is_some() {
-            quote! { #local.unwrap_or(::core::option::Option::None) }
-        } else {
-            quote! { #local.ok_or(::messagepack_core::decode::Error::InvalidData)? }
-        }
-    };
+    let value = field_value(field);
     quote! { #member: #value }
 }
 
 fn named_field_build_direct(field: &FieldInfo) -> TokenStream {
     let member = &field.member;
-    let value = if field.is_phantom {
-        quote! { ::core::default::Default::default() }
+    let value = if field.is_skipped_for_decode() {
+        default_value(field)
     } else {
         let local = field_local(field);
         quote! { #local }
@@ -597,7 +1289,7 @@ fn named_field_build_direct(field: &FieldInfo) -> TokenStream {
 
 fn add_decode_bounds(
     generics: &mut syn::Generics,
-    style: &StructStyle,
+    fields: &[&FieldInfo],
     de_lifetime: &syn::Lifetime,
 ) {
     let borrowed_bound_for = |ty: &syn::Type| -> syn::TypeParamBound {
@@ -606,25 +1298,31 @@ fn add_decode_bounds(
     let bytes_bound: syn::TypeParamBound =
         syn::parse_quote!(::messagepack_core::decode::DecodeBytes<#de_lifetime>);
     let default_bound: syn::TypeParamBound = syn::parse_quote!(::core::default::Default);
-
-    let fields = match style {
-        StructStyle::Named(fields) | StructStyle::Tuple(fields) => fields,
-        StructStyle::Unit => return,
+    let entries_bound_for = |ty: &syn::Type| -> syn::TypeParamBound {
+        syn::parse_quote!(::messagepack_core::decode::DecodeMapEntries<#de_lifetime, Value = #ty>)
     };
 
     for field in fields {
         if field.is_phantom {
             continue;
         }
-        if field.attrs.default {
+        if matches!(field.attrs.default, Some(FieldDefault::Trait))
+            || (field.attrs.skip && field.attrs.default.is_none())
+        {
             add_type_bound(generics, field.ty.clone(), default_bound.clone());
         }
+        if field.attrs.skip {
+            continue;
+        }
         if field.attrs.decode_with.is_some() {
             continue;
         }
         if field.attrs.bytes {
             let decode_ty = replace_lifetimes(&field.ty, de_lifetime);
             add_type_bound(generics, decode_ty, bytes_bound.clone());
+        } else if field.attrs.flatten {
+            let decode_ty = replace_lifetimes(&field.ty, de_lifetime);
+            add_type_bound(generics, decode_ty.clone(), entries_bound_for(&decode_ty));
         } else {
             for ty in collect_bound_types(&field.ty, generics) {
                 let decode_ty = replace_lifetimes(&ty, de_lifetime);
@@ -3,29 +3,73 @@ use quote::quote;
 use syn::DeriveInput;
 
 use crate::shared::{
-    ContainerMode, DeriveKind, FieldInfo, StructStyle, add_type_bound, collect_bound_types,
-    ensure_where_clause, parse_struct,
+    ContainerMode, DeriveKind, EnumRepr, FieldInfo, InputData, StructStyle, VariantInfo,
+    add_type_bound, collect_bound_types, ensure_where_clause, field_local, int_key_fields,
+    parse_input,
 };
 
+/// Produces the expression borrowing a field, `&self.x` for structs or the
+/// binding of a variant pattern.
+type Access<'a> = &'a dyn Fn(&FieldInfo) -> TokenStream;
+
 pub fn derive_encode(input: DeriveInput) -> syn::Result<TokenStream> {
-    let info = parse_struct(input, DeriveKind::Encode)?;
+    let info = parse_input(input, DeriveKind::Encode)?;
     let name = &info.ident;
     let mut generics = info.generics.clone();
 
-    let body = match &info.style {
-        StructStyle::Unit => {
-            quote! {
-                ::messagepack_core::encode::NilEncoder.encode(writer)
-            }
+    let body = match &info.data {
+        InputData::Struct(style) => {
+            let access = |field: &FieldInfo| {
+                let member = &field.member;
+                quote! { &self.#member }
+            };
+            encode_style(style, info.container.mode, &access)?
         }
-        StructStyle::Tuple(fields) => encode_tuple(fields, info.container.mode)?,
-        StructStyle::Named(fields) => encode_named(fields, info.container.mode)?,
+        InputData::Enum(repr, variants) => encode_enum(repr, variants)?,
+    };
+
+    let body = match info.container.ext {
+        Some(ext_type) => quote! {
+            ::messagepack_core::extension::encode_with(#ext_type, writer, |writer| {
+                #body
+            })
+        },
+        None => body,
     };
 
-    add_encode_bounds(&mut generics, &info.style);
+    add_encode_bounds(&mut generics, &info.data.fields());
 
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    let entries_impl = match &info.data {
+        InputData::Struct(StructStyle::Named(fields)) if info.container.flattenable.is_some() => {
+            let access = |field: &FieldInfo| {
+                let member = &field.member;
+                quote! { &self.#member }
+            };
+            let (len, writes) = map_entries(fields, &access)?;
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::messagepack_core::encode::map::EncodeMapEntries for #name #ty_generics
+                    #where_clause
+                {
+                    fn entry_count(&self) -> usize {
+                        #len
+                    }
+
+                    fn encode_entries<__W: ::messagepack_core::io::IoWrite>(&self, writer: &mut __W) -> ::core::result::Result<usize, ::messagepack_core::encode::Error<<__W as ::messagepack_core::io::IoWrite>::Error>> {
+                        let mut __size = 0usize;
+                        #(
+                            #writes
+                        )*
+                        Ok(__size)
+                    }
+                }
+            }
+        }
+        _ => TokenStream::new(),
+    };
+
     Ok(quote! {
         #[automatically_derived]
         impl #impl_generics ::messagepack_core::encode::Encode for #name #ty_generics
@@ -35,11 +79,156 @@ pub fn derive_encode(input: DeriveInput) -> syn::Result<TokenStream> {
                 #body
             }
         }
+
+        #entries_impl
     })
 }
 
-fn encode_tuple(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Result<TokenStream> {
-    if matches!(mode, Some(ContainerMode::Map)) {
+fn encode_style(
+    style: &StructStyle,
+    mode: Option<ContainerMode>,
+    access: Access<'_>,
+) -> syn::Result<TokenStream> {
+    match style {
+        StructStyle::Unit => Ok(quote! {
+            ::messagepack_core::encode::Encode::encode(&::messagepack_core::encode::NilEncoder, writer)
+        }),
+        StructStyle::Tuple(fields) => encode_tuple(fields, mode, access),
+        StructStyle::Named(fields) => encode_named(fields, mode, access),
+    }
+}
+
+fn encode_enum(repr: &EnumRepr, variants: &[VariantInfo]) -> syn::Result<TokenStream> {
+    let arms = variants
+        .iter()
+        .map(|variant| {
+            let pattern = variant_pattern(variant);
+            let body = encode_variant(repr, variant)?;
+            Ok(quote! {
+                #pattern => {
+                    #body
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        match self {
+            #(
+                #arms
+            )*
+        }
+    })
+}
+
+/// `Self::V`, `Self::V(__field_0, _)` or `Self::V { a: __a, b: _ }`, with
+/// skipped fields left unbound.
+fn variant_pattern(variant: &VariantInfo) -> TokenStream {
+    let ident = &variant.ident;
+    let binding = |field: &FieldInfo| {
+        if field.is_skipped_for_encode() {
+            quote! { _ }
+        } else {
+            let local = field_local(field);
+            quote! { #local }
+        }
+    };
+    match &variant.style {
+        StructStyle::Unit => quote! { Self::#ident },
+        StructStyle::Tuple(fields) => {
+            let bindings = fields.iter().map(binding);
+            quote! { Self::#ident(#(#bindings),*) }
+        }
+        StructStyle::Named(fields) => {
+            let bindings = fields.iter().map(|field| {
+                let member = &field.member;
+                let binding = binding(field);
+                quote! { #member: #binding }
+            });
+            quote! { Self::#ident { #(#bindings),* } }
+        }
+    }
+}
+
+fn encode_variant(repr: &EnumRepr, variant: &VariantInfo) -> syn::Result<TokenStream> {
+    let access = |field: &FieldInfo| {
+        let local = field_local(field);
+        quote! { #local }
+    };
+    let payload = match variant.style.newtype() {
+        Some(field) if variant.is_newtype() => {
+            let value = encode_field_expr(field, &access)?;
+            quote! { Ok(#value) }
+        }
+        _ => encode_style(&variant.style, variant.attrs.mode, &access)?,
+    };
+    let is_unit = matches!(variant.style, StructStyle::Unit);
+    let name = &variant.name;
+
+    let wrapped = |header: TokenStream, tag: TokenStream| {
+        quote! {
+            let mut __size = ::messagepack_core::encode::Encode::encode(&#header, writer)?;
+            __size += ::messagepack_core::encode::Encode::encode(&#tag, writer)?;
+            let __payload: ::core::result::Result<usize, ::messagepack_core::encode::Error<<__W as ::messagepack_core::io::IoWrite>::Error>> = {
+                #payload
+            };
+            __size += __payload?;
+            Ok(__size)
+        }
+    };
+
+    Ok(match repr {
+        EnumRepr::Index => {
+            let id = proc_macro2::Literal::u64_suffixed(variant.id);
+            let id = quote! { ::messagepack_core::encode::int::EncodeMinimizeInt(#id) };
+            if is_unit {
+                quote! { ::messagepack_core::encode::Encode::encode(&#id, writer) }
+            } else {
+                wrapped(
+                    quote! { ::messagepack_core::encode::array::ArrayFormatEncoder(2) },
+                    id,
+                )
+            }
+        }
+        EnumRepr::Name => {
+            if is_unit {
+                quote! { ::messagepack_core::encode::Encode::encode(&#name, writer) }
+            } else {
+                wrapped(
+                    quote! { ::messagepack_core::encode::map::MapFormatEncoder(1) },
+                    quote! { #name },
+                )
+            }
+        }
+        EnumRepr::Tag(tag) => {
+            let (len, writes) = match &variant.style {
+                StructStyle::Named(fields) => map_entries(fields, &access)?,
+                _ => (quote! { 0 }, Vec::new()),
+            };
+            quote! {
+                let __field_len: usize = #len + 1;
+                let mut __size = ::messagepack_core::encode::Encode::encode(
+                    &::messagepack_core::encode::map::MapFormatEncoder(__field_len),
+                    writer,
+                )?;
+                __size += ::messagepack_core::encode::Encode::encode(&#tag, writer)?;
+                __size += ::messagepack_core::encode::Encode::encode(&#name, writer)?;
+                #(
+                    #writes
+                )*
+                Ok(__size)
+            }
+        }
+        EnumRepr::Untagged => payload,
+    })
+}
+
+fn encode_tuple(
+    fields: &[FieldInfo],
+    mode: Option<ContainerMode>,
+    access: Access<'_>,
+) -> syn::Result<TokenStream> {
+    if matches!(mode, Some(ContainerMode::Map | ContainerMode::IntMap)) {
         return Err(syn::Error::new(
             proc_macro2::Span::call_site(),
             "tuple structs cannot use `#[msgpack(map)]`",
@@ -54,7 +243,7 @@ fn encode_tuple(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Resul
         .collect::<Vec<_>>();
     let writes = active
         .iter()
-        .map(|field| encode_field_expr(field))
+        .map(|field| encode_field_expr(field, access))
         .collect::<syn::Result<Vec<_>>>()?;
     let len = active.len();
 
@@ -72,36 +261,26 @@ fn encode_tuple(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Resul
     })
 }
 
-fn encode_named(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Result<TokenStream> {
+fn encode_named(
+    fields: &[FieldInfo],
+    mode: Option<ContainerMode>,
+    access: Access<'_>,
+) -> syn::Result<TokenStream> {
     validate_skipped_fields(fields)?;
 
     match mode.unwrap_or(ContainerMode::Map) {
-        ContainerMode::Map => {
-            let active = fields
-                .iter()
-                .filter(|field| !field.is_skipped_for_encode())
-                .collect::<Vec<_>>();
-            let writes = active
-                .iter()
-                .map(|field| {
-                    let key = field
-                        .key_name
-                        .as_ref()
-                        .expect("named fields always have key names");
-                    let encode_value = encode_field_expr(field)?;
-                    Ok(quote! {
-                        __size += ::messagepack_core::encode::Encode::encode(&#key, writer)?;
-                        __size += #encode_value;
-                    })
-                })
-                .collect::<syn::Result<Vec<_>>>()?;
-            let len = active.len();
+        mode @ (ContainerMode::Map | ContainerMode::IntMap) => {
+            let (len, writes) = if mode == ContainerMode::IntMap {
+                int_map_entries(fields, access)?
+            } else {
+                map_entries(fields, access)?
+            };
 
             Ok(quote! {
-                const __FIELD_LEN: usize = #len;
+                let __field_len: usize = #len;
                 let mut __size = 0usize;
                 __size += ::messagepack_core::encode::Encode::encode(
-                    &::messagepack_core::encode::map::MapFormatEncoder(__FIELD_LEN),
+                    &::messagepack_core::encode::map::MapFormatEncoder(__field_len),
                     writer,
                 )?;
                 #(
@@ -111,10 +290,19 @@ fn encode_named(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Resul
             })
         }
         ContainerMode::Array => {
+            if let Some(field) = fields
+                .iter()
+                .find(|field| field.attrs.skip_encoding_if.is_some())
+            {
+                return Err(syn::Error::new(
+                    field.span,
+                    "`skip_encoding_if` is only supported in map mode",
+                ));
+            }
             let active = sorted_array_fields(fields)?;
             let writes = active
                 .iter()
-                .map(|field| encode_field_expr(field))
+                .map(|field| encode_field_expr(field, access))
                 .collect::<syn::Result<Vec<_>>>()?;
             let len = active.len();
 
@@ -134,8 +322,104 @@ fn encode_named(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Resul
     }
 }
 
+/// The number of map entries and the statements writing each key and value.
+fn map_entries(
+    fields: &[FieldInfo],
+    access: Access<'_>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let active = fields
+        .iter()
+        .filter(|field| !field.is_skipped_for_encode())
+        .collect::<Vec<_>>();
+    entries(&active, access, &|field| {
+        let key = field
+            .key_name
+            .as_ref()
+            .expect("named fields always have key names");
+        quote! { #key }
+    })
+}
+
+/// Like [`map_entries`], with each field's `key = N` as its key.
+fn int_map_entries(
+    fields: &[FieldInfo],
+    access: Access<'_>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let active = int_key_fields(fields.iter().filter(|field| !field.is_skipped_for_encode()))?;
+    entries(&active, access, &|field| {
+        let key = field.attrs.key.expect("int_key_fields checks keys") as u64;
+        quote! { ::messagepack_core::encode::int::EncodeMinimizeInt(#key) }
+    })
+}
+
+/// Fields with `skip_encoding_if` are counted and written only when the
+/// predicate is false. `flatten` fields contribute their own entries.
+fn entries(
+    active: &[&FieldInfo],
+    access: Access<'_>,
+    key: Access<'_>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let always = active
+        .iter()
+        .filter(|field| field.attrs.skip_encoding_if.is_none() && !field.attrs.flatten)
+        .count();
+    let counted = active
+        .iter()
+        .filter(|field| field.attrs.skip_encoding_if.is_some() || field.attrs.flatten)
+        .map(|field| {
+            let value = access(field);
+            let count = if field.attrs.flatten {
+                quote! { ::messagepack_core::encode::map::EncodeMapEntries::entry_count(#value) }
+            } else {
+                quote! { 1 }
+            };
+            match &field.attrs.skip_encoding_if {
+                Some(path) => quote! { + if #path(#value) { 0 } else { #count } },
+                None => quote! { + #count },
+            }
+        });
+    let len = quote! { #always #(#counted)* };
+
+    let writes = active
+        .iter()
+        .map(|field| {
+            let write = if field.attrs.flatten {
+                let value = access(field);
+                quote! {
+                    __size += ::messagepack_core::encode::map::EncodeMapEntries::encode_entries(#value, writer)?;
+                }
+            } else {
+                let key = key(field);
+                let encode_value = encode_field_expr(field, access)?;
+                quote! {
+                    __size += ::messagepack_core::encode::Encode::encode(&#key, writer)?;
+                    __size += #encode_value;
+                }
+            };
+            Ok(match &field.attrs.skip_encoding_if {
+                Some(path) => {
+                    let value = access(field);
+                    quote! {
+                        if !#path(#value) {
+                            #write
+                        }
+                    }
+                }
+                None => write,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok((len, writes))
+}
+
 fn validate_skipped_fields(fields: &[FieldInfo]) -> syn::Result<()> {
     for field in fields {
+        if field.attrs.skip_encoding_if.is_some() && field.is_skipped_for_encode() {
+            return Err(syn::Error::new(
+                field.span,
+                "`skip_encoding_if` cannot be used on a skipped field",
+            ));
+        }
         if field.is_phantom && field.attrs.key.is_some() {
             return Err(syn::Error::new(
                 field.span,
@@ -171,28 +455,29 @@ fn sorted_array_fields(fields: &[FieldInfo]) -> syn::Result<Vec<&FieldInfo>> {
     Ok(active)
 }
 
-fn encode_field_expr(field: &FieldInfo) -> syn::Result<TokenStream> {
-    let member = &field.member;
+fn encode_field_expr(field: &FieldInfo, access: Access<'_>) -> syn::Result<TokenStream> {
+    let value = access(field);
     if let Some(path) = &field.attrs.encode_with {
-        return Ok(quote! { #path(&self.#member, writer)? });
+        return Ok(quote! { #path(#value, writer)? });
     }
     if field.attrs.bytes {
         return Ok(quote! {
-            ::messagepack_core::encode::bin::EncodeBytes::encode_bytes(&self.#member, writer)?
+            ::messagepack_core::encode::bin::EncodeBytes::encode_bytes(#value, writer)?
         });
     }
-    Ok(quote! { ::messagepack_core::encode::Encode::encode(&self.#member, writer)? })
+    Ok(quote! { ::messagepack_core::encode::Encode::encode(#value, writer)? })
 }
 
-fn add_encode_bounds(generics: &mut syn::Generics, style: &StructStyle) {
+fn add_encode_bounds(generics: &mut syn::Generics, fields: &[&FieldInfo]) {
     let encode_bound: syn::TypeParamBound = syn::parse_quote!(::messagepack_core::encode::Encode);
     let bytes_bound: syn::TypeParamBound =
         syn::parse_quote!(::messagepack_core::encode::bin::EncodeBytes);
+    let entries_bound: syn::TypeParamBound =
+        syn::parse_quote!(::messagepack_core::encode::map::EncodeMapEntries);
 
-    let fields = match style {
-        StructStyle::Named(fields) | StructStyle::Tuple(fields) => fields,
-        StructStyle::Unit => return,
-    };
+    if fields.is_empty() {
+        return;
+    }
 
     ensure_where_clause(generics);
     for field in fields {
@@ -201,6 +486,8 @@ fn add_encode_bounds(generics: &mut syn::Generics, style: &StructStyle) {
         }
         if field.attrs.bytes {
             add_type_bound(generics, field.ty.clone(), bytes_bound.clone());
+        } else if field.attrs.flatten {
+            add_type_bound(generics, field.ty.clone(), entries_bound.clone());
         } else {
             for ty in collect_bound_types(&field.ty, generics) {
                 add_type_bound(generics, ty, encode_bound.clone());
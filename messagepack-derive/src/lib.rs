@@ -2,6 +2,7 @@
 
 //! Derive marco for [messagepack_core::encode::Encode] and [messagepack_core::decode::Decode]
 
+mod case;
 mod decode;
 mod encode;
 mod shared;
@@ -9,21 +10,62 @@ mod shared;
 use proc_macro::TokenStream;
 use syn::{DeriveInput, parse_macro_input};
 
-/// Derive the `Encode` trait for a struct.
+/// Derive the `Encode` trait for a struct or enum.
 ///
 /// # Supported types
 /// - **Named-field structs** — encoded as a MessagePack map by default
 /// - **Tuple structs** — encoded as a MessagePack array
 /// - **Unit structs** — encoded as MessagePack `nil`
+/// - **Enums** — see [Enum representations](#enum-representations)
 ///
 /// # Container attributes
 /// - `#[msgpack(map)]` — encode as a MessagePack map (default for named-field structs)
 /// - `#[msgpack(array)]` — encode as a MessagePack array
+/// - `#[msgpack(int_keys)]` — encode as a MessagePack map keyed by the
+///   `key = N` of each field instead of its name
+/// - `#[msgpack(ext = N)]` — wrap the encoded struct in an extension of type `N`.
+///   The payload is the struct encoded as MessagePack; fixed-layout binary
+///   payloads are not supported, implement `extension::Extension` for those
+/// - `#[msgpack(flattenable)]` — also implement `EncodeMapEntries`, so the
+///   struct can be a `flatten` field of another struct. Named-field structs
+///   in map mode only
+/// - `#[msgpack(rename_all = "camelCase")]` — rename map keys, or variant
+///   names on enums. Accepts `lowercase`, `UPPERCASE`, `PascalCase`,
+///   `camelCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and
+///   `SCREAMING-KEBAB-CASE`
 ///
 /// # Field attributes
-/// - `#[msgpack(key = N)]` — required for all fields in `array` mode
+/// - `#[msgpack(key = N)]` — required for all fields in `array` and
+///   `int_keys` modes
 /// - `#[msgpack(bytes)]` — encode the field as MessagePack binary
 /// - `#[msgpack(encode_with = "path::to::fn")]` — custom encode function
+/// - `#[msgpack(rename = "name")]` — map key used instead of the field name
+/// - `#[msgpack(skip)]` — never encode the field
+/// - `#[msgpack(skip_encoding_if = "path::to::fn")]` — omit the field from
+///   the map when `fn(&field)` returns `true`; map mode only. The field
+///   should be an `Option` or have a `default` to decode the output
+/// - `#[msgpack(flatten)]` — write the entries of the field into this map;
+///   the field must implement `EncodeMapEntries`, as `flattenable` structs
+///   and `HashMap`/`BTreeMap` do. Map mode only
+///
+/// # Enum representations
+/// A variant's payload is encoded like a struct of the same shape, except
+/// that a tuple variant with a single field is encoded as that field.
+///
+/// - `#[msgpack(index)]` (default) — unit variants as their id, others as
+///   `[id, payload]`. The id is the variant position unless set with
+///   `#[msgpack(key = N)]` on the variant.
+/// - `#[msgpack(name)]` — unit variants as their name, others as
+///   `{name: payload}`
+/// - `#[msgpack(tag = "type")]` — a map holding `"type": name` and the
+///   fields of the variant; only unit and struct variants are allowed
+/// - `#[msgpack(untagged)]` — the payload alone; at most one unit variant,
+///   encoded as `nil`. Decoding needs a reader which exposes its input, such
+///   as `SliceReader`
+///
+/// Struct and tuple variants accept `#[msgpack(map)]`, `#[msgpack(array)]`
+/// and `#[msgpack(int_keys)]` like structs do. Variants accept `#[msgpack(rename = "name")]`, and
+/// `#[msgpack(rename_all = "...")]` for their own fields.
 #[proc_macro_derive(Encode, attributes(msgpack))]
 pub fn derive_encode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -32,7 +74,7 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
         .into()
 }
 
-/// Derive the `DecodeBorrowed` trait for a struct.
+/// Derive the `DecodeBorrowed` trait for a struct or enum.
 ///
 /// Named-field structs accept both map and array MessagePack formats on
 /// decode regardless of the `map`/`array` attribute.
@@ -41,15 +83,49 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
 /// - **Named-field structs** — decoded from a MessagePack map or array
 /// - **Tuple structs** — decoded from a MessagePack array
 /// - **Unit structs** — decoded from MessagePack `nil`
+/// - **Enums** — decoded from the representation chosen for `Encode`
 ///
 /// # Container attributes
 /// - `#[msgpack(map)]` — default for named-field structs
 /// - `#[msgpack(array)]` — encode mode; requires `key` on every field
+/// - `#[msgpack(int_keys)]` — decode maps by matching integer keys against
+///   `key = N`, in any order; unknown keys, and keys which are not unsigned
+///   integers, are skipped
+/// - `#[msgpack(ext = N)]` — decode from the payload of an extension of type
+///   `N`; any other type is rejected
+/// - `#[msgpack(default)]` — on structs, take missing fields from
+///   `<Self as Default>::default()`. Those fields are moved out of it, so
+///   the struct must not implement `Drop`; use `default` on the fields
+///   instead
+/// - `#[msgpack(flattenable)]` — also implement `DecodeMapEntries`, so the
+///   struct can be a `flatten` field of another struct. Named-field structs
+///   in map mode only
 ///
 /// # Field attributes
-/// - `#[msgpack(key = N)]` — array index (required in `array` mode)
+/// - `#[msgpack(key = N)]` — array index, or map key in `int_keys` mode
 /// - `#[msgpack(bytes)]` — decode the field from MessagePack binary
 /// - `#[msgpack(decode_with = "path::to::fn")]` — custom decode function
+/// - `#[msgpack(default)]` — use `Default::default()` when the field is missing
+/// - `#[msgpack(default = "path::to::fn")]` — call `fn()` when the field is missing
+/// - `#[msgpack(skip)]` — never decode the field; it is set from its default
+/// - `#[msgpack(flatten)]` — offer keys which match no other field to the
+///   field, which must implement `DecodeMapEntries`. `flattenable` structs
+///   take their own keys, while `HashMap<String, V>` and
+///   `BTreeMap<String, V>` collect every key offered. Structs with flattened
+///   fields can only be decoded from maps
+/// - `#[msgpack(rename = "name")]` — map key used instead of the field name
+/// - `#[msgpack(alias = "name")]` — another accepted map key; may be repeated.
+///   Also accepted on variants of `name` and `tag` enums
+///
+/// # Enums
+/// `index` and `name` enums also accept unit variants written with a `nil`
+/// payload. The tag of a `tag` enum may be anywhere in the map when the
+/// reader exposes its input (such as `SliceReader`), but must be the first
+/// entry for streaming readers (such as `StdReader`). `untagged` enums try
+/// each variant in order and can only be decoded from readers which expose
+/// their input. In both cases a streaming reader fails with
+/// `Error::UnexpectedFormat`, while a missing tag or a payload matching no
+/// variant is `Error::InvalidData`.
 #[proc_macro_derive(Decode, attributes(msgpack))]
 pub fn derive_decode(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
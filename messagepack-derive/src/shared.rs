@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use proc_macro2::Span;
+
+use crate::case::RenameRule;
 use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
 use syn::{
     Attribute, Data, DeriveInput, Error, Field, Fields, GenericArgument, Generics, Ident, LitInt,
@@ -27,21 +30,64 @@ impl DeriveKind {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerMode {
     Map,
+    /// A map keyed by the `key = N` of each field.
+    IntMap,
     Array,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnumRepr {
+    Index,
+    Name,
+    Tag(String),
+    Untagged,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ContainerAttrs {
     pub mode: Option<ContainerMode>,
+    pub ext: Option<i8>,
+    pub repr: Option<(EnumRepr, Span)>,
+    pub rename_all: Option<RenameRule>,
+    /// Missing fields are taken from `<Self as Default>::default()`
+    pub default: Option<Span>,
+    /// Also implement `EncodeMapEntries`/`DecodeMapEntries`
+    pub flattenable: Option<Span>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VariantAttrs {
+    pub key: Option<usize>,
+    pub mode: Option<ContainerMode>,
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+    pub rename_all: Option<RenameRule>,
+}
+
+/// Where the value of a missing or skipped field comes from.
+#[derive(Debug, Clone)]
+pub enum FieldDefault {
+    /// `Default::default()`
+    Trait,
+    /// A function returning the value
+    Path(Path),
+    /// The field of `<Self as Default>::default()`, with the span of the
+    /// container attribute
+    Container(Span),
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldAttrs {
     pub key: Option<usize>,
     pub bytes: bool,
-    pub default: bool,
+    pub default: Option<FieldDefault>,
     pub encode_with: Option<Path>,
     pub decode_with: Option<Path>,
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+    pub skip: bool,
+    pub skip_encoding_if: Option<Path>,
+    pub flatten: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -57,11 +103,19 @@ pub struct FieldInfo {
 
 impl FieldInfo {
     pub fn is_skipped_for_encode(&self) -> bool {
-        self.is_phantom
+        self.is_phantom || self.attrs.skip
     }
 
     pub fn is_skipped_for_decode(&self) -> bool {
-        self.is_phantom
+        self.is_phantom || self.attrs.skip
+    }
+
+    /// The map key followed by the aliases accepted when decoding.
+    pub fn wire_names(&self) -> impl Iterator<Item = &str> {
+        self.key_name
+            .as_deref()
+            .into_iter()
+            .chain(self.attrs.aliases.iter().map(String::as_str))
     }
 }
 
@@ -72,52 +126,336 @@ pub enum StructStyle {
     Unit,
 }
 
+impl StructStyle {
+    pub fn fields(&self) -> &[FieldInfo] {
+        match self {
+            StructStyle::Named(fields) | StructStyle::Tuple(fields) => fields,
+            StructStyle::Unit => &[],
+        }
+    }
+
+    /// A tuple with a single field, encoded as that field alone.
+    pub fn newtype(&self) -> Option<&FieldInfo> {
+        match self {
+            StructStyle::Tuple(fields)
+                if fields.len() == 1 && !fields[0].is_skipped_for_encode() =>
+            {
+                Some(&fields[0])
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct StructInfo {
+pub struct VariantInfo {
+    pub ident: Ident,
+    pub attrs: VariantAttrs,
+    pub style: StructStyle,
+    pub span: Span,
+    /// Wire id used by `index` enums
+    pub id: u64,
+    /// Wire name used by `name` and `tag` enums
+    pub name: String,
+}
+
+impl VariantInfo {
+    pub fn is_newtype(&self) -> bool {
+        self.attrs.mode.is_none() && self.style.newtype().is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum InputData {
+    Struct(StructStyle),
+    Enum(EnumRepr, Vec<VariantInfo>),
+}
+
+impl InputData {
+    pub fn fields(&self) -> Vec<&FieldInfo> {
+        match self {
+            InputData::Struct(style) => style.fields().iter().collect(),
+            InputData::Enum(_, variants) => variants
+                .iter()
+                .flat_map(|variant| variant.style.fields())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputInfo {
     pub ident: Ident,
     pub generics: Generics,
     pub container: ContainerAttrs,
-    pub style: StructStyle,
+    pub data: InputData,
 }
 
-pub fn parse_struct(input: DeriveInput, kind: DeriveKind) -> syn::Result<StructInfo> {
+pub fn parse_input(input: DeriveInput, kind: DeriveKind) -> syn::Result<InputInfo> {
     let ident = input.ident;
     let generics = input.generics;
     let container = parse_container_attrs(&input.attrs)?;
 
-    let style = match input.data {
-        Data::Struct(data) => parse_fields(data.fields)?,
+    let data = match input.data {
+        Data::Struct(data) => {
+            if let Some((_, span)) = &container.repr {
+                return Err(Error::new(
+                    *span,
+                    "enum representations are only supported on enums",
+                ));
+            }
+            let mut style = parse_fields(data.fields, container.rename_all)?;
+            validate_flatten(style.fields(), container.mode)?;
+            if let Some(span) = container.flattenable
+                && (!matches!(style, StructStyle::Named(_))
+                    || !matches!(container.mode, None | Some(ContainerMode::Map))
+                    || container.ext.is_some())
+            {
+                return Err(Error::new(
+                    span,
+                    "`flattenable` is only supported on named-field structs in map mode with string keys",
+                ));
+            }
+            if let Some(span) = container.default
+                && let StructStyle::Named(fields) | StructStyle::Tuple(fields) = &mut style
+            {
+                for field in fields
+                    .iter_mut()
+                    .filter(|field| field.attrs.default.is_none())
+                {
+                    field.attrs.default = Some(FieldDefault::Container(span));
+                }
+            }
+            InputData::Struct(style)
+        }
         Data::Enum(data) => {
-            return Err(Error::new(
-                data.enum_token.span,
-                format!("{} derive is not yet supported for enums", kind.name()),
-            ));
+            if let Some(span) = container.default {
+                return Err(Error::new(
+                    span,
+                    "container `default` is only supported on structs",
+                ));
+            }
+            if let Some(span) = container.flattenable {
+                return Err(Error::new(
+                    span,
+                    "`flattenable` is only supported on structs",
+                ));
+            }
+            if container.mode.is_some() {
+                return Err(Error::new(
+                    data.enum_token.span,
+                    "enums cannot use `map` or `array`; use `index`, `name`, `tag = \"...\"` or `untagged`",
+                ));
+            }
+            if data.variants.is_empty() {
+                return Err(Error::new(
+                    data.enum_token.span,
+                    format!("{} derive requires at least one enum variant", kind.name()),
+                ));
+            }
+            let repr = container
+                .repr
+                .as_ref()
+                .map(|(repr, _)| repr.clone())
+                .unwrap_or(EnumRepr::Index);
+            let variants = data
+                .variants
+                .into_iter()
+                .enumerate()
+                .map(|(index, variant)| parse_variant(index, variant, container.rename_all))
+                .collect::<syn::Result<Vec<_>>>()?;
+            validate_variants(&repr, &variants)?;
+            InputData::Enum(repr, variants)
         }
         Data::Union(data) => {
             return Err(Error::new(
                 data.union_token.span,
-                format!("{} derive is only supported for structs", kind.name()),
+                format!(
+                    "{} derive is only supported for structs and enums",
+                    kind.name()
+                ),
             ));
         }
     };
 
-    Ok(StructInfo {
+    Ok(InputInfo {
         ident,
         generics,
         container,
+        data,
+    })
+}
+
+fn parse_variant(
+    index: usize,
+    variant: syn::Variant,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<VariantInfo> {
+    let span = variant.ident.span();
+    let attrs = parse_variant_attrs(&variant.attrs)?;
+    let style = parse_fields(variant.fields, attrs.rename_all)?;
+    validate_flatten(style.fields(), attrs.mode)?;
+    let id = attrs.key.unwrap_or(index) as u64;
+    let ident = variant.ident.unraw().to_string();
+    let name = match (&attrs.rename, rename_all) {
+        (Some(name), _) => name.clone(),
+        (None, Some(rule)) => rule.apply_to_variant(&ident),
+        (None, None) => ident,
+    };
+    Ok(VariantInfo {
+        ident: variant.ident,
+        attrs,
         style,
+        span,
+        id,
+        name,
     })
 }
 
-fn parse_fields(fields: Fields) -> syn::Result<StructStyle> {
+fn validate_variants(repr: &EnumRepr, variants: &[VariantInfo]) -> syn::Result<()> {
+    let mut ids = HashSet::new();
+    let mut names = HashSet::new();
+    let mut has_unit = false;
+    for variant in variants {
+        if matches!(repr, EnumRepr::Name | EnumRepr::Tag(_)) {
+            for name in std::iter::once(&variant.name).chain(&variant.attrs.aliases) {
+                if !names.insert(name.as_str()) {
+                    return Err(Error::new(
+                        variant.span,
+                        format!("variant name `{name}` is used more than once"),
+                    ));
+                }
+            }
+        }
+        if variant.attrs.key.is_some() && *repr != EnumRepr::Index {
+            return Err(Error::new(
+                variant.span,
+                "variant `key` is only used by `index` enums",
+            ));
+        }
+        if *repr == EnumRepr::Index && !ids.insert(variant.id) {
+            return Err(Error::new(
+                variant.span,
+                format!("variant id {} is used more than once", variant.id),
+            ));
+        }
+        if matches!(variant.style, StructStyle::Unit) && variant.attrs.mode.is_some() {
+            return Err(Error::new(
+                variant.span,
+                "unit variants cannot use `map` or `array`",
+            ));
+        }
+        match repr {
+            EnumRepr::Tag(tag) => match &variant.style {
+                StructStyle::Tuple(_) => {
+                    return Err(Error::new(
+                        variant.span,
+                        "`tag` enums only support unit and struct variants",
+                    ));
+                }
+                StructStyle::Named(fields) => {
+                    if let Some(field) = fields.iter().find(|field| field.attrs.flatten) {
+                        return Err(Error::new(
+                            field.span,
+                            "`tag` enums do not support `flatten` fields",
+                        ));
+                    }
+                    if variant.attrs.mode == Some(ContainerMode::Array) {
+                        return Err(Error::new(
+                            variant.span,
+                            "`tag` enums encode struct variants as maps and cannot use `array`",
+                        ));
+                    }
+                    if variant.attrs.mode == Some(ContainerMode::IntMap) {
+                        return Err(Error::new(
+                            variant.span,
+                            "`tag` enums use string keys and cannot use `int_keys`",
+                        ));
+                    }
+                    if let Some(field) = fields
+                        .iter()
+                        .find(|field| field.wire_names().any(|name| name == tag))
+                    {
+                        return Err(Error::new(
+                            field.span,
+                            format!("field `{tag}` conflicts with the enum tag"),
+                        ));
+                    }
+                }
+                StructStyle::Unit => {}
+            },
+            EnumRepr::Untagged => {
+                if matches!(variant.style, StructStyle::Unit) {
+                    if has_unit {
+                        return Err(Error::new(
+                            variant.span,
+                            "`untagged` enums can have at most one unit variant, others would never be decoded",
+                        ));
+                    }
+                    has_unit = true;
+                }
+            }
+            EnumRepr::Index | EnumRepr::Name => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_variant_attrs(attrs: &[Attribute]) -> syn::Result<VariantAttrs> {
+    let mut out = VariantAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("msgpack") {
+            continue;
+        }
+        let Meta::List(list) = &attr.meta else {
+            return Err(Error::new(
+                attr.meta.span(),
+                "expected #[msgpack(...)] variant attribute",
+            ));
+        };
+        list.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                let value = meta.value()?;
+                let lit: LitInt = value.parse()?;
+                out.key = Some(lit.base10_parse()?);
+                return Ok(());
+            }
+            if is_mode_attr(&meta) {
+                return parse_mode(&meta, &mut out.mode);
+            }
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                out.rename = Some(lit.value());
+                return Ok(());
+            }
+            if meta.path.is_ident("alias") {
+                let lit: LitStr = meta.value()?.parse()?;
+                out.aliases.push(lit.value());
+                return Ok(());
+            }
+            if meta.path.is_ident("rename_all") {
+                out.rename_all = Some(parse_rename_rule(&meta)?);
+                return Ok(());
+            }
+            Err(meta.error("unsupported variant attribute"))
+        })?;
+    }
+
+    Ok(out)
+}
+
+fn parse_fields(fields: Fields, rename_all: Option<RenameRule>) -> syn::Result<StructStyle> {
     match fields {
-        Fields::Named(named) => Ok(StructStyle::Named(
-            named
+        Fields::Named(named) => {
+            let fields = named
                 .named
                 .into_iter()
-                .map(parse_named_field)
-                .collect::<syn::Result<Vec<_>>>()?,
-        )),
+                .map(|field| parse_named_field(field, rename_all))
+                .collect::<syn::Result<Vec<_>>>()?;
+            validate_unique_keys(&fields)?;
+            Ok(StructStyle::Named(fields))
+        }
         Fields::Unnamed(unnamed) => Ok(StructStyle::Tuple(
             unnamed
                 .unnamed
@@ -130,25 +468,139 @@ fn parse_fields(fields: Fields) -> syn::Result<StructStyle> {
     }
 }
 
-fn parse_named_field(field: Field) -> syn::Result<FieldInfo> {
+fn parse_named_field(field: Field, rename_all: Option<RenameRule>) -> syn::Result<FieldInfo> {
     let attrs = parse_field_attrs(&field.attrs)?;
+    if attrs.skip && attrs.key.is_some() {
+        return Err(Error::new(
+            field.span(),
+            "skipped fields cannot use `#[msgpack(key = N)]`",
+        ));
+    }
     let ident = field
         .ident
         .clone()
         .ok_or_else(|| Error::new(field.span(), "expected named field"))?;
+    if attrs.flatten
+        && (attrs.skip
+            || attrs.key.is_some()
+            || attrs.bytes
+            || attrs.rename.is_some()
+            || !attrs.aliases.is_empty()
+            || attrs.encode_with.is_some()
+            || attrs.decode_with.is_some())
+    {
+        return Err(Error::new(
+            field.span(),
+            "`flatten` cannot be combined with `skip`, `key`, `bytes`, `rename`, `alias`, `encode_with` or `decode_with`",
+        ));
+    }
+    let key_name = match (&attrs.rename, rename_all) {
+        _ if attrs.flatten => None,
+        (Some(name), _) => Some(name.clone()),
+        (None, Some(rule)) => Some(rule.apply_to_field(&ident.unraw().to_string())),
+        (None, None) => Some(ident.unraw().to_string()),
+    };
     Ok(FieldInfo {
         member: Member::Named(ident.clone()),
         ty: field.ty.clone(),
         attrs,
         span: field.span(),
         name: Some(ident.clone()),
-        key_name: Some(ident.to_string()),
+        key_name,
         is_phantom: is_phantom_data(&field.ty),
     })
 }
 
+fn validate_flatten(fields: &[FieldInfo], mode: Option<ContainerMode>) -> syn::Result<()> {
+    if matches!(mode, Some(ContainerMode::Array | ContainerMode::IntMap))
+        && let Some(field) = fields.iter().find(|field| field.attrs.flatten)
+    {
+        return Err(Error::new(
+            field.span,
+            "`flatten` is only supported in map mode with string keys",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_unique_keys(fields: &[FieldInfo]) -> syn::Result<()> {
+    let mut seen = HashSet::new();
+    for field in fields.iter().filter(|field| !field.is_skipped_for_decode()) {
+        for name in field.wire_names() {
+            if !seen.insert(name) {
+                return Err(Error::new(
+                    field.span,
+                    format!("key `{name}` is used by more than one field"),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The active fields of an `int_keys` map, each with a distinct `key = N`.
+pub fn int_key_fields<'a>(
+    fields: impl IntoIterator<Item = &'a FieldInfo>,
+) -> syn::Result<Vec<&'a FieldInfo>> {
+    let mut seen = HashSet::new();
+    let mut active = Vec::new();
+    for field in fields {
+        let Some(key) = field.attrs.key else {
+            return Err(Error::new(
+                field.span,
+                "all fields must have `#[msgpack(key = N)]` when using `#[msgpack(int_keys)]`",
+            ));
+        };
+        if field.attrs.rename.is_some() || !field.attrs.aliases.is_empty() {
+            return Err(Error::new(
+                field.span,
+                "`rename` and `alias` cannot be used with `#[msgpack(int_keys)]`",
+            ));
+        }
+        if !seen.insert(key) {
+            return Err(Error::new(
+                field.span,
+                format!("key {key} is used by more than one field"),
+            ));
+        }
+        active.push(field);
+    }
+    Ok(active)
+}
+
+fn parse_rename_rule(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<RenameRule> {
+    let lit: LitStr = meta.value()?.parse()?;
+    RenameRule::from_str(&lit.value()).ok_or_else(|| {
+        Error::new(
+            lit.span(),
+            format!(
+                "unknown `rename_all` rule, expected one of {}",
+                RenameRule::names()
+            ),
+        )
+    })
+}
+
 fn parse_unnamed_field(index: usize, field: Field) -> syn::Result<FieldInfo> {
     let attrs = parse_field_attrs(&field.attrs)?;
+    if attrs.rename.is_some() || !attrs.aliases.is_empty() {
+        return Err(Error::new(
+            field.span(),
+            "`rename` and `alias` only apply to named fields",
+        ));
+    }
+    if attrs.skip_encoding_if.is_some() || attrs.flatten {
+        return Err(Error::new(
+            field.span(),
+            "`skip_encoding_if` and `flatten` only apply to named fields",
+        ));
+    }
+    if attrs.skip && attrs.key.is_some() {
+        return Err(Error::new(
+            field.span(),
+            "skipped fields cannot use `#[msgpack(key = N)]`",
+        ));
+    }
     Ok(FieldInfo {
         member: Member::Unnamed(index.into()),
         ty: field.ty.clone(),
@@ -181,20 +633,97 @@ fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
     Ok(out)
 }
 
+fn is_mode_attr(meta: &syn::meta::ParseNestedMeta<'_>) -> bool {
+    ["map", "array", "int_keys"]
+        .iter()
+        .any(|name| meta.path.is_ident(name))
+}
+
+/// `int_keys` implies `map`, so the two may be combined.
+fn parse_mode(
+    meta: &syn::meta::ParseNestedMeta<'_>,
+    mode: &mut Option<ContainerMode>,
+) -> syn::Result<()> {
+    *mode = if meta.path.is_ident("int_keys") {
+        match mode {
+            None | Some(ContainerMode::Map) => Some(ContainerMode::IntMap),
+            Some(ContainerMode::Array) => {
+                return Err(meta.error("`int_keys` cannot be used with `array`"));
+            }
+            Some(ContainerMode::IntMap) => return Err(meta.error("duplicate `int_keys`")),
+        }
+    } else if meta.path.is_ident("map") {
+        match mode {
+            None => Some(ContainerMode::Map),
+            Some(ContainerMode::IntMap) => Some(ContainerMode::IntMap),
+            Some(_) => return Err(meta.error("`map` and `array` are mutually exclusive")),
+        }
+    } else {
+        match mode {
+            None => Some(ContainerMode::Array),
+            Some(ContainerMode::IntMap) => {
+                return Err(meta.error("`int_keys` cannot be used with `array`"));
+            }
+            Some(_) => return Err(meta.error("`map` and `array` are mutually exclusive")),
+        }
+    };
+    Ok(())
+}
+
 fn parse_container_list(list: &MetaList, out: &mut ContainerAttrs) -> syn::Result<()> {
     list.parse_nested_meta(|meta| {
-        if meta.path.is_ident("map") {
-            if out.mode.is_some() {
-                return Err(meta.error("`map` and `array` are mutually exclusive"));
+        if is_mode_attr(&meta) {
+            return parse_mode(&meta, &mut out.mode);
+        }
+        let repr = if meta.path.is_ident("index") {
+            Some(EnumRepr::Index)
+        } else if meta.path.is_ident("name") {
+            Some(EnumRepr::Name)
+        } else if meta.path.is_ident("untagged") {
+            Some(EnumRepr::Untagged)
+        } else if meta.path.is_ident("tag") {
+            let lit: LitStr = meta.value()?.parse()?;
+            Some(EnumRepr::Tag(lit.value()))
+        } else {
+            None
+        };
+        if let Some(repr) = repr {
+            if out.repr.is_some() {
+                return Err(
+                    meta.error("`index`, `name`, `tag` and `untagged` are mutually exclusive")
+                );
             }
-            out.mode = Some(ContainerMode::Map);
+            out.repr = Some((repr, meta.path.span()));
+            return Ok(());
+        }
+        if meta.path.is_ident("rename_all") {
+            out.rename_all = Some(parse_rename_rule(&meta)?);
+            return Ok(());
+        }
+        if meta.path.is_ident("default") {
+            out.default = Some(meta.path.span());
             return Ok(());
         }
-        if meta.path.is_ident("array") {
-            if out.mode.is_some() {
-                return Err(meta.error("`map` and `array` are mutually exclusive"));
+        if meta.path.is_ident("flattenable") {
+            out.flattenable = Some(meta.path.span());
+            return Ok(());
+        }
+        if meta.path.is_ident("ext") {
+            if out.ext.is_some() {
+                return Err(meta.error("duplicate `ext` attribute"));
             }
-            out.mode = Some(ContainerMode::Array);
+            let value = meta.value()?;
+            let negative = value.parse::<Option<syn::Token![-]>>()?.is_some();
+            let lit: LitInt = value.parse()?;
+            let digits = if negative {
+                format!("-{}", lit.base10_digits())
+            } else {
+                lit.base10_digits().to_owned()
+            };
+            let r#type: i8 = digits
+                .parse()
+                .map_err(|_| Error::new(lit.span(), "extension type must fit in `i8`"))?;
+            out.ext = Some(r#type);
             return Ok(());
         }
 
@@ -223,7 +752,25 @@ fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
                         return Ok(());
                     }
                     if meta.path.is_ident("default") {
-                        out.default = true;
+                        out.default = Some(if meta.input.peek(syn::Token![=]) {
+                            let lit: LitStr = meta.value()?.parse()?;
+                            FieldDefault::Path(lit.parse()?)
+                        } else {
+                            FieldDefault::Trait
+                        });
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("skip") {
+                        out.skip = true;
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("flatten") {
+                        out.flatten = true;
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("skip_encoding_if") {
+                        let lit: LitStr = meta.value()?.parse()?;
+                        out.skip_encoding_if = Some(lit.parse()?);
                         return Ok(());
                     }
                     if meta.path.is_ident("encode_with") {
@@ -238,6 +785,16 @@ fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
                         out.decode_with = Some(lit.parse()?);
                         return Ok(());
                     }
+                    if meta.path.is_ident("rename") {
+                        let lit: LitStr = meta.value()?.parse()?;
+                        out.rename = Some(lit.value());
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("alias") {
+                        let lit: LitStr = meta.value()?.parse()?;
+                        out.aliases.push(lit.value());
+                        return Ok(());
+                    }
                     Err(meta.error("unsupported field attribute"))
                 })?;
             }
@@ -477,6 +1034,15 @@ fn strip_groups(mut ty: &Type) -> &Type {
     }
 }
 
+pub fn field_local(field: &FieldInfo) -> Ident {
+    match &field.member {
+        Member::Named(name) => Ident::new(&format!("__{}", name.unraw()), name.span()),
+        Member::Unnamed(index) => {
+            Ident::new(&format!("__field_{}", index.index), Span::call_site())
+        }
+    }
+}
+
 pub fn decode_lifetime() -> syn::Lifetime {
     parse_quote!('__msgpack_de)
 }
@@ -2,9 +2,13 @@
 
 mod enum_;
 mod error;
+mod raw;
 mod seq;
+mod stream;
 use error::CoreError;
 pub use error::Error;
+pub(crate) use raw::skip_value;
+pub use stream::{EndOfInput, StreamDeserializer, from_slice_partial};
 
 use messagepack_core::{
     Decode, Format,
@@ -35,6 +39,16 @@ pub fn from_slice<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, Error
     from_core_reader(reader)
 }
 
+/// Deserialize from slice with a [`DeserializeSeed`](de::DeserializeSeed)
+pub fn from_slice_seed<'de, S>(input: &'de [u8], seed: S) -> Result<S::Value, Error<RError>>
+where
+    S: de::DeserializeSeed<'de>,
+{
+    use messagepack_core::io::SliceReader;
+    let mut deserializer = Deserializer::new(SliceReader::new(input));
+    seed.deserialize(&mut deserializer)
+}
+
 #[cfg(feature = "std")]
 /// Deserialize from [std::io::Read]
 #[inline]
@@ -258,6 +272,14 @@ where
     where
         V: de::Visitor<'de>,
     {
+        // Peek the marker when possible so that `Some` sees untouched input.
+        if self.format.is_none()
+            && let Some(&marker) = self.reader.remaining_slice().and_then(<[u8]>::first)
+            && marker != Format::Nil.as_byte()
+        {
+            return visitor.visit_some(self);
+        }
+
         let format = self.decode_format()?;
         match format {
             Format::Nil => visitor.visit_none(),
@@ -268,6 +290,20 @@ where
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match name {
+            crate::value::RAW_VALUE_STRUCT_NAME => self.deserialize_raw(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
@@ -298,7 +334,7 @@ where
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        bytes byte_buf unit unit_struct seq tuple
         tuple_struct map struct identifier ignored_any
     }
 
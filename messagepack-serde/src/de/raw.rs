@@ -0,0 +1,175 @@
+use messagepack_core::{
+    Format,
+    decode::{Any, DecodeBorrowed, NbyteReader},
+    io::IoRead,
+};
+use serde::de;
+
+use super::{CoreError, Deserializer, Error};
+
+/// Skip exactly one value whose marker has already been read.
+///
+/// Containers are walked with a pending-element counter instead of recursion,
+/// so deeply nested input cannot overflow the stack.
+pub(crate) fn skip_value<'de, R>(format: Format, reader: &mut R) -> Result<(), CoreError<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let mut format = format;
+    let mut pending: usize = 0;
+    loop {
+        let children = match format {
+            Format::FixArray(n) => usize::from(n),
+            Format::Array16 => NbyteReader::<2>::read(reader)?,
+            Format::Array32 => NbyteReader::<4>::read(reader)?,
+            Format::FixMap(n) => usize::from(n) * 2,
+            Format::Map16 => NbyteReader::<2>::read(reader)? * 2,
+            Format::Map32 => NbyteReader::<4>::read(reader)?
+                .checked_mul(2)
+                .ok_or(CoreError::InvalidData)?,
+            _ => {
+                Any::decode_borrowed_with_format(format, reader)?;
+                0
+            }
+        };
+        pending = pending
+            .checked_add(children)
+            .ok_or(CoreError::InvalidData)?;
+
+        if pending == 0 {
+            return Ok(());
+        }
+        pending -= 1;
+        format = Format::decode_borrowed(reader)?;
+    }
+}
+
+/// Reader adapter that keeps a copy of every consumed byte.
+#[cfg(feature = "alloc")]
+struct Recorder<'r, R> {
+    reader: &'r mut R,
+    buf: alloc::vec::Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, R> IoRead<'de> for Recorder<'_, R>
+where
+    R: IoRead<'de>,
+{
+    type Error = R::Error;
+
+    #[inline]
+    fn read_slice<'a>(
+        &'a mut self,
+        len: usize,
+    ) -> Result<messagepack_core::io::Reference<'de, 'a>, Self::Error> {
+        let data = self.reader.read_slice(len)?;
+        self.buf.extend_from_slice(data.as_bytes());
+        Ok(data)
+    }
+}
+
+impl<'de, R> Deserializer<R>
+where
+    R: IoRead<'de>,
+{
+    /// Capture the encoded bytes of the next value.
+    ///
+    /// The bytes are handed to `visitor` as a newtype struct wrapping a bytes
+    /// deserializer. They are borrowed from the input when the reader allows
+    /// it, and copied otherwise.
+    pub(super) fn deserialize_raw<V>(&mut self, visitor: V) -> Result<V::Value, Error<R::Error>>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.format.is_none()
+            && let Some(rest) = self.reader.remaining_slice()
+        {
+            let format = Format::decode_borrowed(&mut self.reader)?;
+            skip_value(format, &mut self.reader)?;
+            let unread = self.reader.remaining_slice().map_or(0, <[u8]>::len);
+            let raw = &rest[..rest.len() - unread];
+            return visitor.visit_newtype_struct(de::value::BorrowedBytesDeserializer::<
+                Error<R::Error>,
+            >::new(raw));
+        }
+
+        self.deserialize_raw_copied(visitor)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn deserialize_raw_copied<V>(&mut self, visitor: V) -> Result<V::Value, Error<R::Error>>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut recorder = Recorder {
+            reader: &mut self.reader,
+            buf: alloc::vec::Vec::new(),
+        };
+        let format = match self.format.take() {
+            Some(format) => {
+                recorder.buf.push(format.as_byte());
+                format
+            }
+            None => Format::decode_borrowed(&mut recorder)?,
+        };
+        skip_value(format, &mut recorder)?;
+        visitor.visit_newtype_struct(de::value::BytesDeserializer::<Error<R::Error>>::new(
+            &recorder.buf,
+        ))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn deserialize_raw_copied<V>(&mut self, _visitor: V) -> Result<V::Value, Error<R::Error>>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom(
+            "raw values require a borrowing reader without `alloc`",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messagepack_core::io::SliceReader;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[0xc0])]
+    #[case(&[0xcd, 0x01, 0x00])]
+    #[case(&[0xa2, 0x61, 0x62])]
+    #[case(&[0x92, 0x01, 0x91, 0xc0])]
+    #[case(&[0x81, 0xa1, 0x61, 0x80])]
+    #[case(&[0xd4, 0x05, 0x01])]
+    #[case(&[0xdc, 0x00, 0x01, 0x90])]
+    fn skip_consumes_one_value(#[case] value: &[u8]) {
+        let mut buf = value.to_vec();
+        buf.push(0xc3);
+        let mut reader = SliceReader::new(&buf);
+        let format = Format::decode_borrowed(&mut reader).unwrap();
+        skip_value(format, &mut reader).unwrap();
+        assert_eq!(reader.rest(), &[0xc3]);
+    }
+
+    #[test]
+    fn skip_deeply_nested_array() {
+        let mut buf = vec![0x91; 100_000];
+        buf.push(0xc0);
+        let mut reader = SliceReader::new(&buf);
+        let format = Format::decode_borrowed(&mut reader).unwrap();
+        skip_value(format, &mut reader).unwrap();
+        assert!(reader.rest().is_empty());
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0x01])]
+    #[case(&[0x81, 0xa1, 0x61])]
+    #[case(&[0xa2, 0x61])]
+    fn skip_truncated_is_error(#[case] buf: &[u8]) {
+        let mut reader = SliceReader::new(buf);
+        let format = Format::decode_borrowed(&mut reader).unwrap();
+        assert!(skip_value(format, &mut reader).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use core::marker::PhantomData;
+
+use messagepack_core::{
+    Format,
+    decode::DecodeBorrowed,
+    io::{IoRead, RError, Reference, SliceReader},
+};
+use serde::Deserialize;
+
+use super::{CoreError, Deserializer, Error};
+
+/// Reader errors which can tell a clean end of input apart from other failures.
+///
+/// [`StreamDeserializer`] uses this to stop iteration when the input ends
+/// exactly between two values.
+pub trait EndOfInput {
+    /// Returns `true` if the error was caused by running out of input.
+    fn is_end_of_input(&self) -> bool;
+}
+
+impl EndOfInput for RError {
+    fn is_end_of_input(&self) -> bool {
+        matches!(self, RError::BufferEmpty)
+    }
+}
+
+#[cfg(feature = "std")]
+impl EndOfInput for std::io::Error {
+    fn is_end_of_input(&self) -> bool {
+        self.kind() == std::io::ErrorKind::UnexpectedEof
+    }
+}
+
+/// Reader adapter that counts consumed bytes.
+struct CountingReader<R> {
+    reader: R,
+    offset: usize,
+}
+
+impl<'de, R> IoRead<'de> for CountingReader<R>
+where
+    R: IoRead<'de>,
+{
+    type Error = R::Error;
+
+    #[inline]
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a>, Self::Error> {
+        let data = self.reader.read_slice(len)?;
+        self.offset += len;
+        Ok(data)
+    }
+
+    #[inline]
+    fn remaining_slice(&self) -> Option<&'de [u8]> {
+        self.reader.remaining_slice()
+    }
+}
+
+/// Iterator that deserializes a stream of concatenated MessagePack values.
+///
+/// Each call to [`Iterator::next`] decodes one value. Iteration ends with
+/// `None` when the input is exhausted exactly at a value boundary, while
+/// running out of input in the middle of a value is reported as an error.
+/// After an error is returned, the iterator yields no further items.
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_core::io::SliceReader;
+/// use messagepack_serde::de::StreamDeserializer;
+///
+/// // 1, 2, 256
+/// let buf: &[u8] = &[0x01, 0x02, 0xcd, 0x01, 0x00];
+///
+/// let mut stream = StreamDeserializer::<_, u16>::new(SliceReader::new(buf));
+/// assert_eq!(stream.next().unwrap().unwrap(), 1);
+/// assert_eq!(stream.byte_offset(), 1);
+/// assert_eq!(stream.next().unwrap().unwrap(), 2);
+/// assert_eq!(stream.byte_offset(), 2);
+/// assert_eq!(stream.next().unwrap().unwrap(), 256);
+/// assert_eq!(stream.byte_offset(), 5);
+/// assert!(stream.next().is_none());
+/// ```
+pub struct StreamDeserializer<'de, R, T> {
+    de: Deserializer<CountingReader<R>>,
+    failed: bool,
+    output: PhantomData<(&'de (), T)>,
+}
+
+impl<'de, R, T> StreamDeserializer<'de, R, T>
+where
+    R: IoRead<'de>,
+    T: Deserialize<'de>,
+{
+    /// Create a new stream over `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            de: Deserializer::new(CountingReader { reader, offset: 0 }),
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Number of bytes consumed from the reader so far.
+    ///
+    /// After a successful `next`, this is the offset just past the returned value.
+    pub fn byte_offset(&self) -> usize {
+        self.de.reader.offset
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.de.reader.reader
+    }
+}
+
+impl<'de, R, T> Iterator for StreamDeserializer<'de, R, T>
+where
+    R: IoRead<'de>,
+    R::Error: EndOfInput,
+    T: Deserialize<'de>,
+{
+    type Item = Result<T, Error<R::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        // Borrowing readers can detect the end without consuming the marker,
+        // which leaves the value intact for deserializers that capture raw bytes.
+        match self.de.reader.remaining_slice() {
+            Some([]) => return None,
+            Some(_) => {}
+            None => {
+                let format =
+                    match <Format as DecodeBorrowed<'de>>::decode_borrowed(&mut self.de.reader) {
+                        Ok(format) => format,
+                        Err(CoreError::Io(e)) if e.is_end_of_input() => return None,
+                        Err(e) => {
+                            self.failed = true;
+                            return Some(Err(e.into()));
+                        }
+                    };
+                self.de.format = Some(format);
+            }
+        }
+
+        let result = T::deserialize(&mut self.de);
+        self.de.format = None;
+        if result.is_err() {
+            self.failed = true;
+        }
+        Some(result)
+    }
+}
+
+/// Deserialize one value from the front of a slice and return the remaining bytes.
+///
+/// ## Example
+///
+/// ```rust
+/// let buf: &[u8] = &[0x01, 0xa1, 0x61];
+/// let (n, rest) = messagepack_serde::de::from_slice_partial::<u8>(buf).unwrap();
+/// assert_eq!(n, 1);
+/// assert_eq!(rest, &[0xa1, 0x61]);
+/// ```
+pub fn from_slice_partial<'de, T>(input: &'de [u8]) -> Result<(T, &'de [u8]), Error<RError>>
+where
+    T: Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::new(SliceReader::new(input));
+    let value = T::deserialize(&mut deserializer)?;
+    Ok((value, deserializer.reader.rest()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn stream_reads_until_end() {
+        use crate::Value;
+
+        // 1, "a", {"b": nil}
+        let buf: &[u8] = &[0x01, 0xa1, 0x61, 0x81, 0xa1, 0x62, 0xc0];
+        let stream = StreamDeserializer::<_, Value>::new(SliceReader::new(buf));
+
+        let values = stream.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Value::from(1u8),
+                Value::from("a"),
+                Value::Map(vec![(Value::from("b"), Value::Nil)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn stream_reports_byte_offset() {
+        // 1, 256, "ab"
+        let buf: &[u8] = &[0x01, 0xcd, 0x01, 0x00, 0xa2, 0x61, 0x62];
+        let mut stream = StreamDeserializer::<_, serde::de::IgnoredAny>::new(SliceReader::new(buf));
+
+        let mut offsets = vec![];
+        while let Some(v) = stream.next() {
+            v.unwrap();
+            offsets.push(stream.byte_offset());
+        }
+        assert_eq!(offsets, [1, 4, 7]);
+    }
+
+    #[test]
+    fn stream_empty_input_yields_nothing() {
+        let mut stream = StreamDeserializer::<_, u8>::new(SliceReader::new(&[]));
+        assert!(stream.next().is_none());
+        assert_eq!(stream.byte_offset(), 0);
+    }
+
+    #[rstest]
+    // fixstr len 3 with only 1 byte of payload
+    #[case(&[0x01, 0xa3, 0x61])]
+    // array with missing element
+    #[case(&[0x01, 0x92, 0x01])]
+    // uint16 with missing byte
+    #[case(&[0x01, 0xcd, 0x01])]
+    fn stream_truncated_value_is_error(#[case] buf: &[u8]) {
+        let mut stream = StreamDeserializer::<_, serde::de::IgnoredAny>::new(SliceReader::new(buf));
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn stream_option_values() {
+        // nil, 5
+        let buf: &[u8] = &[0xc0, 0x05];
+        let stream = StreamDeserializer::<_, Option<u8>>::new(SliceReader::new(buf));
+        let values = stream.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values, [None, Some(5)]);
+    }
+
+    #[test]
+    fn stream_borrows_from_slice() {
+        // "ab", "c"
+        let buf: &[u8] = &[0xa2, 0x61, 0x62, 0xa1, 0x63];
+        let stream = StreamDeserializer::<_, &str>::new(SliceReader::new(buf));
+        let values = stream.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values, ["ab", "c"]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stream_from_std_reader() {
+        use messagepack_core::io::StdReader;
+
+        let buf = std::io::Cursor::new(vec![0x01, 0x02, 0xcc, 0xff]);
+        let stream = StreamDeserializer::<_, u8>::new(StdReader::new(buf));
+        let values = stream.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values, [1, 2, 255]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stream_from_std_reader_truncated() {
+        use messagepack_core::io::StdReader;
+
+        let buf = std::io::Cursor::new(vec![0x01, 0xcc]);
+        let mut stream = StreamDeserializer::<_, u8>::new(StdReader::new(buf));
+        assert_eq!(stream.next().unwrap().unwrap(), 1);
+        assert!(stream.next().unwrap().is_err());
+    }
+
+    #[rstest]
+    #[case(&[0x01], 1, &[])]
+    #[case(&[0x02, 0xc0, 0xc3], 2, &[0xc0, 0xc3])]
+    fn partial_returns_rest(#[case] buf: &[u8], #[case] expected: u8, #[case] rest: &[u8]) {
+        let (v, r) = from_slice_partial::<u8>(buf).unwrap();
+        assert_eq!(v, expected);
+        assert_eq!(r, rest);
+    }
+
+    #[test]
+    fn partial_truncated_is_error() {
+        assert!(from_slice_partial::<&str>(&[0xa3, 0x61]).is_err());
+    }
+}
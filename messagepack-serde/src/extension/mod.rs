@@ -11,10 +11,23 @@ mod owned;
 #[cfg(feature = "alloc")]
 pub use owned::ext_owned;
 
-mod timestamp;
-pub use timestamp::{timestamp32, timestamp64, timestamp96};
-
-struct Bytes<'a>(pub &'a [u8]);
+#[cfg(feature = "alloc")]
+mod typed;
+#[cfg(feature = "alloc")]
+pub use typed::ext_typed;
+
+mod timestamps;
+#[cfg(feature = "chrono")]
+pub use timestamps::chrono;
+#[cfg(feature = "jiff")]
+pub use timestamps::jiff;
+#[cfg(feature = "std")]
+pub use timestamps::system_time;
+#[cfg(feature = "time")]
+pub use timestamps::time;
+pub use timestamps::{timestamp, timestamp32, timestamp64, timestamp96};
+
+pub(crate) struct Bytes<'a>(pub &'a [u8]);
 impl Serialize for Bytes<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,584 @@
+use crate::extension::ext_fixed;
+
+/// De/Serialize messagepack timestamp extension of any width.
+///
+/// This module allows serializing and deserializing
+/// `messagepack_core::timestamp::Timestamp` as MessagePack timestamp
+/// extension (type `-1`). Serialization picks the smallest of the 4, 8 and
+/// 12-byte payloads that holds the value; deserialization accepts all three.
+///
+/// The `chrono`, `time`, `jiff` and `system_time` modules convert to
+/// `Timestamp` and go through this module, so they share its layout choice
+/// and keep instants before 1970 and nanoseconds.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use messagepack_core::timestamp::Timestamp;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::timestamp")] Timestamp,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(Timestamp::new(123456, 0).unwrap());
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// assert_eq!(n, 6);
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+pub mod timestamp {
+    /// Serialize `Timestamp` as MessagePack extension.
+    pub fn serialize<S>(
+        ts: &messagepack_core::timestamp::Timestamp,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ext: messagepack_core::extension::FixedExtension<12> = (*ts).into();
+        super::ext_fixed::serialize::<12, _>(&ext, serializer)
+    }
+
+    /// Deserialize `Timestamp` from MessagePack extension.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<messagepack_core::timestamp::Timestamp, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ext = super::ext_fixed::deserialize::<12, _>(deserializer)?;
+        ext.try_into()
+            .map_err(|_| serde::de::Error::custom("invalid timestamp"))
+    }
+}
+
+/// De/Serialize messagepack timestamp 32 extension.
+///
+/// This module allows serializing and deserializing
+/// `messagepack_core::timestamp::Timestamp32` as MessagePack
+/// timestamp extension (type `-1`, 4‑byte payload).
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use messagepack_core::timestamp::Timestamp32;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::timestamp32")] Timestamp32,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(Timestamp32::new(123456));
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+pub mod timestamp32 {
+    /// Serialize `Timestamp32` as MessagePack extension.
+    pub fn serialize<S>(
+        ts: &messagepack_core::timestamp::Timestamp32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ext: messagepack_core::extension::FixedExtension<4> = (*ts).into();
+        super::ext_fixed::serialize::<4, _>(&ext, serializer)
+    }
+
+    /// Deserialize `Timestamp32` from MessagePack extension.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<messagepack_core::timestamp::Timestamp32, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ext = super::ext_fixed::deserialize::<4, _>(deserializer)?;
+        ext.try_into()
+            .map_err(|_| serde::de::Error::custom("invalid timestamp32"))
+    }
+}
+
+/// De/Serialize messagepack timestamp 64 extension.
+///
+/// This module allows serializing and deserializing
+/// `messagepack_core::timestamp::Timestamp64` as MessagePack
+/// timestamp extension (type `-1`, 8‑byte payload).
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use messagepack_core::timestamp::Timestamp64;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::timestamp64")] Timestamp64,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(Timestamp64::new(123456, 789).unwrap());
+/// let mut buf = [0u8; 32];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+pub mod timestamp64 {
+    /// Serialize `Timestamp64` as MessagePack extension.
+    pub fn serialize<S>(
+        ts: &messagepack_core::timestamp::Timestamp64,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ext: messagepack_core::extension::FixedExtension<8> = (*ts).into();
+        super::ext_fixed::serialize::<8, _>(&ext, serializer)
+    }
+
+    /// Deserialize `Timestamp64` from MessagePack extension.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<messagepack_core::timestamp::Timestamp64, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ext = super::ext_fixed::deserialize::<8, _>(deserializer)?;
+        ext.try_into()
+            .map_err(|_| serde::de::Error::custom("invalid timestamp64"))
+    }
+}
+
+/// De/Serialize messagepack timestamp 96 extension.
+///
+/// This module allows serializing and deserializing
+/// `messagepack_core::timestamp::Timestamp96` as MessagePack
+/// timestamp extension (type `-1`, 12‑byte payload).
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use messagepack_core::timestamp::Timestamp96;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::timestamp96")] Timestamp96,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(Timestamp96::new(123456, 789).unwrap());
+/// let mut buf = [0u8; 32];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+pub mod timestamp96 {
+    /// Serialize `Timestamp96` as MessagePack extension.
+    pub fn serialize<S>(
+        ts: &messagepack_core::timestamp::Timestamp96,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ext: messagepack_core::extension::FixedExtension<12> = (*ts).into();
+        super::ext_fixed::serialize::<12, _>(&ext, serializer)
+    }
+
+    /// Deserialize `Timestamp96` from MessagePack extension.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<messagepack_core::timestamp::Timestamp96, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ext = super::ext_fixed::deserialize::<12, _>(deserializer)?;
+        ext.try_into()
+            .map_err(|_| serde::de::Error::custom("invalid timestamp96"))
+    }
+}
+
+/// De/Serialize `chrono::DateTime<Utc>` as messagepack timestamp extension.
+///
+/// Written like [`timestamp`]. A leap second rolls over into the next second.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use chrono::{DateTime, Utc};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::chrono")] DateTime<Utc>,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(DateTime::from_timestamp(-1, 5).unwrap());
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    /// Serialize `chrono::DateTime<Utc>` as MessagePack extension.
+    pub fn serialize<S>(
+        value: &::chrono::DateTime<::chrono::Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ts = messagepack_core::timestamp::Timestamp::from(*value);
+        super::timestamp::serialize(&ts, serializer)
+    }
+
+    /// Deserialize `chrono::DateTime<Utc>` from MessagePack extension.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<::chrono::DateTime<::chrono::Utc>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ts = super::timestamp::deserialize(deserializer)?;
+        ts.try_into()
+            .map_err(|_| serde::de::Error::custom("timestamp out of range"))
+    }
+}
+
+/// De/Serialize `time::OffsetDateTime` as messagepack timestamp extension.
+///
+/// Written like [`timestamp`]. The offset is not kept and values deserialize
+/// in UTC.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use time::OffsetDateTime;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::time")] OffsetDateTime,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(OffsetDateTime::from_unix_timestamp_nanos(-1_500_000_000).unwrap());
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+#[cfg(feature = "time")]
+pub mod time {
+    /// Serialize `time::OffsetDateTime` as MessagePack extension.
+    pub fn serialize<S>(value: &::time::OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ts = messagepack_core::timestamp::Timestamp::from(*value);
+        super::timestamp::serialize(&ts, serializer)
+    }
+
+    /// Deserialize `time::OffsetDateTime` from MessagePack extension.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<::time::OffsetDateTime, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ts = super::timestamp::deserialize(deserializer)?;
+        ts.try_into()
+            .map_err(|_| serde::de::Error::custom("timestamp out of range"))
+    }
+}
+
+/// De/Serialize `jiff::Timestamp` as messagepack timestamp extension.
+///
+/// Written like [`timestamp`].
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::jiff")] jiff::Timestamp,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(jiff::Timestamp::new(-1, 5).unwrap());
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+#[cfg(feature = "jiff")]
+pub mod jiff {
+    /// Serialize `jiff::Timestamp` as MessagePack extension.
+    pub fn serialize<S>(value: &::jiff::Timestamp, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ts = messagepack_core::timestamp::Timestamp::from(*value);
+        super::timestamp::serialize(&ts, serializer)
+    }
+
+    /// Deserialize `jiff::Timestamp` from MessagePack extension.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<::jiff::Timestamp, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ts = super::timestamp::deserialize(deserializer)?;
+        ts.try_into()
+            .map_err(|_| serde::de::Error::custom("timestamp out of range"))
+    }
+}
+
+/// De/Serialize `std::time::SystemTime` as messagepack timestamp extension.
+///
+/// Written like [`timestamp`]. Times before 1970 use the signed seconds of
+/// timestamp 96.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Wrap(
+///     #[serde(with = "messagepack_serde::extension::system_time")] SystemTime,
+/// );
+///
+/// # fn main() {
+/// let v = Wrap(SystemTime::UNIX_EPOCH - Duration::from_millis(1500));
+/// let mut buf = [0u8; 16];
+/// let n = messagepack_serde::to_slice(&v, &mut buf).unwrap();
+/// let back = messagepack_serde::from_slice::<Wrap>(&buf[..n]).unwrap();
+/// assert_eq!(v, back);
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub mod system_time {
+    use serde::{de, ser};
+
+    /// Serialize `SystemTime` as MessagePack extension.
+    pub fn serialize<S>(value: &std::time::SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let ts =
+            messagepack_core::timestamp::Timestamp::try_from(*value).map_err(ser::Error::custom)?;
+        super::timestamp::serialize(&ts, serializer)
+    }
+
+    /// Deserialize `SystemTime` from MessagePack extension.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<std::time::SystemTime, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ts = super::timestamp::deserialize(deserializer)?;
+        ts.try_into()
+            .map_err(|_| de::Error::custom("timestamp out of range"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messagepack_core::timestamp::{Timestamp, Timestamp32, Timestamp64, Timestamp96};
+
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct WrapTs32(#[serde(with = "timestamp32")] Timestamp32);
+
+    #[rstest]
+    fn encode_timestamp32() {
+        let ts = WrapTs32(Timestamp32::new(123456));
+        let mut buf = [0u8; 16];
+        let n = crate::to_slice(&ts, &mut buf).unwrap();
+
+        let mut expected = vec![0xd6, (-1i8 as u8)];
+        expected.extend_from_slice(&123456u32.to_be_bytes());
+        assert_eq!(&buf[..n], expected.as_slice());
+    }
+
+    #[rstest]
+    fn decode_timestamp32() {
+        let mut buf = vec![0xd6, (-1i8 as u8)];
+        buf.extend_from_slice(&0u32.to_be_bytes());
+        let v = crate::from_slice::<WrapTs32>(&buf).unwrap();
+        assert_eq!(v.0.seconds(), 0);
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct WrapTs64(#[serde(with = "timestamp64")] Timestamp64);
+
+    #[rstest]
+    fn encode_timestamp64() {
+        let ts = WrapTs64(Timestamp64::new(123456, 789).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&ts, &mut buf).unwrap();
+
+        let mut expected = vec![0xd7, (-1i8 as u8)];
+        let data = ((789u64 << 34) | 123456).to_be_bytes();
+        expected.extend_from_slice(&data);
+        assert_eq!(&buf[..n], expected.as_slice());
+    }
+
+    #[rstest]
+    fn decode_timestamp64() {
+        let mut buf = vec![0xd7, (-1i8 as u8)];
+        let data = ((789u64 << 34) | 123456).to_be_bytes();
+        buf.extend_from_slice(&data);
+        let v = crate::from_slice::<WrapTs64>(&buf).unwrap();
+        assert_eq!(v.0.seconds(), 123456);
+        assert_eq!(v.0.nanos(), 789);
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct WrapTs96(#[serde(with = "timestamp96")] Timestamp96);
+
+    #[rstest]
+    fn encode_timestamp96() {
+        let ts = WrapTs96(Timestamp96::new(123456, 789).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&ts, &mut buf).unwrap();
+
+        let mut expected = vec![0xc7, 12, (-1i8 as u8)];
+        expected.extend_from_slice(&789u32.to_be_bytes());
+        expected.extend_from_slice(&123456u64.to_be_bytes());
+        assert_eq!(&buf[..n], expected.as_slice());
+    }
+
+    #[rstest]
+    fn decode_timestamp96() {
+        let mut buf = vec![0xc7, 12, (-1i8 as u8)];
+        buf.extend_from_slice(&789u32.to_be_bytes());
+        buf.extend_from_slice(&123456u64.to_be_bytes());
+        let v = crate::from_slice::<WrapTs96>(&buf).unwrap();
+        assert_eq!(v.0.seconds(), 123456);
+        assert_eq!(v.0.nanos(), 789);
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct WrapTs(#[serde(with = "timestamp")] Timestamp);
+
+    #[rstest]
+    #[case(123456, 0, &[0xd6, 0xff, 0x00, 0x01, 0xe2, 0x40])]
+    #[case(123456, 789, &[0xd7, 0xff, 0x00, 0x00, 0x0c, 0x54, 0x00, 0x01, 0xe2, 0x40])]
+    #[case(-1, 0, &[0xc7, 12, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])]
+    fn timestamp_round_trips_through_smallest_layout(
+        #[case] seconds: i64,
+        #[case] nanos: u32,
+        #[case] expected: &[u8],
+    ) {
+        let ts = WrapTs(Timestamp::new(seconds, nanos).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&ts, &mut buf).unwrap();
+        assert_eq!(&buf[..n], expected);
+
+        let back = crate::from_slice::<WrapTs>(expected).unwrap();
+        assert_eq!(back, ts);
+    }
+
+    #[rstest]
+    fn timestamp_rejects_other_ext() {
+        let buf = [0xd6, 0x05, 0, 0, 0, 0];
+        assert!(crate::from_slice::<WrapTs>(&buf).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[rstest]
+    fn chrono_keeps_pre_epoch_nanos() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrap(#[serde(with = "super::chrono")] ::chrono::DateTime<::chrono::Utc>);
+
+        let v = Wrap(::chrono::DateTime::from_timestamp(-2_208_988_800, 5).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&v, &mut buf).unwrap();
+        let expected = WrapTs(Timestamp::new(-2_208_988_800, 5).unwrap());
+        let mut expected_buf = [0u8; 32];
+        let expected_n = crate::to_slice(&expected, &mut expected_buf).unwrap();
+        assert_eq!(&buf[..n], &expected_buf[..expected_n]);
+        assert_eq!(crate::from_slice::<Wrap>(&buf[..n]).unwrap(), v);
+    }
+
+    #[cfg(feature = "time")]
+    #[rstest]
+    fn time_keeps_pre_epoch_nanos() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrap(#[serde(with = "super::time")] ::time::OffsetDateTime);
+
+        let v = Wrap(::time::OffsetDateTime::from_unix_timestamp_nanos(-1_500_000_000).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&v, &mut buf).unwrap();
+        let back = crate::from_slice::<WrapTs>(&buf[..n]).unwrap();
+        assert_eq!(back.0, Timestamp::new(-2, 500_000_000).unwrap());
+        assert_eq!(crate::from_slice::<Wrap>(&buf[..n]).unwrap(), v);
+    }
+
+    #[cfg(feature = "jiff")]
+    #[rstest]
+    fn jiff_keeps_pre_epoch_nanos() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrap(#[serde(with = "super::jiff")] ::jiff::Timestamp);
+
+        let v = Wrap(::jiff::Timestamp::new(-1, -500_000_000).unwrap());
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&v, &mut buf).unwrap();
+        let back = crate::from_slice::<WrapTs>(&buf[..n]).unwrap();
+        assert_eq!(back.0, Timestamp::new(-2, 500_000_000).unwrap());
+        assert_eq!(crate::from_slice::<Wrap>(&buf[..n]).unwrap(), v);
+
+        let far = WrapTs(Timestamp::new(i64::MAX, 0).unwrap());
+        let n = crate::to_slice(&far, &mut buf).unwrap();
+        assert!(crate::from_slice::<Wrap>(&buf[..n]).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[rstest]
+    #[case(1_700_000_000, 0, Timestamp::new(1_700_000_000, 0).unwrap())]
+    #[case(-1, 500_000_000, Timestamp::new(-2, 500_000_000).unwrap())]
+    fn system_time_matches_timestamp(
+        #[case] seconds: i64,
+        #[case] nanos: u32,
+        #[case] expected: Timestamp,
+    ) {
+        use std::time::{Duration, SystemTime};
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrap(#[serde(with = "super::system_time")] SystemTime);
+
+        let offset = Duration::new(seconds.unsigned_abs(), nanos);
+        let time = if seconds < 0 {
+            SystemTime::UNIX_EPOCH - offset
+        } else {
+            SystemTime::UNIX_EPOCH + offset
+        };
+        let v = Wrap(time);
+        let mut buf = [0u8; 32];
+        let n = crate::to_slice(&v, &mut buf).unwrap();
+        let back = crate::from_slice::<WrapTs>(&buf[..n]).unwrap();
+        assert_eq!(back.0, expected);
+        assert_eq!(crate::from_slice::<Wrap>(&buf[..n]).unwrap(), v);
+    }
+}
@@ -0,0 +1,157 @@
+/// De/Serialize a type implementing [messagepack_core::extension::Extension]
+///
+/// The payload is produced by `Extension::encode_payload` and tagged with
+/// `Extension::TYPE`. Deserializing an extension with another type code
+/// fails with an error naming the type code found.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde::{Serialize,Deserialize};
+/// use messagepack_core::{
+///     encode,
+///     extension::{Extension, TryFromExtensionError},
+///     io::IoWrite,
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Uuid([u8; 16]);
+///
+/// impl Extension for Uuid {
+///     const TYPE: i8 = 2;
+///
+///     fn encode_payload<W: IoWrite>(&self, writer: &mut W) -> Result<(), encode::Error<W::Error>> {
+///         writer.write(&self.0)?;
+///         Ok(())
+///     }
+///
+///     fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+///         data.try_into().map(Uuid).map_err(|_| TryFromExtensionError::InvalidData)
+///     }
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// #[serde(transparent)]
+/// struct WrapUuid(
+///     #[serde(with = "messagepack_serde::extension::ext_typed")] Uuid,
+/// );
+///
+/// # fn main() {
+///
+/// let id = WrapUuid(Uuid([7; 16]));
+/// let mut buf = [0u8; 18];
+/// messagepack_serde::to_slice(&id, &mut buf).unwrap();
+/// assert_eq!(buf[..2], [0xd8, 2]);
+///
+/// let result = messagepack_serde::from_slice::<WrapUuid>(&buf).unwrap();
+/// assert_eq!(id, result);
+///
+/// # }
+/// ```
+pub mod ext_typed {
+    use crate::extension::{ext_owned, ext_ref};
+    use messagepack_core::extension::{Extension, ExtensionRef};
+    use serde::{de, ser};
+
+    /// Serialize [messagepack_core::extension::Extension]
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Extension,
+        S: serde::Serializer,
+    {
+        let mut payload = alloc::vec::Vec::new();
+        value
+            .encode_payload(&mut payload)
+            .map_err(|_| ser::Error::custom("cannot encode extension payload"))?;
+        ext_ref::serialize(&ExtensionRef::new(T::TYPE, &payload), serializer)
+    }
+
+    /// Deserialize [messagepack_core::extension::Extension]
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Extension,
+        D: serde::Deserializer<'de>,
+    {
+        let ext = ext_owned::deserialize(deserializer)?;
+        T::from_extension(ext.as_ref()).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messagepack_core::{
+        encode,
+        extension::{Extension, TryFromExtensionError},
+        io::IoWrite,
+    };
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq)]
+    struct Decimal {
+        mantissa: i64,
+        scale: u8,
+    }
+
+    impl Extension for Decimal {
+        const TYPE: i8 = 3;
+
+        fn encode_payload<W: IoWrite>(
+            &self,
+            writer: &mut W,
+        ) -> Result<(), encode::Error<W::Error>> {
+            writer.write(&[self.scale])?;
+            writer.write(&self.mantissa.to_be_bytes())?;
+            Ok(())
+        }
+
+        fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+            let (&scale, mantissa) = data
+                .split_first()
+                .ok_or(TryFromExtensionError::InvalidData)?;
+            let mantissa = mantissa
+                .try_into()
+                .map_err(|_| TryFromExtensionError::InvalidData)?;
+            Ok(Decimal {
+                mantissa: i64::from_be_bytes(mantissa),
+                scale,
+            })
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Price {
+        #[serde(with = "ext_typed")]
+        amount: Decimal,
+    }
+
+    #[rstest]
+    fn round_trip() {
+        let price = Price {
+            amount: Decimal {
+                mantissa: -1234,
+                scale: 2,
+            },
+        };
+        let buf = crate::to_vec(&price).unwrap();
+
+        let mut expected = vec![0x81, 0xa6];
+        expected.extend_from_slice(b"amount");
+        expected.extend_from_slice(&[0xc7, 9, 3, 2]);
+        expected.extend_from_slice(&(-1234i64).to_be_bytes());
+        assert_eq!(buf, expected);
+
+        assert_eq!(crate::from_slice::<Price>(&buf).unwrap(), price);
+    }
+
+    #[rstest]
+    fn mismatched_type_names_found_type() {
+        let mut buf = vec![0x81, 0xa6];
+        buf.extend_from_slice(b"amount");
+        buf.extend_from_slice(&[0xd4, 5, 0]);
+
+        let err = crate::from_slice::<Price>(&buf).unwrap_err();
+        assert!(err.to_string().contains("unexpected extension type 5"));
+    }
+}
@@ -26,3 +26,5 @@ pub mod value;
 pub use value::{Value, ValueRef, to_value};
 
 pub mod extension;
+
+pub mod transcode;
@@ -318,7 +318,8 @@ where
         T: ?Sized + ser::Serialize,
     {
         match name {
-            crate::extension::EXTENSION_STRUCT_NAME => {
+            // Both are written as raw bytes by the extension serializer
+            crate::extension::EXTENSION_STRUCT_NAME | crate::value::RAW_VALUE_STRUCT_NAME => {
                 let mut ser = crate::extension::ser::SerializeExt::new(self.writer);
                 value.serialize(&mut ser)?;
                 self.current_length += ser.length();
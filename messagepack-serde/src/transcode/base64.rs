@@ -0,0 +1,95 @@
+//! Standard base64 alphabet with padding, as in RFC 4648.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode `input` as padded base64, passing the text to `emit` in pieces of
+/// at most 64 bytes.
+pub(crate) fn encode<E>(
+    input: &[u8],
+    mut emit: impl FnMut(&[u8]) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut buf = [0u8; 64];
+    for block in input.chunks(48) {
+        let mut len = 0;
+        for chunk in block.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+            let encoded = &mut buf[len..len + 4];
+            encoded.fill(b'=');
+            for (i, c) in encoded.iter_mut().take(chunk.len() + 1).enumerate() {
+                *c = ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f];
+            }
+            len += 4;
+        }
+        emit(&buf[..len])?;
+    }
+    Ok(())
+}
+
+/// Decode padded base64 whose padding bits are zero, or `None` if `s` is not
+/// exactly that.
+#[cfg(feature = "json")]
+pub(crate) fn decode(s: &str) -> Option<alloc::vec::Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(v.into())
+    }
+
+    let b = s.as_bytes();
+    if !b.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = alloc::vec::Vec::with_capacity(b.len() / 4 * 3);
+    let last = b.len() / 4;
+    for (i, chunk) in b.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != last) {
+            return None;
+        }
+        let mut n = 0;
+        for (j, &c) in chunk[..4 - padding].iter().enumerate() {
+            n |= sextet(c)? << (18 - 6 * j);
+        }
+        let bytes = n.to_be_bytes();
+        // The bits below the last decoded byte must be zero, so each byte
+        // string has exactly one encoding.
+        if bytes[4 - padding..].iter().any(|&b| b != 0) {
+            return None;
+        }
+        out.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Some(out)
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", Some(&b""[..]))]
+    #[case("Zg==", Some(&b"f"[..]))]
+    #[case("Zm8=", Some(&b"fo"[..]))]
+    #[case("Zm9vYmFy", Some(&b"foobar"[..]))]
+    #[case("AP8=", Some(&[0x00, 0xff][..]))]
+    #[case("Zg", None)]
+    #[case("Zh==", None)]
+    #[case("Zm9=", None)]
+    #[case("Zg=a", None)]
+    #[case("Z===", None)]
+    #[case("Zg==Zg==", None)]
+    #[case("Zm9v YmFy", None)]
+    #[case("hello!!!", None)]
+    fn decode_padded(#[case] s: &str, #[case] expected: Option<&[u8]>) {
+        assert_eq!(decode(s).as_deref(), expected);
+    }
+}
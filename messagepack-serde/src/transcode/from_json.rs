@@ -0,0 +1,600 @@
+use alloc::{string::String, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+use messagepack_core::{io::IoWrite, timestamp::Timestamp};
+use serde::{
+    Deserializer, Serialize, Serializer,
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq},
+};
+
+use super::{base64, rfc3339};
+use crate::{
+    extension::timestamp,
+    ser::{LosslessMinimize, NumEncoder},
+    value::index::{parse_index, pointer_tokens},
+};
+
+/// How strings selected by a rule of [`FromJsonConfig`] are converted.
+///
+/// Strings which do not match the expected pattern are kept as str.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringConversion {
+    /// Padded standard base64 with zero padding bits becomes bin.
+    Base64,
+    /// RFC 3339 date-times become the smallest timestamp ext which holds them.
+    Rfc3339,
+}
+
+/// Options for [`from_json_reader`].
+///
+/// `C` is the [`NumEncoder`] used for numbers, [`LosslessMinimize`] by default.
+#[derive(Debug, Clone)]
+pub struct FromJsonConfig<C = LosslessMinimize> {
+    num_encoder: C,
+    rules: Vec<(Pattern, StringConversion)>,
+}
+
+impl FromJsonConfig {
+    /// Minimize numbers losslessly and keep every string as str.
+    pub fn new() -> Self {
+        Self {
+            num_encoder: LosslessMinimize,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Default for FromJsonConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> FromJsonConfig<C> {
+    /// Encode numbers with `num_encoder`.
+    pub fn with_num_encoder<N>(self, num_encoder: N) -> FromJsonConfig<N> {
+        FromJsonConfig {
+            num_encoder,
+            rules: self.rules,
+        }
+    }
+
+    /// Convert strings found at `path`.
+    ///
+    /// `path` is a JSON Pointer such as `/users/0/avatar` in which a `*`
+    /// segment matches any key or index, e.g. `/users/*/avatar`. The first
+    /// matching rule applies.
+    ///
+    /// Fails if `path` is neither empty nor starts with `/`.
+    pub fn with_rule(
+        mut self,
+        path: &str,
+        conversion: StringConversion,
+    ) -> Result<Self, InvalidRulePath> {
+        let pattern = Pattern::new(path).ok_or_else(|| InvalidRulePath(path.into()))?;
+        self.rules.push((pattern, conversion));
+        Ok(self)
+    }
+}
+
+/// Error returned by [`FromJsonConfig::with_rule`] for a path which is not a
+/// JSON Pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRulePath(String);
+
+impl fmt::Display for InvalidRulePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rule path {:?} is not a JSON Pointer", self.0)
+    }
+}
+
+impl core::error::Error for InvalidRulePath {}
+
+/// Error returned by [`from_json_reader`].
+#[derive(Debug)]
+pub enum FromJsonError<W> {
+    /// The input is not valid JSON or could not be read.
+    Input(serde_json::Error),
+    /// The MessagePack could not be written.
+    Output(crate::ser::Error<W>),
+}
+
+impl<W> fmt::Display for FromJsonError<W>
+where
+    W: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromJsonError::Input(e) => e.fmt(f),
+            FromJsonError::Output(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<W> core::error::Error for FromJsonError<W>
+where
+    W: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            FromJsonError::Input(e) => Some(e),
+            FromJsonError::Output(e) => Some(e),
+        }
+    }
+}
+
+/// Convert one JSON document read from `reader` to MessagePack written into
+/// `writer`, returning the number of bytes written.
+///
+/// Tokens are passed from `serde_json` straight to the MessagePack serializer,
+/// so no tree of the document is built. JSON does not announce the length of
+/// arrays and objects, so each of them is buffered until it is complete, as
+/// the serializer does for any unknown-length container.
+///
+/// As with [`serde_json::from_reader`], only whitespace may follow the
+/// document.
+///
+/// ```rust
+/// use messagepack_serde::transcode::{FromJsonConfig, StringConversion, from_json_reader};
+///
+/// let json = br#"{"id": 1, "blob": "AP8=", "at": "1970-01-01T00:00:00Z", "name": "AP8="}"#;
+/// let config = FromJsonConfig::new()
+///     .with_rule("/blob", StringConversion::Base64)
+///     .unwrap()
+///     .with_rule("/at", StringConversion::Rfc3339)
+///     .unwrap();
+///
+/// let mut buf = Vec::new();
+/// from_json_reader(&json[..], &mut buf, config).unwrap();
+/// assert_eq!(
+///     buf,
+///     [
+///         &[0x84, 0xa2][..], b"id", &[0x01, 0xa4], b"blob", &[0xc4, 0x02, 0x00, 0xff],
+///         &[0xa2], b"at", &[0xd6, 0xff, 0, 0, 0, 0],
+///         &[0xa4], b"name", &[0xa4], b"AP8=",
+///     ]
+///     .concat()
+/// );
+/// ```
+pub fn from_json_reader<R, W, C>(
+    reader: R,
+    writer: &mut W,
+    config: FromJsonConfig<C>,
+) -> Result<usize, FromJsonError<W::Error>>
+where
+    R: std::io::Read,
+    W: IoWrite,
+    C: NumEncoder<W>,
+{
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    let state = State {
+        rules: &config.rules,
+        path: RefCell::new(Vec::new()),
+    };
+
+    let document = Transcode::new(&mut de, &state);
+    let written = crate::ser::to_core_writer_with_config(&document, writer, config.num_encoder)
+        .map_err(|e| match document.error.into_inner() {
+            Some(e) => FromJsonError::Input(e),
+            None => FromJsonError::Output(e),
+        })?;
+    de.end().map_err(FromJsonError::Input)?;
+    Ok(written)
+}
+
+/// JSON Pointer in which `None` segments match anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern(Vec<Option<String>>);
+
+impl Pattern {
+    fn new(path: &str) -> Option<Self> {
+        let segments = pointer_tokens(path)?
+            .map(|token| (token != "*").then(|| token.into_owned()))
+            .collect();
+        Some(Self(segments))
+    }
+
+    fn matches(&self, path: &[Segment]) -> bool {
+        self.0.len() == path.len()
+            && self
+                .0
+                .iter()
+                .zip(path)
+                .all(|(pattern, segment)| match (pattern, segment) {
+                    (None, _) => true,
+                    (Some(p), Segment::Key(k)) => p == k,
+                    (Some(p), Segment::Index(i)) => parse_index(p) == Some(*i),
+                })
+    }
+}
+
+#[derive(Debug)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Shared by every nested [`Transcode`] of one document.
+struct State<'s> {
+    rules: &'s [(Pattern, StringConversion)],
+    path: RefCell<Vec<Segment>>,
+}
+
+impl State<'_> {
+    fn conversion(&self) -> Option<StringConversion> {
+        let path = self.path.borrow();
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.matches(&path))
+            .map(|(_, conversion)| *conversion)
+    }
+}
+
+/// Serializes whatever the wrapped deserializer yields.
+///
+/// Serializer errors travel up as usual. An error of the deserializer is kept
+/// in `error` so the caller, which knows the deserializer type, can return it
+/// unchanged while the serializer only sees a placeholder.
+struct Transcode<'s, D, E> {
+    de: RefCell<Option<D>>,
+    error: RefCell<Option<E>>,
+    state: &'s State<'s>,
+}
+
+impl<'s, D, E> Transcode<'s, D, E> {
+    fn new(de: D, state: &'s State<'s>) -> Self {
+        Self {
+            de: RefCell::new(Some(de)),
+            error: RefCell::new(None),
+            state,
+        }
+    }
+}
+
+impl<'de, D> Serialize for Transcode<'_, D, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let de = self
+            .de
+            .borrow_mut()
+            .take()
+            .expect("a value is transcoded only once");
+        match de.deserialize_any(Forward {
+            serializer,
+            state: self.state,
+        }) {
+            Ok(result) => result,
+            Err(e) => {
+                *self.error.borrow_mut() = Some(e);
+                Err(ser::Error::custom("invalid JSON input"))
+            }
+        }
+    }
+}
+
+/// Visitor passing every JSON token to `serializer`.
+///
+/// The serializer's result is returned as the visited value so its errors
+/// keep their type instead of being turned into deserializer errors.
+struct Forward<'s, S> {
+    serializer: S,
+    state: &'s State<'s>,
+}
+
+impl<'de, 's, S: Serializer> Visitor<'de> for Forward<'s, S> {
+    type Value = Result<S::Ok, S::Error>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(self.serializer.serialize_unit())
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(self.serializer.serialize_bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(self.serializer.serialize_i64(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(self.serializer.serialize_u64(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(self.serializer.serialize_f64(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let serialized = match self.state.conversion() {
+            Some(StringConversion::Base64) => match base64::decode(v) {
+                Some(bytes) => self.serializer.serialize_bytes(&bytes),
+                None => self.serializer.serialize_str(v),
+            },
+            Some(StringConversion::Rfc3339) => match rfc3339::parse(v) {
+                Some((seconds, nanos)) => serialize_timestamp(seconds, nanos, self.serializer),
+                None => self.serializer.serialize_str(v),
+            },
+            None => self.serializer.serialize_str(v),
+        };
+        Ok(serialized)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = match self.serializer.serialize_seq(seq.size_hint()) {
+            Ok(out) => out,
+            Err(e) => return drain_seq(seq, e),
+        };
+        let mut index = 0;
+        loop {
+            self.state.path.borrow_mut().push(Segment::Index(index));
+            let element = seq.next_element_seed(Element {
+                out: &mut out,
+                state: self.state,
+            });
+            self.state.path.borrow_mut().pop();
+            match element? {
+                Some(Ok(())) => index += 1,
+                Some(Err(e)) => return drain_seq(seq, e),
+                None => return Ok(out.end()),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut out = match self.serializer.serialize_map(map.size_hint()) {
+            Ok(out) => out,
+            Err(e) => return drain_map(map, e),
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            if let Err(e) = out.serialize_key(&key) {
+                map.next_value::<IgnoredAny>()?;
+                return drain_map(map, e);
+            }
+            self.state.path.borrow_mut().push(Segment::Key(key));
+            let value = map.next_value_seed(Value {
+                out: &mut out,
+                state: self.state,
+            });
+            self.state.path.borrow_mut().pop();
+            if let Err(e) = value? {
+                return drain_map(map, e);
+            }
+        }
+        Ok(out.end())
+    }
+}
+
+/// Consume the rest of a sequence after the serializer failed, so the
+/// deserializer does not report the unread elements as an error instead.
+fn drain_seq<'de, A: SeqAccess<'de>, T, E>(mut seq: A, e: E) -> Result<Result<T, E>, A::Error> {
+    while seq.next_element::<IgnoredAny>()?.is_some() {}
+    Ok(Err(e))
+}
+
+/// Same as [`drain_seq`] for maps.
+fn drain_map<'de, A: MapAccess<'de>, T, E>(mut map: A, e: E) -> Result<Result<T, E>, A::Error> {
+    while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+    Ok(Err(e))
+}
+
+struct Element<'a, 's, S> {
+    out: &'a mut S,
+    state: &'s State<'s>,
+}
+
+impl<'de, S: SerializeSeq> DeserializeSeed<'de> for Element<'_, '_, S> {
+    type Value = Result<(), S::Error>;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        transcode_into(de, self.state, |value| self.out.serialize_element(value))
+    }
+}
+
+struct Value<'a, 's, S> {
+    out: &'a mut S,
+    state: &'s State<'s>,
+}
+
+impl<'de, S: SerializeMap> DeserializeSeed<'de> for Value<'_, '_, S> {
+    type Value = Result<(), S::Error>;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        transcode_into(de, self.state, |value| self.out.serialize_value(value))
+    }
+}
+
+/// Serialize the value of `de` with `serialize`, returning an error of `de`
+/// through the deserializer and anything else through the serializer.
+fn transcode_into<'de, D, E>(
+    de: D,
+    state: &State<'_>,
+    serialize: impl FnOnce(&Transcode<'_, D, D::Error>) -> Result<(), E>,
+) -> Result<Result<(), E>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Transcode::new(de, state);
+    let result = serialize(&value);
+    match value.error.into_inner() {
+        Some(e) => Err(e),
+        None => Ok(result),
+    }
+}
+
+fn serialize_timestamp<S: Serializer>(
+    seconds: i64,
+    nanos: u32,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let ts = Timestamp::new(seconds, nanos).map_err(ser::Error::custom)?;
+    timestamp::serialize(&ts, serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ser::{AggressiveMinimize, Exact};
+    use messagepack_core::io::SliceWriter;
+    use rstest::rstest;
+
+    fn convert<C>(json: &str, config: FromJsonConfig<C>) -> Vec<u8>
+    where
+        C: NumEncoder<Vec<u8>>,
+    {
+        let mut buf = Vec::new();
+        let written = from_json_reader(json.as_bytes(), &mut buf, config).unwrap();
+        assert_eq!(written, buf.len());
+        buf
+    }
+
+    #[rstest]
+    #[case("null", &[0xc0])]
+    #[case("true", &[0xc3])]
+    #[case("1", &[0x01])]
+    #[case("-1", &[0xff])]
+    #[case("300", &[0xcd, 0x01, 0x2c])]
+    #[case("18446744073709551615", &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])]
+    #[case("1.5", &[0xca, 0x3f, 0xc0, 0x00, 0x00])]
+    #[case("0.1", &[0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a])]
+    #[case(r#""a""#, &[0xa1, b'a'])]
+    #[case(r#"[1, [], {}]"#, &[0x93, 0x01, 0x90, 0x80])]
+    #[case(r#" {"a": {"b": null}} "#, &[0x81, 0xa1, b'a', 0x81, 0xa1, b'b', 0xc0])]
+    fn lossless_minimize(#[case] json: &str, #[case] expected: &[u8]) {
+        assert_eq!(convert(json, FromJsonConfig::new()), expected);
+    }
+
+    #[test]
+    fn num_encoder() {
+        let exact = FromJsonConfig::new().with_num_encoder(Exact);
+        assert_eq!(convert("1", exact), [0xcf, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let aggressive = FromJsonConfig::new().with_num_encoder(AggressiveMinimize);
+        assert_eq!(
+            convert("[2.0, 1.5]", aggressive),
+            [0x92, 0x02, 0xca, 0x3f, 0xc0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn rules_follow_paths() {
+        let json = r#"{"users": [{"avatar": "AP8="}, {"avatar": "not base64"}], "avatar": "AP8="}"#;
+        let config = FromJsonConfig::new()
+            .with_rule("/users/*/avatar", StringConversion::Base64)
+            .unwrap();
+        let expected = [
+            &[0x82, 0xa5][..],
+            b"users",
+            &[0x92, 0x81, 0xa6],
+            b"avatar",
+            &[0xc4, 0x02, 0x00, 0xff, 0x81, 0xa6],
+            b"avatar",
+            &[0xaa],
+            b"not base64",
+            &[0xa6],
+            b"avatar",
+            &[0xa4],
+            b"AP8=",
+        ]
+        .concat();
+        assert_eq!(convert(json, config), expected);
+    }
+
+    #[rstest]
+    #[case("", r#""AP8=""#, &[0xc4, 0x02, 0x00, 0xff])]
+    #[case("/1", r#"["AP8=", "AP8="]"#, &[0x92, 0xa4, b'A', b'P', b'8', b'=', 0xc4, 0x02, 0x00, 0xff])]
+    #[case("/a~1b", r#"{"a/b": "AP8="}"#, &[0x81, 0xa3, b'a', b'/', b'b', 0xc4, 0x02, 0x00, 0xff])]
+    #[case("/*", r#"{"k": "AP8="}"#, &[0x81, 0xa1, b'k', 0xc4, 0x02, 0x00, 0xff])]
+    #[case("/x", r#"{"k": "AP8="}"#, &[0x81, 0xa1, b'k', 0xa4, b'A', b'P', b'8', b'='])]
+    #[case("/01", r#"["x", "AP8="]"#, &[0x92, 0xa1, b'x', 0xa4, b'A', b'P', b'8', b'='])]
+    #[case("/+1", r#"["x", "AP8="]"#, &[0x92, 0xa1, b'x', 0xa4, b'A', b'P', b'8', b'='])]
+    fn pointer_syntax(#[case] path: &str, #[case] json: &str, #[case] expected: &[u8]) {
+        let config = FromJsonConfig::new()
+            .with_rule(path, StringConversion::Base64)
+            .unwrap();
+        assert_eq!(convert(json, config), expected);
+    }
+
+    #[rstest]
+    #[case("blob")]
+    #[case("*/avatar")]
+    fn rule_path_must_be_pointer(#[case] path: &str) {
+        let result = FromJsonConfig::new().with_rule(path, StringConversion::Base64);
+        assert_eq!(result.err(), Some(InvalidRulePath(path.into())));
+    }
+
+    #[rstest]
+    #[case(r#""1970-01-01T00:00:00Z""#, &[0xd6, 0xff, 0, 0, 0, 0])]
+    #[case(
+        r#""2023-11-14T22:13:20.5Z""#,
+        &[0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x65, 0x53, 0xf1, 0x00]
+    )]
+    #[case(
+        r#""1969-12-31T23:59:59.999999999Z""#,
+        &[0xc7, 0x0c, 0xff, 0x3b, 0x9a, 0xc9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    )]
+    #[case(r#""yesterday""#, &[0xa9, b'y', b'e', b's', b't', b'e', b'r', b'd', b'a', b'y'])]
+    fn rfc3339_timestamps(#[case] json: &str, #[case] expected: &[u8]) {
+        let config = FromJsonConfig::new()
+            .with_rule("", StringConversion::Rfc3339)
+            .unwrap();
+        assert_eq!(convert(json, config), expected);
+    }
+
+    #[test]
+    fn round_trip_through_to_json() {
+        let json = r#"{"a":[1,-2,0.5,"x",null,true],"bin":"AP8=","at":"2023-11-14T22:13:20.5Z"}"#;
+        let config = FromJsonConfig::new()
+            .with_rule("/bin", StringConversion::Base64)
+            .unwrap()
+            .with_rule("/at", StringConversion::Rfc3339)
+            .unwrap();
+        let msgpack = convert(json, config);
+
+        let mut back = Vec::new();
+        let mut reader = messagepack_core::io::SliceReader::new(&msgpack);
+        super::super::to_json(&mut reader, &mut back, super::super::ToJsonConfig::new()).unwrap();
+        assert_eq!(String::from_utf8(back).unwrap(), json);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("[1,")]
+    #[case("[1, 2] 3")]
+    #[case(r#"{"a": [1, {"b": tru}]}"#)]
+    fn invalid_json(#[case] json: &str) {
+        let mut buf = Vec::new();
+        let result = from_json_reader(json.as_bytes(), &mut buf, FromJsonConfig::new());
+        assert!(matches!(result, Err(FromJsonError::Input(_))), "{result:?}");
+    }
+
+    #[test]
+    fn nested_syntax_error_keeps_its_position() {
+        let mut buf = Vec::new();
+        let json = "[[1],\n [2, x]]";
+        let Err(FromJsonError::Input(e)) =
+            from_json_reader(json.as_bytes(), &mut buf, FromJsonConfig::new())
+        else {
+            panic!("expected a syntax error");
+        };
+        assert!(e.is_syntax());
+        assert_eq!((e.line(), e.column()), (2, 6));
+    }
+
+    #[test]
+    fn output_error() {
+        let mut buf = [0u8; 4];
+        let mut writer = SliceWriter::new(&mut buf);
+        let result = from_json_reader(&b"[1, 2, 3, 4, 5]"[..], &mut writer, FromJsonConfig::new());
+        assert!(
+            matches!(result, Err(FromJsonError::Output(_))),
+            "{result:?}"
+        );
+    }
+}
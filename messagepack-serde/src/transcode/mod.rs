@@ -0,0 +1,41 @@
+//! Streaming conversion between MessagePack and JSON.
+//!
+//! The transcoders work token by token and never build a tree of the whole
+//! document, so memory use is bounded by the largest single string or bin
+//! payload rather than the size of the input.
+//!
+//! JSON cannot express every MessagePack type. [`ToJsonConfig`] decides how
+//! bin, ext, timestamps, non-string map keys and non-finite floats are written.
+//!
+//! ```rust
+//! use messagepack_core::io::SliceReader;
+//! use messagepack_serde::transcode::{BinPolicy, ToJsonConfig, to_json};
+//!
+//! // {"id": 1, "blob": h'00ff', 2: nil}
+//! let buf: &[u8] = &[0x83, 0xa2, b'i', b'd', 0x01, 0xa4, b'b', b'l', b'o', b'b', 0xc4, 0x02, 0x00, 0xff, 0x02, 0xc0];
+//!
+//! let mut json = [0u8; 64];
+//! let mut out = json.as_mut_slice();
+//! let config = ToJsonConfig::new().with_bin(BinPolicy::Hex);
+//! to_json(&mut SliceReader::new(buf), &mut out, config).unwrap();
+//!
+//! let written = 64 - out.len();
+//! assert_eq!(&json[..written], br#"{"id":1,"blob":"00ff","2":null}"#);
+//! ```
+
+pub(crate) mod base64;
+#[cfg(feature = "json")]
+mod from_json;
+pub(crate) mod rfc3339;
+mod to_json;
+
+#[cfg(feature = "json")]
+pub use from_json::{
+    FromJsonConfig, FromJsonError, InvalidRulePath, StringConversion, from_json_reader,
+};
+#[cfg(feature = "json")]
+pub(crate) use to_json::HEX;
+pub use to_json::{
+    BinPolicy, ExtPolicy, KeyPolicy, NonFinitePolicy, TimestampPolicy, ToJsonConfig, ToJsonError,
+    to_json,
+};
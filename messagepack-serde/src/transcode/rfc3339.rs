@@ -0,0 +1,211 @@
+//! RFC 3339 date-times in UTC, limited to the years 0 to 9999.
+//!
+//! Calendar arithmetic follows `civil_from_days` and `days_from_civil` from
+//! <http://howardhinnant.github.io/date_algorithms.html>.
+
+use core::fmt;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// UTC date-time printed with the shortest fraction, e.g.
+/// `2023-11-14T22:13:20.5Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DateTime {
+    fields: (i64, u32, u32, u32, u32, u32),
+    nanos: u32,
+}
+
+impl DateTime {
+    /// `None` outside the years 0 to 9999.
+    pub(crate) fn new(seconds: i64, nanos: u32) -> Option<Self> {
+        Some(Self {
+            fields: split(seconds)?,
+            nanos,
+        })
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day, hour, minute, second) = self.fields;
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}"
+        )?;
+        if self.nanos > 0 {
+            let mut fraction = self.nanos;
+            let mut width = 9;
+            while fraction.is_multiple_of(10) {
+                fraction /= 10;
+                width -= 1;
+            }
+            write!(f, ".{fraction:0width$}")?;
+        }
+        f.write_str("Z")
+    }
+}
+
+/// Split Unix `seconds` into UTC year, month, day, hour, minute and second,
+/// or `None` outside the years 0 to 9999.
+fn split(seconds: i64) -> Option<(i64, u32, u32, u32, u32, u32)> {
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let time = seconds.rem_euclid(SECONDS_PER_DAY) as u32;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (0..=9999)
+        .contains(&year)
+        .then_some((year, month, day, time / 3600, time / 60 % 60, time % 60))
+}
+
+/// Parse a date-time such as `2023-11-14T22:13:20.5+09:00` into Unix seconds
+/// and nanoseconds.
+///
+/// Fractions beyond nanoseconds are truncated. Leap seconds are rejected.
+#[cfg(feature = "json")]
+pub(crate) fn parse(s: &str) -> Option<(i64, u32)> {
+    let b = s.as_bytes();
+    let digits = |at: usize, len: usize| -> Option<u32> {
+        let field = b.get(at..at + len)?;
+        field.iter().try_fold(0u32, |n, &c| {
+            c.is_ascii_digit().then(|| n * 10 + u32::from(c - b'0'))
+        })
+    };
+    let separator = |at: usize, expected: &[u8]| b.get(at).is_some_and(|c| expected.contains(c));
+
+    let year = digits(0, 4)?;
+    let month = digits(5, 2)?;
+    let day = digits(8, 2)?;
+    let hour = digits(11, 2)?;
+    let minute = digits(14, 2)?;
+    let second = digits(17, 2)?;
+    if !(separator(4, b"-")
+        && separator(7, b"-")
+        && separator(10, b"Tt")
+        && separator(13, b":")
+        && separator(16, b":"))
+    {
+        return None;
+    }
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut at = 19;
+    let mut nanos = 0;
+    if separator(at, b".") {
+        at += 1;
+        let start = at;
+        while b.get(at).is_some_and(u8::is_ascii_digit) {
+            if at - start < 9 {
+                nanos = nanos * 10 + u32::from(b[at] - b'0');
+            }
+            at += 1;
+        }
+        let len = at - start;
+        if len == 0 {
+            return None;
+        }
+        if len < 9 {
+            nanos *= 10u32.pow((9 - len) as u32);
+        }
+    }
+
+    let offset = match &b[at..] {
+        b"Z" | b"z" => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let hours = digits(at + 1, 2)?;
+            let minutes = digits(at + 4, 2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = i64::from(hours * 3600 + minutes * 60);
+            if *sign == b'-' { -offset } else { offset }
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year.into(), month, day);
+    let time = i64::from(hour * 3600 + minute * 60 + second);
+    Some((days * SECONDS_PER_DAY + time - offset, nanos))
+}
+
+#[cfg(feature = "json")]
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(feature = "json")]
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = year - i64::from(month <= 2);
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1970-01-01T00:00:00Z", Some((0, 0)))]
+    #[case("2023-11-14T22:13:20.5Z", Some((1_700_000_000, 500_000_000)))]
+    #[case("2023-11-15t07:13:20+09:00", Some((1_700_000_000, 0)))]
+    #[case("2023-11-14T21:43:20-00:30", Some((1_700_000_000, 0)))]
+    #[case("1969-12-31T23:59:59.9999999999Z", Some((-1, 999_999_999)))]
+    #[case("0000-01-01T00:00:00Z", Some((-62_167_219_200, 0)))]
+    #[case("2000-02-29T00:00:00Z", Some((951_782_400, 0)))]
+    #[case("2001-02-29T00:00:00Z", None)]
+    #[case("2023-11-14T22:13:60Z", None)]
+    #[case("2023-11-14T22:13:20", None)]
+    #[case("2023-11-14T22:13:20.Z", None)]
+    #[case("2023-11-14 22:13:20Z", None)]
+    #[case("2023-11-14T22:13:20+0900", None)]
+    #[case("hello", None)]
+    fn parse_date_time(#[case] s: &str, #[case] expected: Option<(i64, u32)>) {
+        assert_eq!(parse(s), expected);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(-1)]
+    #[case(951_782_400)]
+    #[case(-62_167_219_200)]
+    #[case(253_402_300_799)]
+    fn format_and_parse_agree(#[case] seconds: i64) {
+        for nanos in [0, 1, 500_000_000, 999_999_999] {
+            let text = DateTime::new(seconds, nanos).unwrap().to_string();
+            assert_eq!(parse(&text), Some((seconds, nanos)), "{text}");
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(DateTime::new(-62_167_219_201, 0), None);
+        assert_eq!(DateTime::new(253_402_300_800, 0), None);
+    }
+}
@@ -0,0 +1,859 @@
+use core::fmt::{self, Write as _};
+
+use messagepack_core::{
+    Decode, Format,
+    decode::{
+        DecodeBorrowed, Error as DecodeError, NbyteReader, ReferenceDecoder, ReferenceStrDecoder,
+    },
+    extension::ExtensionRef,
+    io::{IoRead, IoWrite},
+    timestamp::Timestamp,
+};
+
+use super::{base64, rfc3339};
+
+/// Maximum nesting depth of arrays and maps.
+const MAX_DEPTH: usize = 256;
+
+/// Maximum number of stringified map keys nested inside each other.
+const MAX_KEY_NESTING: u32 = 8;
+
+/// Lowercase hex digits, indexed by nibble.
+pub(crate) const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// Enough backslashes to escape a quote inside [`MAX_KEY_NESTING`] strings.
+const BACKSLASHES: [u8; (1 << MAX_KEY_NESTING) - 1] = [b'\\'; (1 << MAX_KEY_NESTING) - 1];
+
+/// How bin payloads are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinPolicy {
+    /// Standard base64 string with padding, e.g. `"AP8="`.
+    #[default]
+    Base64,
+    /// Lowercase hex string, e.g. `"00ff"`.
+    Hex,
+    /// Array of the byte values, e.g. `[0,255]`.
+    Array,
+    /// Fail with [`ToJsonError::Bin`].
+    Reject,
+}
+
+/// How ext values are written, apart from timestamps handled by
+/// [`TimestampPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtPolicy {
+    /// Object holding the type and the payload written per [`BinPolicy`],
+    /// e.g. `{"type":5,"data":"AP8="}`.
+    #[default]
+    Object,
+    /// Fail with [`ToJsonError::Extension`].
+    Reject,
+}
+
+/// How timestamp ext values are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPolicy {
+    /// RFC 3339 string in UTC, e.g. `"2023-11-14T22:13:20.5Z"`.
+    ///
+    /// Timestamps outside the years 0 to 9999 fail with
+    /// [`ToJsonError::TimestampOutOfRange`]. Malformed timestamp payloads are
+    /// written like any other ext.
+    #[default]
+    Rfc3339,
+    /// Write timestamps like any other ext, following [`ExtPolicy`].
+    Extension,
+}
+
+/// How map keys which are not strings are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyPolicy {
+    /// Write the JSON of the key as a string, e.g. `1` becomes `"1"` and
+    /// `[1,"a"]` becomes `"[1,\"a\"]"`.
+    #[default]
+    Stringify,
+    /// Fail with [`ToJsonError::NonStringKey`].
+    Reject,
+}
+
+/// How NaN and infinite floats are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// `null`, like `serde_json` does.
+    #[default]
+    Null,
+    /// `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
+    /// Fail with [`ToJsonError::NonFiniteFloat`].
+    Reject,
+}
+
+/// Options for [`to_json`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToJsonConfig {
+    pub(crate) bin: BinPolicy,
+    pub(crate) ext: ExtPolicy,
+    pub(crate) timestamp: TimestampPolicy,
+    pub(crate) keys: KeyPolicy,
+    pub(crate) non_finite: NonFinitePolicy,
+    pretty: bool,
+}
+
+impl ToJsonConfig {
+    /// Default policies with compact output.
+    pub const fn new() -> Self {
+        Self {
+            bin: BinPolicy::Base64,
+            ext: ExtPolicy::Object,
+            timestamp: TimestampPolicy::Rfc3339,
+            keys: KeyPolicy::Stringify,
+            non_finite: NonFinitePolicy::Null,
+            pretty: false,
+        }
+    }
+
+    /// Reject everything JSON cannot represent exactly: bin, ext including
+    /// timestamps, non-string map keys and non-finite floats.
+    pub const fn strict() -> Self {
+        Self::new()
+            .with_bin(BinPolicy::Reject)
+            .with_ext(ExtPolicy::Reject)
+            .with_timestamp(TimestampPolicy::Extension)
+            .with_keys(KeyPolicy::Reject)
+            .with_non_finite(NonFinitePolicy::Reject)
+    }
+
+    /// Set how bin payloads are written.
+    pub const fn with_bin(mut self, policy: BinPolicy) -> Self {
+        self.bin = policy;
+        self
+    }
+
+    /// Set how ext values are written.
+    pub const fn with_ext(mut self, policy: ExtPolicy) -> Self {
+        self.ext = policy;
+        self
+    }
+
+    /// Set how timestamps are written.
+    pub const fn with_timestamp(mut self, policy: TimestampPolicy) -> Self {
+        self.timestamp = policy;
+        self
+    }
+
+    /// Set how map keys which are not strings are written.
+    pub const fn with_keys(mut self, policy: KeyPolicy) -> Self {
+        self.keys = policy;
+        self
+    }
+
+    /// Set how NaN and infinite floats are written.
+    pub const fn with_non_finite(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Indent nested values by two spaces, one element per line.
+    pub const fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+}
+
+/// Error returned by [`to_json`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToJsonError<R, W> {
+    /// The input is not valid MessagePack.
+    Input(DecodeError<R>),
+    /// The output rejected the JSON.
+    Output(W),
+    /// A map key is not a string and [`KeyPolicy::Reject`] is set, or
+    /// stringified keys are nested too deeply.
+    NonStringKey,
+    /// A float is NaN or infinite and [`NonFinitePolicy::Reject`] is set.
+    NonFiniteFloat,
+    /// A bin was found and [`BinPolicy::Reject`] is set.
+    Bin,
+    /// An ext of this type was found and [`ExtPolicy::Reject`] is set.
+    Extension(i8),
+    /// A timestamp lies outside the years RFC 3339 can express.
+    TimestampOutOfRange,
+}
+
+impl<R, W> From<DecodeError<R>> for ToJsonError<R, W> {
+    fn from(e: DecodeError<R>) -> Self {
+        ToJsonError::Input(e)
+    }
+}
+
+impl<R, W> From<Output<W>> for ToJsonError<R, W> {
+    fn from(e: Output<W>) -> Self {
+        ToJsonError::Output(e.0)
+    }
+}
+
+impl<R, W> fmt::Display for ToJsonError<R, W>
+where
+    R: fmt::Display,
+    W: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToJsonError::Input(e) => e.fmt(f),
+            ToJsonError::Output(e) => e.fmt(f),
+            ToJsonError::NonStringKey => f.write_str("map key is not a string"),
+            ToJsonError::NonFiniteFloat => f.write_str("float is not finite"),
+            ToJsonError::Bin => f.write_str("bin is not allowed"),
+            ToJsonError::Extension(t) => write!(f, "ext type {t} is not allowed"),
+            ToJsonError::TimestampOutOfRange => f.write_str("timestamp is out of range"),
+        }
+    }
+}
+
+impl<R, W> core::error::Error for ToJsonError<R, W>
+where
+    R: core::error::Error + 'static,
+    W: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ToJsonError::Input(e) => Some(e),
+            ToJsonError::Output(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error of the underlying writer, kept apart from [`DecodeError`] so both
+/// convert into [`ToJsonError`] with `?`.
+struct Output<E>(E);
+
+/// Writes JSON tokens, escaping them again for every stringified map key
+/// they are nested in.
+struct Json<'w, W> {
+    out: &'w mut W,
+    config: ToJsonConfig,
+    indent: usize,
+    escape: u32,
+}
+
+impl<W: IoWrite> Json<'_, W> {
+    fn raw(&mut self, bytes: &[u8]) -> Result<(), Output<W::Error>> {
+        if self.escape == 0 {
+            return self.out.write(bytes).map_err(Output);
+        }
+        // Every level of quoting doubles the backslashes and adds one more.
+        let prefix = &BACKSLASHES[..(1 << self.escape) - 1];
+        let mut rest = bytes;
+        while let Some(i) = rest.iter().position(|b| matches!(b, b'"' | b'\\')) {
+            self.out.write(&rest[..i]).map_err(Output)?;
+            self.out.write(prefix).map_err(Output)?;
+            self.out.write(&rest[i..=i]).map_err(Output)?;
+            rest = &rest[i + 1..];
+        }
+        self.out.write(rest).map_err(Output)
+    }
+
+    fn display(&mut self, args: fmt::Arguments<'_>) -> Result<(), Output<W::Error>> {
+        struct Adapter<'a, 'w, W: IoWrite> {
+            json: &'a mut Json<'w, W>,
+            error: Option<Output<W::Error>>,
+        }
+
+        impl<W: IoWrite> fmt::Write for Adapter<'_, '_, W> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.json.raw(s.as_bytes()).map_err(|e| {
+                    self.error = Some(e);
+                    fmt::Error
+                })
+            }
+        }
+
+        let mut adapter = Adapter {
+            json: self,
+            error: None,
+        };
+        // Formatting numbers only fails when the output does.
+        match adapter.write_fmt(args) {
+            Ok(()) => Ok(()),
+            Err(_) => adapter.error.map_or(Ok(()), Err),
+        }
+    }
+
+    fn str(&mut self, v: &str) -> Result<(), Output<W::Error>> {
+        self.raw(b"\"")?;
+        let bytes = v.as_bytes();
+        let mut unicode = *b"\\u0000";
+        let mut start = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            let escaped: &[u8] = match b {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0x08 => b"\\b",
+                0x0c => b"\\f",
+                0x00..=0x1f => {
+                    unicode[4] = HEX[usize::from(b >> 4)];
+                    unicode[5] = HEX[usize::from(b & 0xf)];
+                    &unicode
+                }
+                _ => continue,
+            };
+            self.raw(&bytes[start..i])?;
+            self.raw(escaped)?;
+            start = i + 1;
+        }
+        self.raw(&bytes[start..])?;
+        self.raw(b"\"")
+    }
+
+    fn bin<R>(&mut self, v: &[u8]) -> Result<(), ToJsonError<R, W::Error>> {
+        match self.config.bin {
+            BinPolicy::Base64 => {
+                self.raw(b"\"")?;
+                base64::encode(v, |text| self.raw(text))?;
+                self.raw(b"\"")?;
+            }
+            BinPolicy::Hex => {
+                let mut buf = [0u8; 64];
+                self.raw(b"\"")?;
+                for block in v.chunks(32) {
+                    for (i, &b) in block.iter().enumerate() {
+                        buf[2 * i] = HEX[usize::from(b >> 4)];
+                        buf[2 * i + 1] = HEX[usize::from(b & 0xf)];
+                    }
+                    self.raw(&buf[..2 * block.len()])?;
+                }
+                self.raw(b"\"")?;
+            }
+            BinPolicy::Array => {
+                self.open(b'[', v.len())?;
+                for (i, b) in v.iter().enumerate() {
+                    self.element(i)?;
+                    self.display(format_args!("{b}"))?;
+                }
+                self.close(b']', v.len())?;
+            }
+            BinPolicy::Reject => return Err(ToJsonError::Bin),
+        }
+        Ok(())
+    }
+
+    /// Stringified keys are always compact.
+    fn pretty(&self) -> bool {
+        self.config.pretty && self.escape == 0
+    }
+
+    /// Open a container which will hold `len` elements.
+    fn open(&mut self, bracket: u8, len: usize) -> Result<(), Output<W::Error>> {
+        self.raw(&[bracket])?;
+        if len > 0 {
+            self.indent += 1;
+        }
+        Ok(())
+    }
+
+    /// Separator written before the `index`-th element.
+    fn element(&mut self, index: usize) -> Result<(), Output<W::Error>> {
+        if index > 0 {
+            self.raw(b",")?;
+        }
+        if self.pretty() {
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    fn key_separator(&mut self) -> Result<(), Output<W::Error>> {
+        self.raw(if self.pretty() { b": " } else { b":" })
+    }
+
+    /// Close a container which held `len` elements.
+    fn close(&mut self, bracket: u8, len: usize) -> Result<(), Output<W::Error>> {
+        if len > 0 {
+            self.indent -= 1;
+            if self.pretty() {
+                self.newline()?;
+            }
+        }
+        self.raw(&[bracket])
+    }
+
+    fn newline(&mut self) -> Result<(), Output<W::Error>> {
+        self.raw(b"\n")?;
+        for _ in 0..self.indent {
+            self.raw(b"  ")?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert the next encoded value from `reader` to JSON written into `writer`.
+///
+/// Exactly one value is consumed and nothing but its JSON is written, so a
+/// stream of concatenated values can be converted by calling this in a loop.
+/// Strings must be valid UTF-8.
+///
+/// With [`StdReader`](messagepack_core::io::StdReader) only the largest str,
+/// bin or ext payload is held in memory at once.
+pub fn to_json<'de, R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    config: ToJsonConfig,
+) -> Result<(), ToJsonError<R::Error, W::Error>>
+where
+    R: IoRead<'de>,
+    W: IoWrite,
+{
+    let mut json = Json {
+        out: writer,
+        config,
+        indent: 0,
+        escape: 0,
+    };
+    let format = Format::decode_borrowed(reader)?;
+    transcode(format, reader, &mut json, 0)
+}
+
+fn transcode<'de, R, W>(
+    format: Format,
+    reader: &mut R,
+    json: &mut Json<'_, W>,
+    depth: usize,
+) -> Result<(), ToJsonError<R::Error, W::Error>>
+where
+    R: IoRead<'de>,
+    W: IoWrite,
+{
+    macro_rules! number {
+        ($ty:ty) => {{
+            let v = <$ty>::decode_borrowed_with_format(format, reader)?;
+            json.display(format_args!("{v}"))?
+        }};
+    }
+
+    match format {
+        Format::Nil => json.raw(b"null")?,
+        Format::False => json.raw(b"false")?,
+        Format::True => json.raw(b"true")?,
+        Format::PositiveFixInt(v) => json.display(format_args!("{v}"))?,
+        Format::Uint8 => number!(u8),
+        Format::Uint16 => number!(u16),
+        Format::Uint32 => number!(u32),
+        Format::Uint64 => number!(u64),
+        Format::NegativeFixInt(v) => json.display(format_args!("{v}"))?,
+        Format::Int8 => number!(i8),
+        Format::Int16 => number!(i16),
+        Format::Int32 => number!(i32),
+        Format::Int64 => number!(i64),
+        Format::Float32 => {
+            let v = f32::decode_borrowed_with_format(format, reader)?;
+            // `Debug` prints the shortest representation which reads back
+            // as the same `f32`, always with a fraction or exponent.
+            if v.is_finite() {
+                json.display(format_args!("{v:?}"))?
+            } else {
+                non_finite(json, v.into())?
+            }
+        }
+        Format::Float64 => {
+            let v = f64::decode_borrowed_with_format(format, reader)?;
+            if v.is_finite() {
+                json.display(format_args!("{v:?}"))?
+            } else {
+                non_finite(json, v)?
+            }
+        }
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32 => {
+            let s = ReferenceStrDecoder::decode_with_format(format, reader)?;
+            json.str(s.as_str())?
+        }
+        Format::Bin8 | Format::Bin16 | Format::Bin32 => {
+            let b = ReferenceDecoder::decode_with_format(format, reader)?;
+            json.bin(b.as_bytes())?
+        }
+        Format::FixExt1
+        | Format::FixExt2
+        | Format::FixExt4
+        | Format::FixExt8
+        | Format::FixExt16
+        | Format::Ext8
+        | Format::Ext16
+        | Format::Ext32 => {
+            let (len, r#type) = ext_header(format, reader)?;
+            let data = reader.read_slice(len).map_err(DecodeError::Io)?;
+            ext(json, ExtensionRef::new(r#type, data.as_bytes()))?
+        }
+        Format::FixArray(_) | Format::Array16 | Format::Array32 => {
+            let depth = enter(depth)?;
+            let len = match format {
+                Format::FixArray(n) => n.into(),
+                Format::Array16 => NbyteReader::<2>::read(reader)?,
+                _ => NbyteReader::<4>::read(reader)?,
+            };
+            json.open(b'[', len)?;
+            for i in 0..len {
+                json.element(i)?;
+                let format = Format::decode_borrowed(reader)?;
+                transcode(format, reader, json, depth)?;
+            }
+            json.close(b']', len)?
+        }
+        Format::FixMap(_) | Format::Map16 | Format::Map32 => {
+            let depth = enter(depth)?;
+            let len = match format {
+                Format::FixMap(n) => n.into(),
+                Format::Map16 => NbyteReader::<2>::read(reader)?,
+                _ => NbyteReader::<4>::read(reader)?,
+            };
+            json.open(b'{', len)?;
+            for i in 0..len {
+                json.element(i)?;
+                let format = Format::decode_borrowed(reader)?;
+                key(format, reader, json, depth)?;
+                json.key_separator()?;
+                let format = Format::decode_borrowed(reader)?;
+                transcode(format, reader, json, depth)?;
+            }
+            json.close(b'}', len)?
+        }
+        Format::NeverUsed => return Err(DecodeError::UnexpectedFormat.into()),
+    };
+    Ok(())
+}
+
+fn key<'de, R, W>(
+    format: Format,
+    reader: &mut R,
+    json: &mut Json<'_, W>,
+    depth: usize,
+) -> Result<(), ToJsonError<R::Error, W::Error>>
+where
+    R: IoRead<'de>,
+    W: IoWrite,
+{
+    if matches!(
+        format,
+        Format::FixStr(_) | Format::Str8 | Format::Str16 | Format::Str32
+    ) {
+        return transcode(format, reader, json, depth);
+    }
+    if json.config.keys == KeyPolicy::Reject || json.escape >= MAX_KEY_NESTING {
+        return Err(ToJsonError::NonStringKey);
+    }
+    json.raw(b"\"")?;
+    json.escape += 1;
+    let result = transcode(format, reader, json, depth);
+    json.escape -= 1;
+    result?;
+    Ok(json.raw(b"\"")?)
+}
+
+fn non_finite<R, W: IoWrite>(
+    json: &mut Json<'_, W>,
+    v: f64,
+) -> Result<(), ToJsonError<R, W::Error>> {
+    match json.config.non_finite {
+        NonFinitePolicy::Null => json.raw(b"null")?,
+        NonFinitePolicy::String if v.is_nan() => json.str("NaN")?,
+        NonFinitePolicy::String if v.is_sign_positive() => json.str("Infinity")?,
+        NonFinitePolicy::String => json.str("-Infinity")?,
+        NonFinitePolicy::Reject => return Err(ToJsonError::NonFiniteFloat),
+    }
+    Ok(())
+}
+
+fn ext<R, W: IoWrite>(
+    json: &mut Json<'_, W>,
+    ext: ExtensionRef<'_>,
+) -> Result<(), ToJsonError<R, W::Error>> {
+    if json.config.timestamp == TimestampPolicy::Rfc3339
+        && let Ok(ts) = Timestamp::try_from(ext)
+    {
+        let date_time = rfc3339::DateTime::new(ts.seconds(), ts.nanos())
+            .ok_or(ToJsonError::TimestampOutOfRange)?;
+        return Ok(json.display(format_args!("\"{date_time}\""))?);
+    }
+
+    match json.config.ext {
+        ExtPolicy::Object => {
+            json.open(b'{', 2)?;
+            json.element(0)?;
+            json.str("type")?;
+            json.key_separator()?;
+            json.display(format_args!("{}", ext.r#type))?;
+            json.element(1)?;
+            json.str("data")?;
+            json.key_separator()?;
+            json.bin(ext.data)?;
+            Ok(json.close(b'}', 2)?)
+        }
+        ExtPolicy::Reject => Err(ToJsonError::Extension(ext.r#type)),
+    }
+}
+
+fn ext_header<'de, R>(format: Format, reader: &mut R) -> Result<(usize, i8), DecodeError<R::Error>>
+where
+    R: IoRead<'de>,
+{
+    let len = match format {
+        Format::FixExt1 => 1,
+        Format::FixExt2 => 2,
+        Format::FixExt4 => 4,
+        Format::FixExt8 => 8,
+        Format::FixExt16 => 16,
+        Format::Ext8 => NbyteReader::<1>::read(reader)?,
+        Format::Ext16 => NbyteReader::<2>::read(reader)?,
+        Format::Ext32 => NbyteReader::<4>::read(reader)?,
+        _ => return Err(DecodeError::UnexpectedFormat),
+    };
+    let r#type = reader.read_slice(1).map_err(DecodeError::Io)?;
+    let &[r#type] = r#type.as_bytes() else {
+        return Err(DecodeError::UnexpectedEof);
+    };
+    Ok((len, r#type as i8))
+}
+
+fn enter<R, W>(depth: usize) -> Result<usize, ToJsonError<R, W>> {
+    if depth >= MAX_DEPTH {
+        Err(DecodeError::InvalidData.into())
+    } else {
+        Ok(depth + 1)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use messagepack_core::io::SliceReader;
+    use rstest::rstest;
+
+    fn convert(buf: &[u8], config: ToJsonConfig) -> Result<String, ToJsonError<(), ()>> {
+        let mut out = Vec::new();
+        let mut reader = SliceReader::new(buf);
+        to_json(&mut reader, &mut out, config).map_err(|e| match e {
+            ToJsonError::Input(_) => ToJsonError::Input(DecodeError::Io(())),
+            ToJsonError::Output(_) => ToJsonError::Output(()),
+            ToJsonError::NonStringKey => ToJsonError::NonStringKey,
+            ToJsonError::NonFiniteFloat => ToJsonError::NonFiniteFloat,
+            ToJsonError::Bin => ToJsonError::Bin,
+            ToJsonError::Extension(t) => ToJsonError::Extension(t),
+            ToJsonError::TimestampOutOfRange => ToJsonError::TimestampOutOfRange,
+        })?;
+        assert!(reader.rest().is_empty());
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[rstest]
+    #[case(&[0xc0], "null")]
+    #[case(&[0xc3], "true")]
+    #[case(&[0x7f], "127")]
+    #[case(&[0xe0], "-32")]
+    #[case(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], "18446744073709551615")]
+    #[case(&[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0], "-9223372036854775808")]
+    #[case(&[0xca, 0x3f, 0x8c, 0xcc, 0xcd], "1.1")]
+    #[case(&[0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0], "1.0")]
+    #[case(&[0xa4, b'a', b'"', b'\\', 0x01], r#""a\"\\\u0001""#)]
+    #[case(&[0xa2, b'\n', b'\t'], r#""\n\t""#)]
+    #[case(&[0x92, 0x01, 0x90], "[1,[]]")]
+    #[case(&[0x82, 0xa1, b'a', 0x80, 0xa1, b'b', 0xc2], r#"{"a":{},"b":false}"#)]
+    fn scalars_and_containers(#[case] buf: &[u8], #[case] expected: &str) {
+        assert_eq!(convert(buf, ToJsonConfig::new()).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(BinPolicy::Base64, &[], r#""""#)]
+    #[case(BinPolicy::Base64, b"f", r#""Zg==""#)]
+    #[case(BinPolicy::Base64, b"fo", r#""Zm8=""#)]
+    #[case(BinPolicy::Base64, b"foo", r#""Zm9v""#)]
+    #[case(BinPolicy::Base64, b"foobar", r#""Zm9vYmFy""#)]
+    #[case(BinPolicy::Hex, &[0x00, 0xff], r#""00ff""#)]
+    #[case(BinPolicy::Array, &[0x00, 0xff], "[0,255]")]
+    #[case(BinPolicy::Array, &[], "[]")]
+    fn bin(#[case] policy: BinPolicy, #[case] data: &[u8], #[case] expected: &str) {
+        let mut buf = vec![0xc4, data.len() as u8];
+        buf.extend_from_slice(data);
+        let config = ToJsonConfig::new().with_bin(policy);
+        assert_eq!(convert(&buf, config).unwrap(), expected);
+    }
+
+    #[test]
+    fn strict() {
+        let config = ToJsonConfig::strict();
+        assert_eq!(
+            convert(&[0x92, 0x01, 0xa1, b'a'], config).unwrap(),
+            r#"[1,"a"]"#
+        );
+        assert_eq!(convert(&[0xc4, 0x00], config), Err(ToJsonError::Bin));
+        assert_eq!(
+            convert(&[0xd6, 0xff, 0, 0, 0, 0], config),
+            Err(ToJsonError::Extension(-1))
+        );
+        assert_eq!(
+            convert(&[0x81, 0x01, 0xc0], config),
+            Err(ToJsonError::NonStringKey)
+        );
+        assert_eq!(
+            convert(&[0xca, 0x7f, 0x80, 0, 0], config),
+            Err(ToJsonError::NonFiniteFloat)
+        );
+    }
+
+    #[test]
+    fn long_bin_spans_blocks() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut buf = vec![0xc5, 0x01, 0x00];
+        buf.extend_from_slice(&data);
+
+        let hex = convert(&buf, ToJsonConfig::new().with_bin(BinPolicy::Hex)).unwrap();
+        let expected: String = data.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(hex, format!("\"{expected}\""));
+
+        let base64 = convert(&buf, ToJsonConfig::new()).unwrap();
+        assert_eq!(base64.len(), 2 + 344);
+        assert!(base64.starts_with("\"AAECAwQF") && base64.ends_with("+/w==\""));
+    }
+
+    #[rstest]
+    #[case(ExtPolicy::Object, Ok(r#"{"type":5,"data":"AP8="}"#))]
+    #[case(ExtPolicy::Reject, Err(ToJsonError::Extension(5)))]
+    fn ext(#[case] policy: ExtPolicy, #[case] expected: Result<&str, ToJsonError<(), ()>>) {
+        let buf = [0xd5, 0x05, 0x00, 0xff];
+        let config = ToJsonConfig::new().with_ext(policy);
+        assert_eq!(convert(&buf, config), expected.map(String::from));
+    }
+
+    #[rstest]
+    #[case(&[0xd6, 0xff, 0, 0, 0, 0], r#""1970-01-01T00:00:00Z""#)]
+    #[case(&[0xd6, 0xff, 0x65, 0x53, 0xf1, 0x00], r#""2023-11-14T22:13:20Z""#)]
+    // 500_000_000 ns after 1700000000 s
+    #[case(&[0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x65, 0x53, 0xf1, 0x00], r#""2023-11-14T22:13:20.5Z""#)]
+    // 1 ns before the epoch
+    #[case(
+        &[0xc7, 0x0c, 0xff, 0x3b, 0x9a, 0xc9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        r#""1969-12-31T23:59:59.999999999Z""#
+    )]
+    // 2000-02-29
+    #[case(&[0xd6, 0xff, 0x38, 0xbb, 0x0c, 0x00], r#""2000-02-29T00:00:00Z""#)]
+    // nanoseconds out of range is not a timestamp
+    #[case(
+        &[0xd7, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0],
+        r#"{"type":-1,"data":"/////wAAAAA="}"#
+    )]
+    fn timestamp_rfc3339(#[case] buf: &[u8], #[case] expected: &str) {
+        assert_eq!(convert(buf, ToJsonConfig::new()).unwrap(), expected);
+    }
+
+    #[test]
+    fn timestamp_policies() {
+        let buf = [0xd6, 0xff, 0, 0, 0, 0];
+        let config = ToJsonConfig::new().with_timestamp(TimestampPolicy::Extension);
+        assert_eq!(
+            convert(&buf, config).unwrap(),
+            r#"{"type":-1,"data":"AAAAAA=="}"#
+        );
+
+        // year 10000
+        let seconds = 253_402_300_800i64.to_be_bytes();
+        let mut buf = vec![0xc7, 0x0c, 0xff, 0, 0, 0, 0];
+        buf.extend_from_slice(&seconds);
+        assert_eq!(
+            convert(&buf, ToJsonConfig::new()),
+            Err(ToJsonError::TimestampOutOfRange)
+        );
+    }
+
+    #[rstest]
+    #[case(&[0x81, 0x01, 0xc0], r#"{"1":null}"#)]
+    #[case(&[0x81, 0xc0, 0xc0], r#"{"null":null}"#)]
+    #[case(&[0x81, 0x92, 0x01, 0xa1, b'a', 0xc0], r#"{"[1,\"a\"]":null}"#)]
+    #[case(&[0x81, 0x81, 0xa1, b'"', 0x02, 0xc0], r#"{"{\"\\\"\":2}":null}"#)]
+    #[case(&[0x81, 0x81, 0x81, 0x03, 0xa1, b'x', 0x02, 0xc0], r#"{"{\"{\\\"3\\\":\\\"x\\\"}\":2}":null}"#)]
+    fn stringified_keys(#[case] buf: &[u8], #[case] expected: &str) {
+        let json = convert(buf, ToJsonConfig::new()).unwrap();
+        assert_eq!(json, expected);
+        // The stringified key reads back as JSON.
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&json).unwrap();
+        let key = map.keys().next().unwrap();
+        serde_json::from_str::<serde_json::Value>(key).unwrap();
+    }
+
+    #[test]
+    fn rejected_key() {
+        let config = ToJsonConfig::new().with_keys(KeyPolicy::Reject);
+        assert_eq!(
+            convert(&[0x81, 0x01, 0xc0], config),
+            Err(ToJsonError::NonStringKey)
+        );
+    }
+
+    #[rstest]
+    #[case(NonFinitePolicy::Null, &[0xcb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0], Ok("null"))]
+    #[case(NonFinitePolicy::String, &[0xcb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0], Ok(r#""NaN""#))]
+    #[case(NonFinitePolicy::String, &[0xca, 0x7f, 0x80, 0, 0], Ok(r#""Infinity""#))]
+    #[case(NonFinitePolicy::String, &[0xca, 0xff, 0x80, 0, 0], Ok(r#""-Infinity""#))]
+    #[case(NonFinitePolicy::Reject, &[0xca, 0xff, 0x80, 0, 0], Err(ToJsonError::NonFiniteFloat))]
+    fn non_finite_floats(
+        #[case] policy: NonFinitePolicy,
+        #[case] buf: &[u8],
+        #[case] expected: Result<&str, ToJsonError<(), ()>>,
+    ) {
+        let config = ToJsonConfig::new().with_non_finite(policy);
+        assert_eq!(convert(buf, config), expected.map(String::from));
+    }
+
+    #[test]
+    fn pretty() {
+        // {"a": [1, {}], 1: [2]}
+        let buf = [0x82, 0xa1, b'a', 0x92, 0x01, 0x80, 0x01, 0x91, 0x02];
+        let config = ToJsonConfig::new().with_pretty(true);
+        assert_eq!(
+            convert(&buf, config).unwrap(),
+            "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"1\": [\n    2\n  ]\n}"
+        );
+        // map keys are stringified compactly
+        let buf = [0x81, 0x92, 0x01, 0x02, 0xc0];
+        assert_eq!(convert(&buf, config).unwrap(), "{\n  \"[1,2]\": null\n}");
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0x01])]
+    #[case(&[0xa2, 0xff, 0xfe])]
+    #[case(&[0xc1])]
+    fn invalid_input(#[case] buf: &[u8]) {
+        assert!(matches!(
+            convert(buf, ToJsonConfig::new()),
+            Err(ToJsonError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let mut buf = vec![0x91; MAX_DEPTH];
+        buf.push(0xc0);
+        assert!(convert(&buf, ToJsonConfig::new()).is_ok());
+
+        let mut buf = vec![0x91; MAX_DEPTH + 1];
+        buf.push(0xc0);
+        assert!(matches!(
+            convert(&buf, ToJsonConfig::new()),
+            Err(ToJsonError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn output_error() {
+        let mut out = [0u8; 4];
+        let mut writer = out.as_mut_slice();
+        let buf = [0xa5, b'h', b'e', b'l', b'l', b'o'];
+        assert!(matches!(
+            to_json(
+                &mut SliceReader::new(&buf),
+                &mut writer,
+                ToJsonConfig::new()
+            ),
+            Err(ToJsonError::Output(_))
+        ));
+    }
+}
@@ -17,6 +17,18 @@ where
     }
 }
 
+/// Hand the re-encoded `value` to a [`Raw`](crate::value::Raw) or
+/// [`RawBuf`](crate::value::RawBuf) visitor. The bytes are not borrowed from
+/// the input, so only `RawBuf` accepts them.
+fn visit_raw<'de, T, V>(value: &T, visitor: V) -> Result<V::Value, Error>
+where
+    T: serde::Serialize + ?Sized,
+    V: de::Visitor<'de>,
+{
+    let bytes = crate::to_vec(value).map_err(<Error as de::Error>::custom)?;
+    visitor.visit_newtype_struct(de::value::BytesDeserializer::<Error>::new(&bytes))
+}
+
 mod value_ref {
     use super::*;
     impl<'de> de::Deserializer<'de> for &'de Value {
@@ -74,9 +86,23 @@ mod value_ref {
             visitor.visit_enum(access)
         }
 
+        fn deserialize_newtype_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            match name {
+                crate::value::RAW_VALUE_STRUCT_NAME => visit_raw(self, visitor),
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            bytes byte_buf unit unit_struct seq tuple
             tuple_struct map struct identifier ignored_any
         }
     }
@@ -432,9 +458,23 @@ mod value_ref {
             visitor.visit_enum(access)
         }
 
+        fn deserialize_newtype_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            match name {
+                crate::value::RAW_VALUE_STRUCT_NAME => visit_raw(self, visitor),
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct
+            bytes byte_buf unit unit_struct seq tuple tuple_struct
             map struct identifier ignored_any
         }
     }
@@ -661,9 +701,23 @@ mod value_owned {
             visitor.visit_enum(access)
         }
 
+        fn deserialize_newtype_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            match name {
+                crate::value::RAW_VALUE_STRUCT_NAME => visit_raw(&self, visitor),
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct
+            bytes byte_buf unit unit_struct seq tuple tuple_struct
             map struct identifier ignored_any
         }
     }
@@ -0,0 +1,390 @@
+use super::{Number, Value, ValueRef};
+use alloc::{borrow::Cow, string::String};
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for alloc::string::String {}
+    impl<T> Sealed for &T where T: ?Sized + Sealed {}
+}
+
+/// A type that can be used to index into a [`Value`] or [`ValueRef`].
+///
+/// `usize` indexes into arrays and string types look up map entries whose key
+/// is a string. This trait is sealed and cannot be implemented outside of
+/// this crate.
+pub trait Index: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value>;
+
+    #[doc(hidden)]
+    fn index_into_ref<'v, 'a>(&self, v: &'v ValueRef<'a>) -> Option<&'v ValueRef<'a>>;
+
+    #[doc(hidden)]
+    fn remove_from(&self, v: &mut Value) -> Option<Value>;
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Array(items) => items.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Array(items) => items.get_mut(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_ref<'v, 'a>(&self, v: &'v ValueRef<'a>) -> Option<&'v ValueRef<'a>> {
+        match v {
+            ValueRef::Array(items) => items.get(*self),
+            _ => None,
+        }
+    }
+
+    fn remove_from(&self, v: &mut Value) -> Option<Value> {
+        match v {
+            Value::Array(items) if *self < items.len() => Some(items.remove(*self)),
+            _ => None,
+        }
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_string() == Some(self))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Map(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| k.as_string() == Some(self))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn index_into_ref<'v, 'a>(&self, v: &'v ValueRef<'a>) -> Option<&'v ValueRef<'a>> {
+        match v {
+            ValueRef::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_string() == Some(self))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn remove_from(&self, v: &mut Value) -> Option<Value> {
+        match v {
+            Value::Map(entries) => {
+                let pos = entries
+                    .iter()
+                    .position(|(k, _)| k.as_string() == Some(self))?;
+                Some(entries.remove(pos).1)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(v)
+    }
+
+    fn index_into_ref<'v, 'a>(&self, v: &'v ValueRef<'a>) -> Option<&'v ValueRef<'a>> {
+        self.as_str().index_into_ref(v)
+    }
+
+    fn remove_from(&self, v: &mut Value) -> Option<Value> {
+        self.as_str().remove_from(v)
+    }
+}
+
+impl<T> Index for &T
+where
+    T: ?Sized + Index,
+{
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(v)
+    }
+
+    fn index_into_ref<'v, 'a>(&self, v: &'v ValueRef<'a>) -> Option<&'v ValueRef<'a>> {
+        (**self).index_into_ref(v)
+    }
+
+    fn remove_from(&self, v: &mut Value) -> Option<Value> {
+        (**self).remove_from(v)
+    }
+}
+
+static NIL: Value = Value::Nil;
+static NIL_REF: ValueRef<'static> = ValueRef::Nil;
+
+impl<I> core::ops::Index<I> for Value
+where
+    I: Index,
+{
+    type Output = Value;
+
+    /// Index into an array or map, returning [`Value::Nil`] when the entry
+    /// does not exist or `self` has the wrong type.
+    fn index(&self, index: I) -> &Self::Output {
+        index.index_into(self).unwrap_or(&NIL)
+    }
+}
+
+impl<'a, I> core::ops::Index<I> for ValueRef<'a>
+where
+    I: Index,
+{
+    type Output = ValueRef<'a>;
+
+    /// Index into an array or map, returning [`ValueRef::Nil`] when the entry
+    /// does not exist or `self` has the wrong type.
+    fn index(&self, index: I) -> &Self::Output {
+        index.index_into_ref(self).unwrap_or(&NIL_REF)
+    }
+}
+
+/// Unescape one JSON Pointer reference token, borrowing it unless it holds
+/// a `~` escape.
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Whether `digits` has no sign and no leading zeros.
+fn is_plain_decimal(digits: &str) -> bool {
+    !digits.starts_with('+') && (!digits.starts_with('0') || digits.len() == 1)
+}
+
+/// Parse an array index, rejecting signs and leading zeros.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if !is_plain_decimal(token) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Parse an integer map key with the same rules as [`parse_index`], plus an
+/// optional leading `-` for negative keys.
+fn parse_int_key(token: &str) -> Option<Number> {
+    match token.strip_prefix('-') {
+        Some("0") => None,
+        Some(digits) if is_plain_decimal(digits) => token.parse().ok().map(Number::NegativeInt),
+        None if is_plain_decimal(token) => token.parse().ok().map(Number::PositiveInt),
+        _ => None,
+    }
+}
+
+/// Split a JSON Pointer into unescaped reference tokens.
+///
+/// Returns `None` if the pointer is neither empty nor starts with `/`.
+pub(crate) fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = Cow<'_, str>>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+    Some(pointer.split('/').skip(1).map(unescape_token))
+}
+
+/// Whether `key` matches a pointer token, either as a string or as an integer.
+fn key_matches(key: Option<&str>, number: Option<Number>, token: &str) -> bool {
+    if key == Some(token) {
+        return true;
+    }
+    match (number, parse_int_key(token)) {
+        (Some(n), Some(t)) => n == t,
+        _ => false,
+    }
+}
+
+impl Value {
+    /// Look up a pointer such as `/a/0/b`, following
+    /// [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901).
+    ///
+    /// Map segments match string keys, or integer keys when the segment is a
+    /// plain decimal number, optionally with a leading `-`. Array segments
+    /// must be a plain decimal index. Plain means no `+` and no leading zeros,
+    /// so `/07` matches neither the key `7` nor index `7`.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        pointer_tokens(pointer)?.try_fold(self, |target, token| match target {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| key_matches(k.as_string(), k.as_number(), &token))
+                .map(|(_, v)| v),
+            Value::Array(items) => parse_index(&token).and_then(|i| items.get(i)),
+            _ => None,
+        })
+    }
+
+    /// Mutable variant of [`Value::pointer`].
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        pointer_tokens(pointer)?.try_fold(self, |target, token| match target {
+            Value::Map(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| key_matches(k.as_string(), k.as_number(), &token))
+                .map(|(_, v)| v),
+            Value::Array(items) => parse_index(&token).and_then(|i| items.get_mut(i)),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> ValueRef<'a> {
+    /// Look up a pointer such as `/a/0/b`. See [`Value::pointer`].
+    pub fn pointer(&self, pointer: &str) -> Option<&ValueRef<'a>> {
+        pointer_tokens(pointer)?.try_fold(self, |target, token| match target {
+            ValueRef::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| key_matches(k.as_string(), k.as_number(), &token))
+                .map(|(_, v)| v),
+            ValueRef::Array(items) => parse_index(&token).and_then(|i| items.get(i)),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use rstest::rstest;
+
+    fn sample() -> Value {
+        // {"a": [1, {"b": true}], "c/d": nil, 7: "seven"}
+        Value::Map(vec![
+            (
+                Value::from("a"),
+                Value::Array(vec![
+                    Value::from(1u8),
+                    Value::Map(vec![(Value::from("b"), Value::from(true))]),
+                ]),
+            ),
+            (Value::from("c/d"), Value::Nil),
+            (Value::from(7u8), Value::from("seven")),
+        ])
+    }
+
+    #[test]
+    fn index_returns_nil_sentinel() {
+        let v = sample();
+        assert_eq!(v["a"][0], Value::from(1u8));
+        assert_eq!(v["a"][1]["b"], Value::from(true));
+        assert_eq!(v["missing"], Value::Nil);
+        assert_eq!(v["a"][5], Value::Nil);
+        assert_eq!(v[0], Value::Nil);
+        assert_eq!(v["a"]["b"], Value::Nil);
+    }
+
+    #[rstest]
+    #[case("", true)]
+    #[case("/a/0", true)]
+    #[case("/a/1/b", true)]
+    #[case("/c~1d", true)]
+    #[case("/7", true)]
+    #[case("/a/01", false)]
+    #[case("/a/+1", false)]
+    #[case("/a/2", false)]
+    #[case("a", false)]
+    #[case("/x", false)]
+    fn pointer_lookup(#[case] pointer: &str, #[case] found: bool) {
+        let v = sample();
+        assert_eq!(v.pointer(pointer).is_some(), found);
+
+        let buf = crate::to_vec(&v).unwrap();
+        let r = crate::from_slice::<ValueRef<'_>>(&buf).unwrap();
+        assert_eq!(r.pointer(pointer).is_some(), found);
+    }
+
+    #[rstest]
+    #[case("/7", Some("seven"))]
+    #[case("/07", None)]
+    #[case("/+7", None)]
+    #[case("/-3", Some("minus three"))]
+    #[case("/-03", None)]
+    #[case("/-0", None)]
+    #[case("/18446744073709551615", Some("max"))]
+    #[case("/18446744073709551616", None)]
+    fn pointer_int_keys(#[case] pointer: &str, #[case] expected: Option<&str>) {
+        let v = Value::Map(vec![
+            (Value::from(7u8), Value::from("seven")),
+            (Value::from(-3i8), Value::from("minus three")),
+            (Value::from(u64::MAX), Value::from("max")),
+        ]);
+        assert_eq!(v.pointer(pointer).and_then(Value::as_string), expected);
+    }
+
+    #[rstest]
+    #[case("a", "a", false)]
+    #[case("a~1b~0", "a/b~", true)]
+    #[case("~01", "~1", true)]
+    fn unescape_allocates_only_for_escapes(
+        #[case] token: &str,
+        #[case] expected: &str,
+        #[case] owned: bool,
+    ) {
+        let unescaped = unescape_token(token);
+        assert_eq!(unescaped, expected);
+        assert_eq!(matches!(unescaped, Cow::Owned(_)), owned);
+    }
+
+    #[test]
+    fn pointer_mut_updates_in_place() {
+        let mut v = sample();
+        *v.pointer_mut("/a/1/b").unwrap() = Value::from(false);
+        assert_eq!(v["a"][1]["b"], Value::from(false));
+        assert_eq!(v.pointer("/7"), Some(&Value::from("seven")));
+    }
+
+    #[test]
+    fn remove_from_map_and_array() {
+        let mut v = sample();
+        assert_eq!(v.remove("c/d"), Some(Value::Nil));
+        assert_eq!(v.remove("c/d"), None);
+
+        let a = v.get_mut("a").unwrap();
+        assert_eq!(a.remove(0), Some(Value::from(1u8)));
+        assert_eq!(a.remove(5), None);
+        assert_eq!(a.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn value_ref_index() {
+        let buf = crate::to_vec(&sample()).unwrap();
+        let r = crate::from_slice::<ValueRef<'_>>(&buf).unwrap();
+        assert_eq!(r["a"][1]["b"], ValueRef::Bool(true));
+        assert_eq!(r["nope"], ValueRef::Nil);
+        assert_eq!(
+            r.get("a").and_then(|a| a.get(0)),
+            Some(&ValueRef::from(1u8))
+        );
+        assert_eq!(r.keys().count(), 3);
+    }
+}
@@ -0,0 +1,374 @@
+//! Conversions between [`Value`] and [`serde_json::Value`].
+//!
+//! Other dynamic values which implement `serde::Serialize`, such as
+//! `ciborium::Value`, convert with [`to_value`](super::to_value), and
+//! `T::deserialize(&value)` converts back.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use messagepack_core::{extension::ExtensionRef, timestamp::Timestamp};
+
+use super::{Number, Value};
+use crate::transcode::{
+    BinPolicy, ExtPolicy, HEX, KeyPolicy, NonFinitePolicy, TimestampPolicy, ToJsonConfig, base64,
+    rfc3339::DateTime,
+};
+
+/// Error returned when a [`Value`] cannot be converted to
+/// [`serde_json::Value`] under the chosen [`ToJsonConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToJsonValueError {
+    /// A map key is not a string and [`KeyPolicy::Reject`] is set.
+    NonStringKey,
+    /// A float is NaN or infinite and [`NonFinitePolicy::Reject`] is set.
+    NonFiniteFloat,
+    /// A bin was found and [`BinPolicy::Reject`] is set.
+    Bin,
+    /// An ext of this type was found and [`ExtPolicy::Reject`] is set.
+    Extension(i8),
+    /// A timestamp lies outside the years RFC 3339 can express.
+    TimestampOutOfRange,
+}
+
+impl fmt::Display for ToJsonValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToJsonValueError::NonStringKey => f.write_str("map key is not a string"),
+            ToJsonValueError::NonFiniteFloat => f.write_str("float is not finite"),
+            ToJsonValueError::Bin => f.write_str("bin is not allowed"),
+            ToJsonValueError::Extension(t) => write!(f, "ext type {t} is not allowed"),
+            ToJsonValueError::TimestampOutOfRange => f.write_str("timestamp is out of range"),
+        }
+    }
+}
+
+impl core::error::Error for ToJsonValueError {}
+
+impl From<serde_json::Number> for Number {
+    fn from(n: serde_json::Number) -> Self {
+        if let Some(v) = n.as_u64() {
+            Number::PositiveInt(v)
+        } else if let Some(v) = n.as_i64() {
+            Number::NegativeInt(v)
+        } else {
+            Number::Float(n.as_f64().unwrap_or(f64::NAN))
+        }
+    }
+}
+
+/// JSON maps to MessagePack without loss. Object keys become
+/// [`Value::String`].
+impl From<serde_json::Value> for Value {
+    fn from(v: serde_json::Value) -> Self {
+        match v {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => Value::Number(n.into()),
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.into_iter().map(Into::into).collect())
+            }
+            serde_json::Value::Object(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Value::String(k), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Fails on anything JSON cannot represent exactly, as with
+/// [`ToJsonConfig::strict`].
+impl TryFrom<Value> for serde_json::Value {
+    type Error = ToJsonValueError;
+
+    fn try_from(v: Value) -> Result<Self, Self::Error> {
+        v.to_json(ToJsonConfig::strict())
+    }
+}
+
+impl Value {
+    /// Convert to [`serde_json::Value`], writing bin, ext, timestamps,
+    /// non-string map keys and non-finite floats as `config` says.
+    ///
+    /// The result matches what
+    /// [`transcode::to_json`](crate::transcode::to_json) writes for the
+    /// encoded value, except that a JSON object holds each key once. When
+    /// two entries of a map have the same key, as given or after
+    /// stringifying (`1` and `"1"`), the later one replaces the earlier.
+    ///
+    /// ```rust
+    /// use messagepack_serde::{Value, transcode::{BinPolicy, ToJsonConfig}};
+    ///
+    /// let v = Value::Map(vec![(Value::from(1), Value::Bin(vec![0x00, 0xff]))]);
+    /// let config = ToJsonConfig::new().with_bin(BinPolicy::Hex);
+    /// assert_eq!(v.to_json(config).unwrap(), serde_json::json!({ "1": "00ff" }));
+    /// ```
+    pub fn to_json(&self, config: ToJsonConfig) -> Result<serde_json::Value, ToJsonValueError> {
+        Converter {
+            config,
+            lossy: false,
+        }
+        .value(self)
+    }
+
+    /// Convert to [`serde_json::Value`] for display, never failing.
+    ///
+    /// Bin becomes base64, timestamps become RFC 3339 strings, other ext
+    /// become `{"type": t, "data": base64}`, non-string map keys are
+    /// stringified and non-finite floats become `null`.
+    ///
+    /// ```rust
+    /// use messagepack_serde::Value;
+    ///
+    /// let v = Value::Array(vec![Value::Bin(vec![0x00, 0xff]), Value::from(f64::NAN)]);
+    /// assert_eq!(v.to_json_lossy(), serde_json::json!(["AP8=", null]));
+    /// ```
+    pub fn to_json_lossy(&self) -> serde_json::Value {
+        Converter {
+            config: ToJsonConfig::new(),
+            lossy: true,
+        }
+        .value(self)
+        .expect("lossy conversion never fails")
+    }
+}
+
+struct Converter {
+    config: ToJsonConfig,
+    /// Write timestamps outside the RFC 3339 range as ext.
+    lossy: bool,
+}
+
+impl Converter {
+    fn value(&self, v: &Value) -> Result<serde_json::Value, ToJsonValueError> {
+        let json = match v {
+            Value::Nil => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Number(Number::PositiveInt(n)) => (*n).into(),
+            Value::Number(Number::NegativeInt(n)) => (*n).into(),
+            Value::Number(Number::Float(f)) => match serde_json::Number::from_f64(*f) {
+                Some(n) => serde_json::Value::Number(n),
+                None => self.non_finite(*f)?,
+            },
+            Value::String(s) => serde_json::Value::String(s.clone()),
+            Value::Bin(b) => self.bin(b)?,
+            Value::Extension(ext) => self.ext(ext.as_ref())?,
+            Value::Array(items) => serde_json::Value::Array(
+                items
+                    .iter()
+                    .map(|v| self.value(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Map(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(k, v)| Ok((self.key(k)?, self.value(v)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(json)
+    }
+
+    fn key(&self, k: &Value) -> Result<String, ToJsonValueError> {
+        match k {
+            Value::String(s) => Ok(s.clone()),
+            _ if self.config.keys == KeyPolicy::Reject => Err(ToJsonValueError::NonStringKey),
+            _ => Ok(self.value(k)?.to_string()),
+        }
+    }
+
+    fn non_finite(&self, f: f64) -> Result<serde_json::Value, ToJsonValueError> {
+        let s = match self.config.non_finite {
+            NonFinitePolicy::Null => return Ok(serde_json::Value::Null),
+            NonFinitePolicy::Reject => return Err(ToJsonValueError::NonFiniteFloat),
+            NonFinitePolicy::String if f.is_nan() => "NaN",
+            NonFinitePolicy::String if f.is_sign_positive() => "Infinity",
+            NonFinitePolicy::String => "-Infinity",
+        };
+        Ok(serde_json::Value::String(s.into()))
+    }
+
+    fn bin(&self, b: &[u8]) -> Result<serde_json::Value, ToJsonValueError> {
+        let json = match self.config.bin {
+            BinPolicy::Base64 => {
+                let mut s = String::with_capacity(b.len().div_ceil(3) * 4);
+                base64::encode(b, |text| {
+                    s.extend(text.iter().map(|&c| char::from(c)));
+                    Ok::<_, core::convert::Infallible>(())
+                })
+                .ok();
+                serde_json::Value::String(s)
+            }
+            BinPolicy::Hex => {
+                let mut s = String::with_capacity(b.len() * 2);
+                for &byte in b {
+                    s.push(char::from(HEX[usize::from(byte >> 4)]));
+                    s.push(char::from(HEX[usize::from(byte & 0xf)]));
+                }
+                serde_json::Value::String(s)
+            }
+            BinPolicy::Array => {
+                serde_json::Value::Array(b.iter().map(|&byte| byte.into()).collect::<Vec<_>>())
+            }
+            BinPolicy::Reject => return Err(ToJsonValueError::Bin),
+        };
+        Ok(json)
+    }
+
+    fn ext(&self, ext: ExtensionRef<'_>) -> Result<serde_json::Value, ToJsonValueError> {
+        if self.config.timestamp == TimestampPolicy::Rfc3339
+            && let Ok(ts) = Timestamp::try_from(ext)
+        {
+            match DateTime::new(ts.seconds(), ts.nanos()) {
+                Some(date_time) => return Ok(serde_json::Value::String(date_time.to_string())),
+                None if !self.lossy => return Err(ToJsonValueError::TimestampOutOfRange),
+                None => {}
+            }
+        }
+        match self.config.ext {
+            ExtPolicy::Object => {
+                let mut object = serde_json::Map::new();
+                object.insert("type".into(), ext.r#type.into());
+                object.insert("data".into(), self.bin(ext.data)?);
+                Ok(serde_json::Value::Object(object))
+            }
+            ExtPolicy::Reject => Err(ToJsonValueError::Extension(ext.r#type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messagepack_core::extension::ExtensionOwned;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn timestamp(seconds: i64, nanos: u32) -> Value {
+        let mut data = nanos.to_be_bytes().to_vec();
+        data.extend_from_slice(&seconds.to_be_bytes());
+        Value::Extension(ExtensionOwned::new(-1, data))
+    }
+
+    #[test]
+    fn from_json() {
+        let v = Value::from(json!({"a": [null, true, 1, -1, 0.5, "s"]}));
+        assert_eq!(
+            v,
+            Value::Map(vec![(
+                Value::from("a"),
+                Value::Array(vec![
+                    Value::Nil,
+                    Value::Bool(true),
+                    Value::Number(Number::PositiveInt(1)),
+                    Value::Number(Number::NegativeInt(-1)),
+                    Value::Number(Number::Float(0.5)),
+                    Value::from("s"),
+                ])
+            )])
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = json!({"a": [null, true, 1, -1, 0.5, "s", {}], "u": u64::MAX});
+        let back = serde_json::Value::try_from(Value::from(json.clone())).unwrap();
+        assert_eq!(back, json);
+    }
+
+    #[rstest]
+    #[case(Value::Bin(vec![1]), ToJsonValueError::Bin)]
+    #[case(timestamp(0, 0), ToJsonValueError::Extension(-1))]
+    #[case(Value::Map(vec![(Value::Nil, Value::Nil)]), ToJsonValueError::NonStringKey)]
+    #[case(Value::from(f64::INFINITY), ToJsonValueError::NonFiniteFloat)]
+    fn try_from_is_strict(#[case] v: Value, #[case] expected: ToJsonValueError) {
+        assert_eq!(serde_json::Value::try_from(v), Err(expected));
+    }
+
+    #[test]
+    fn later_duplicate_key_wins() {
+        let v = Value::Map(vec![
+            (Value::from(1), Value::from("int")),
+            (Value::from("1"), Value::from("str")),
+        ]);
+        assert_eq!(v.to_json(ToJsonConfig::new()).unwrap(), json!({"1": "str"}));
+    }
+
+    #[rstest]
+    #[case(BinPolicy::Base64, json!("AP8="))]
+    #[case(BinPolicy::Hex, json!("00ff"))]
+    #[case(BinPolicy::Array, json!([0, 255]))]
+    fn bin_policy(#[case] policy: BinPolicy, #[case] expected: serde_json::Value) {
+        let v = Value::Bin(vec![0x00, 0xff]);
+        let config = ToJsonConfig::new().with_bin(policy);
+        assert_eq!(v.to_json(config).unwrap(), expected);
+    }
+
+    #[test]
+    fn ext_and_timestamps() {
+        let ext = Value::Extension(ExtensionOwned::new(5, vec![0x00, 0xff]));
+        assert_eq!(
+            ext.to_json(ToJsonConfig::new()).unwrap(),
+            json!({"type": 5, "data": "AP8="})
+        );
+        assert_eq!(
+            timestamp(1_700_000_000, 500_000_000).to_json_lossy(),
+            json!("2023-11-14T22:13:20.5Z")
+        );
+
+        let far = timestamp(253_402_300_800, 0);
+        assert_eq!(
+            far.to_json(ToJsonConfig::new()),
+            Err(ToJsonValueError::TimestampOutOfRange)
+        );
+        assert_eq!(
+            far.to_json_lossy(),
+            json!({"type": -1, "data": "AAAAAAAAADr/9EGA"})
+        );
+    }
+
+    #[test]
+    fn keys_and_floats() {
+        let v = Value::Map(vec![
+            (Value::from(1), Value::from(f64::NAN)),
+            (
+                Value::Array(vec![Value::from(1), Value::from("a")]),
+                Value::from(f64::NEG_INFINITY),
+            ),
+        ]);
+        assert_eq!(v.to_json_lossy(), json!({"1": null, "[1,\"a\"]": null}));
+        let config = ToJsonConfig::new().with_non_finite(NonFinitePolicy::String);
+        assert_eq!(
+            v.to_json(config).unwrap(),
+            json!({"1": "NaN", "[1,\"a\"]": "-Infinity"})
+        );
+    }
+
+    #[test]
+    fn matches_transcoder() {
+        let v = Value::Map(vec![
+            (Value::from("bin"), Value::Bin(vec![1, 2, 3, 4])),
+            (Value::Nil, timestamp(-1, 999_999_999)),
+            (
+                Value::from("ext"),
+                Value::Extension(ExtensionOwned::new(3, vec![9])),
+            ),
+        ]);
+        let buf = crate::to_vec(&v).unwrap();
+        let mut text = Vec::new();
+        crate::transcode::to_json(
+            &mut messagepack_core::io::SliceReader::new(&buf),
+            &mut text,
+            ToJsonConfig::new(),
+        )
+        .unwrap();
+        let transcoded: serde_json::Value = serde_json::from_slice(&text).unwrap();
+        assert_eq!(v.to_json_lossy(), transcoded);
+    }
+}
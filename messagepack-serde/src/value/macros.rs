@@ -0,0 +1,338 @@
+/// Construct a [`Value`](crate::value::Value) from a JSON-like literal.
+///
+/// - `nil`, `true` and `false` map to the corresponding values.
+/// - String literals become `String`, and byte string literals such as
+///   `b"\x01\x02"` become `Bin`.
+/// - `[...]` builds an array and `{key: value, ...}` builds a map. Keys may
+///   be any value, not only strings.
+/// - `ext(type, bytes)` builds an `Extension` from an `i8` type and anything
+///   that is `AsRef<[u8]>`.
+/// - Any other expression is converted with [`to_value`](crate::value::to_value)
+///   and must implement `Serialize`. Wrap multi-token keys in parentheses.
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_serde::{Value, msgpack};
+///
+/// let name = "alice";
+/// let v = msgpack!({
+///     "name": name,
+///     "tags": ["a", "b"],
+///     "raw": b"\x00\x01",
+///     "pos": ext(5, [0x01, 0x02]),
+///     1: nil,
+/// });
+///
+/// assert_eq!(v["name"], Value::from("alice"));
+/// assert_eq!(v["tags"][1], Value::from("b"));
+/// assert_eq!(v["raw"].as_bin(), Some(&[0x00, 0x01][..]));
+/// assert_eq!(v["pos"].as_extension().unwrap().r#type, 5);
+/// assert_eq!(v.pointer("/1"), Some(&Value::Nil));
+/// ```
+#[macro_export]
+macro_rules! msgpack {
+    ($($tt:tt)+) => {
+        $crate::__msgpack_internal!($($tt)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __msgpack_internal {
+    //////////////////////////////////////////////////////////////////////
+    // Array elements, accumulated in `[...]` as expressions.
+    //////////////////////////////////////////////////////////////////////
+
+    // Done with trailing comma.
+    (@array [$($elems:expr,)*]) => {
+        $crate::value::__private::vec![$($elems,)*]
+    };
+
+    // Done without trailing comma.
+    (@array [$($elems:expr),*]) => {
+        $crate::value::__private::vec![$($elems),*]
+    };
+
+    (@array [$($elems:expr,)*] nil $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!(nil)] $($rest)*)
+    };
+
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!([$($array)*])] $($rest)*)
+    };
+
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!({$($map)*})] $($rest)*)
+    };
+
+    (@array [$($elems:expr,)*] ext($($args:tt)*) $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!(ext($($args)*))] $($rest)*)
+    };
+
+    // Next element is an expression followed by comma.
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!($next),] $($rest)*)
+    };
+
+    // Last element is an expression with no trailing comma.
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::__msgpack_internal!(@array [$($elems,)* $crate::__msgpack_internal!($last)])
+    };
+
+    // Comma after the most recent element.
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    (@array [$($elems:expr),*] $unexpected:tt $($rest:tt)*) => {
+        ::core::compile_error!("unexpected token in msgpack! array")
+    };
+
+    //////////////////////////////////////////////////////////////////////
+    // Map entries, accumulated in `[...]` as `(key, value)` expressions.
+    // The key is collected token by token in `(...)` until a `:`.
+    //////////////////////////////////////////////////////////////////////
+
+    // Done.
+    (@map [$($entries:expr,)*] () ()) => {
+        $crate::value::Value::Map($crate::value::__private::vec![$($entries,)*])
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: nil $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!(nil)) ($($rest)*))
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!([$($array)*])) ($($rest)*))
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!({$($map)*})) ($($rest)*))
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: ext($($args:tt)*) $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!(ext($($args)*))) ($($rest)*))
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: $value:expr , $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!($value)) (, $($rest)*))
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (: $value:expr)) => {
+        $crate::__msgpack_internal!(@entry [$($entries,)*] ($($key)+) ($crate::__msgpack_internal!($value)) ())
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) (:)) => {
+        ::core::compile_error!("missing value for msgpack! map key")
+    };
+
+    (@map [$($entries:expr,)*] ($($key:tt)+) ()) => {
+        ::core::compile_error!("expected `:` after msgpack! map key")
+    };
+
+    // Munch one more token of the key.
+    (@map [$($entries:expr,)*] ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@map [$($entries,)*] ($($key)* $tt) ($($rest)*))
+    };
+
+    // Finish an entry and continue with the next key.
+    (@entry [$($entries:expr,)*] ($($key:tt)+) ($value:expr) ()) => {
+        $crate::__msgpack_internal!(@map [$($entries,)* ($crate::__msgpack_internal!($($key)+), $value),] () ())
+    };
+
+    (@entry [$($entries:expr,)*] ($($key:tt)+) ($value:expr) (, $($rest:tt)*)) => {
+        $crate::__msgpack_internal!(@map [$($entries,)* ($crate::__msgpack_internal!($($key)+), $value),] () ($($rest)*))
+    };
+
+    (@entry [$($entries:expr,)*] ($($key:tt)+) ($value:expr) ($unexpected:tt $($rest:tt)*)) => {
+        ::core::compile_error!("unexpected token after msgpack! map value")
+    };
+
+    //////////////////////////////////////////////////////////////////////
+    // Values.
+    //////////////////////////////////////////////////////////////////////
+
+    (nil) => {
+        $crate::value::Value::Nil
+    };
+
+    ([]) => {
+        $crate::value::Value::Array($crate::value::__private::vec![])
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::value::Value::Array($crate::__msgpack_internal!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::value::Value::Map($crate::value::__private::vec![])
+    };
+
+    ({ $($tt:tt)+ }) => {
+        $crate::__msgpack_internal!(@map [] () ($($tt)+))
+    };
+
+    (ext($type:expr, $data:expr $(,)?)) => {
+        $crate::value::__private::ext($type, $data)
+    };
+
+    ($lit:literal) => {
+        $crate::value::__private::Literal::into_value($lit)
+    };
+
+    ($other:expr) => {
+        $crate::value::to_value(&$other).unwrap()
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use super::super::{Number, Value};
+    use alloc::string::{String, ToString};
+    use messagepack_core::extension::ExtensionOwned;
+
+    pub use alloc::vec;
+
+    /// Conversion for literal tokens, so that byte strings become `Bin`.
+    pub trait Literal {
+        fn into_value(self) -> Value;
+    }
+
+    impl Literal for &str {
+        fn into_value(self) -> Value {
+            Value::String(self.into())
+        }
+    }
+
+    impl Literal for char {
+        fn into_value(self) -> Value {
+            Value::String(self.to_string())
+        }
+    }
+
+    impl<const N: usize> Literal for &[u8; N] {
+        fn into_value(self) -> Value {
+            Value::Bin(self.to_vec())
+        }
+    }
+
+    impl Literal for bool {
+        fn into_value(self) -> Value {
+            Value::Bool(self)
+        }
+    }
+
+    macro_rules! impl_literal_num {
+        ($($ty:ty),*) => {
+            $(
+                impl Literal for $ty {
+                    fn into_value(self) -> Value {
+                        Value::Number(Number::from(self))
+                    }
+                }
+            )*
+        };
+    }
+    impl_literal_num!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+    impl Literal for String {
+        fn into_value(self) -> Value {
+            Value::String(self)
+        }
+    }
+
+    pub fn ext(r#type: i8, data: impl AsRef<[u8]>) -> Value {
+        Value::Extension(ExtensionOwned::new(r#type, data.as_ref().to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{Number, Value, to_value};
+    use alloc::{string::String, vec};
+    use messagepack_core::extension::ExtensionOwned;
+
+    #[test]
+    fn scalars() {
+        assert_eq!(msgpack!(nil), Value::Nil);
+        assert_eq!(msgpack!(true), Value::Bool(true));
+        assert_eq!(msgpack!(1), Value::Number(Number::PositiveInt(1)));
+        assert_eq!(msgpack!(-1), Value::Number(Number::NegativeInt(-1)));
+        assert_eq!(msgpack!(1.5), Value::Number(Number::Float(1.5)));
+        assert_eq!(msgpack!("a"), Value::String("a".into()));
+        assert_eq!(msgpack!('c'), Value::String("c".into()));
+        assert_eq!(msgpack!(b"\x01\x02"), Value::Bin(vec![1, 2]));
+        assert_eq!(
+            msgpack!(ext(-1, [0u8; 4])),
+            Value::Extension(ExtensionOwned::new(-1, vec![0; 4]))
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let v = msgpack!({
+            "a": [1, [], {}, nil, ext(3, b"x")],
+            "b": { "c": b"yz" },
+            7: "seven",
+            b"k": false
+        });
+
+        let expected = Value::Map(vec![
+            (
+                Value::from("a"),
+                Value::Array(vec![
+                    Value::from(1u8),
+                    Value::Array(vec![]),
+                    Value::Map(vec![]),
+                    Value::Nil,
+                    Value::Extension(ExtensionOwned::new(3, vec![b'x'])),
+                ]),
+            ),
+            (
+                Value::from("b"),
+                Value::Map(vec![(Value::from("c"), Value::Bin(vec![b'y', b'z']))]),
+            ),
+            (Value::from(7u8), Value::from("seven")),
+            (Value::Bin(vec![b'k']), Value::Bool(false)),
+        ]);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn interpolation() {
+        #[derive(serde::Serialize)]
+        struct P {
+            x: u8,
+        }
+
+        let key = String::from("k");
+        let inner = msgpack!([1, 2]);
+        let n = 5u16;
+        let v = msgpack!({
+            (key.clone()): n + 1,
+            "p": P { x: 1 },
+            "inner": inner.clone(),
+            "list": [n, key.as_str(), Some(1u8), None::<u8>],
+        });
+
+        assert_eq!(v["k"], Value::from(6u16));
+        assert_eq!(v["p"], to_value(P { x: 1 }).unwrap());
+        assert_eq!(v["inner"], inner);
+        assert_eq!(
+            v["list"],
+            Value::Array(vec![
+                Value::from(5u16),
+                Value::from("k"),
+                Value::from(1u8),
+                Value::Nil,
+            ])
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        assert_eq!(msgpack!([1, 2,]), msgpack!([1, 2]));
+        assert_eq!(msgpack!({ "a": 1, }), msgpack!({ "a": 1 }));
+    }
+}
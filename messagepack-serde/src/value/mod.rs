@@ -74,14 +74,54 @@ pub use value_owned::Value;
 mod number;
 pub use number::Number;
 
+#[cfg(feature = "alloc")]
+pub(crate) mod index;
+#[cfg(feature = "alloc")]
+pub use index::Index;
+
+mod raw;
+pub(crate) use raw::RAW_VALUE_STRUCT_NAME;
+pub use raw::Raw;
+#[cfg(feature = "alloc")]
+pub use raw::RawBuf;
+
 #[cfg(feature = "alloc")]
 mod de;
 
+#[cfg(feature = "alloc")]
+mod macros;
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub use macros::__private;
+
+#[cfg(feature = "alloc")]
+mod native;
+
+#[cfg(feature = "alloc")]
+mod registry;
+#[cfg(feature = "alloc")]
+pub use registry::ExtensionRegistry;
+
 #[cfg(feature = "alloc")]
 mod ser;
 #[cfg(feature = "alloc")]
 pub use ser::to_value;
 
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::ToJsonValueError;
+
+/// Interpret an extension as a timestamp in any of the three layouts.
+#[cfg(feature = "alloc")]
+fn timestamp_from_extension(
+    ext: messagepack_core::extension::ExtensionRef<'_>,
+) -> Option<messagepack_core::timestamp::Timestamp96> {
+    messagepack_core::timestamp::Timestamp::try_from(ext)
+        .ok()
+        .map(Into::into)
+}
+
 #[cfg(feature = "alloc")]
 fn cautiously_size_hint<T>(hint: Option<usize>) -> usize {
     const MAX_ALLOC_BYTES: usize = 1024 * 1024;
@@ -0,0 +1,134 @@
+//! Conversions between these values and [`messagepack_core::value`].
+
+use super::{Value, ValueRef};
+use messagepack_core::value as native;
+
+impl From<native::Value> for Value {
+    fn from(v: native::Value) -> Self {
+        match v {
+            native::Value::Nil => Value::Nil,
+            native::Value::Bool(b) => Value::Bool(b),
+            native::Value::Bin(b) => Value::Bin(b),
+            native::Value::Extension(ext) => Value::Extension(ext),
+            native::Value::Number(n) => Value::Number(n.into()),
+            native::Value::String(s) => Value::String(s),
+            native::Value::Array(items) => {
+                Value::Array(items.into_iter().map(Into::into).collect())
+            }
+            native::Value::Map(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Value> for native::Value {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Nil => native::Value::Nil,
+            Value::Bool(b) => native::Value::Bool(b),
+            Value::Bin(b) => native::Value::Bin(b),
+            Value::Extension(ext) => native::Value::Extension(ext),
+            Value::Number(n) => native::Value::Number(n.into()),
+            Value::String(s) => native::Value::String(s),
+            Value::Array(items) => {
+                native::Value::Array(items.into_iter().map(Into::into).collect())
+            }
+            Value::Map(entries) => native::Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<native::ValueRef<'a>> for ValueRef<'a> {
+    fn from(v: native::ValueRef<'a>) -> Self {
+        match v {
+            native::ValueRef::Nil => ValueRef::Nil,
+            native::ValueRef::Bool(b) => ValueRef::Bool(b),
+            native::ValueRef::Bin(b) => ValueRef::Bin(b),
+            native::ValueRef::Extension(ext) => ValueRef::Extension(ext),
+            native::ValueRef::Number(n) => ValueRef::Number(n.into()),
+            native::ValueRef::String(s) => ValueRef::String(s),
+            native::ValueRef::Array(items) => {
+                ValueRef::Array(items.into_iter().map(Into::into).collect())
+            }
+            native::ValueRef::Map(entries) => ValueRef::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for native::ValueRef<'a> {
+    fn from(v: ValueRef<'a>) -> Self {
+        match v {
+            ValueRef::Nil => native::ValueRef::Nil,
+            ValueRef::Bool(b) => native::ValueRef::Bool(b),
+            ValueRef::Bin(b) => native::ValueRef::Bin(b),
+            ValueRef::Extension(ext) => native::ValueRef::Extension(ext),
+            ValueRef::Number(n) => native::ValueRef::Number(n.into()),
+            ValueRef::String(s) => native::ValueRef::String(s),
+            ValueRef::Array(items) => {
+                native::ValueRef::Array(items.into_iter().map(Into::into).collect())
+            }
+            ValueRef::Map(entries) => native::ValueRef::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for native::ValueRef<'a> {
+    fn from(v: &'a Value) -> Self {
+        match v {
+            Value::Nil => native::ValueRef::Nil,
+            Value::Bool(b) => native::ValueRef::Bool(*b),
+            Value::Bin(b) => native::ValueRef::Bin(b),
+            Value::Extension(ext) => native::ValueRef::Extension(ext.as_ref()),
+            Value::Number(n) => native::ValueRef::Number((*n).into()),
+            Value::String(s) => native::ValueRef::String(s),
+            Value::Array(items) => native::ValueRef::Array(items.iter().map(Into::into).collect()),
+            Value::Map(entries) => {
+                native::ValueRef::Map(entries.iter().map(|(k, v)| (k.into(), v.into())).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messagepack_core::{Decode, io::SliceReader};
+
+    #[test]
+    fn native_decoded_matches_serde() {
+        let v = crate::msgpack!({
+            "a": [1, -1, 0.1, nil],
+            "b": b"\x01\x02",
+            3: ext(5, [0x00]),
+        });
+        let buf = crate::to_vec(&v).unwrap();
+
+        let n = native::Value::decode(&mut SliceReader::new(&buf)).unwrap();
+        assert_eq!(Value::from(n.clone()), v);
+        assert_eq!(native::Value::from(v), n);
+
+        let r = crate::from_slice::<ValueRef<'_>>(&buf).unwrap();
+        let nr = native::ValueRef::decode(&mut SliceReader::new(&buf)).unwrap();
+        assert_eq!(ValueRef::from(nr.clone()), r);
+        assert_eq!(native::ValueRef::from(r), nr);
+    }
+}
@@ -1,3 +1,4 @@
+use messagepack_core::value as native;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
 
 /// Represents any number, it could be int or float.
@@ -23,7 +24,18 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
 /// let data = from_slice::<Data>(buf).unwrap();
 /// assert_eq!(data.num,Number::Float(1.5));
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+///
+/// ## Equality and ordering
+///
+/// `Number` implements [`Eq`], [`Ord`] and [`Hash`](core::hash::Hash) by
+/// comparing the [`messagepack_core::value::Number`] it converts to, with
+/// `Float` in the place of `Float64`. See there for the rules.
+///
+/// This replaced the derived `PartialEq`/`PartialOrd`, under which `NaN`
+/// was unequal to itself, `-0.0 == 0.0`, and `PositiveInt` sorted before
+/// `NegativeInt`. [`Value`](super::Value) and [`ValueRef`](super::ValueRef)
+/// compare their numbers the same way.
+#[derive(Debug, Clone, Copy)]
 pub enum Number {
     /// Always positive
     PositiveInt(u64),
@@ -92,6 +104,55 @@ impl Number {
     }
 }
 
+impl From<native::Number> for Number {
+    fn from(n: native::Number) -> Self {
+        match n {
+            native::Number::PositiveInt(v) => Number::PositiveInt(v),
+            native::Number::NegativeInt(v) => Number::NegativeInt(v),
+            native::Number::Float32(v) => Number::Float(v.into()),
+            native::Number::Float64(v) => Number::Float(v),
+        }
+    }
+}
+
+/// Floats always become [`native::Number::Float64`], since [`Number`] does
+/// not keep the original width.
+impl From<Number> for native::Number {
+    fn from(n: Number) -> Self {
+        match n {
+            Number::PositiveInt(v) => native::Number::PositiveInt(v),
+            Number::NegativeInt(v) => native::Number::NegativeInt(v),
+            Number::Float(v) => native::Number::Float64(v),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        native::Number::from(*self).cmp(&native::Number::from(*other))
+    }
+}
+
+impl core::hash::Hash for Number {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        native::Number::from(*self).hash(state);
+    }
+}
+
 impl From<u64> for Number {
     fn from(value: u64) -> Self {
         Number::PositiveInt(value)
@@ -208,8 +269,55 @@ impl<'de> Deserialize<'de> for Number {
 mod tests {
     use super::*;
     use crate::from_slice;
+    use core::cmp::Ordering;
     use rstest::rstest;
 
+    fn hash_of(n: &Number) -> u64 {
+        use core::hash::{Hash, Hasher};
+        let mut hasher = std::hash::DefaultHasher::new();
+        n.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[rstest]
+    #[case(Number::NegativeInt(-2), Number::NegativeInt(-1), Ordering::Less)]
+    #[case(Number::NegativeInt(-1), Number::PositiveInt(0), Ordering::Less)]
+    #[case(
+        Number::PositiveInt(u64::MAX),
+        Number::Float(f64::NEG_INFINITY),
+        Ordering::Less
+    )]
+    #[case(Number::PositiveInt(1), Number::Float(1.0), Ordering::Less)]
+    #[case(Number::Float(-0.0), Number::Float(0.0), Ordering::Less)]
+    #[case(Number::Float(f64::INFINITY), Number::Float(f64::NAN), Ordering::Less)]
+    #[case(Number::Float(f64::NAN), Number::Float(f64::NAN), Ordering::Equal)]
+    #[case(Number::PositiveInt(3), Number::PositiveInt(3), Ordering::Equal)]
+    fn total_order(#[case] a: Number, #[case] b: Number, #[case] expected: Ordering) {
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(b.cmp(&a), expected.reverse());
+        assert_eq!(a.partial_cmp(&b), Some(expected));
+        assert_eq!(a == b, expected.is_eq());
+        if expected.is_eq() {
+            assert_eq!(hash_of(&a), hash_of(&b));
+        }
+    }
+
+    /// Pairs whose comparison changed when the derived `PartialOrd` was
+    /// replaced.
+    #[rstest]
+    #[case(Number::Float(f64::NAN), Number::Float(f64::NAN), Ordering::Equal)]
+    #[case(Number::Float(-0.0), Number::Float(0.0), Ordering::Less)]
+    #[case(Number::NegativeInt(-1), Number::PositiveInt(0), Ordering::Less)]
+    fn comparison_changed_from_derived(
+        #[case] a: Number,
+        #[case] b: Number,
+        #[case] expected: Ordering,
+    ) {
+        assert_eq!(a.partial_cmp(&b), Some(expected));
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(a == b, expected.is_eq());
+    }
+
     #[rstest]
     #[case([0x05],5)]
     #[case([0xcd, 0xff, 0xff],u16::MAX.into())]
@@ -246,4 +354,11 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn float32_widens() {
+        let n = Number::from(native::Number::Float32(1.5));
+        assert_eq!(n, Number::Float(1.5));
+        assert_eq!(native::Number::from(n), native::Number::Float64(1.5));
+    }
 }
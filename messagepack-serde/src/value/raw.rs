@@ -0,0 +1,429 @@
+use messagepack_core::{
+    Format,
+    decode::DecodeBorrowed,
+    io::{RError, SliceReader},
+};
+use serde::{Deserialize, Serialize, de};
+
+use crate::de::Error;
+
+pub(crate) const RAW_VALUE_STRUCT_NAME: &str = "$__MSGPACK_RAW_VALUE_STRUCT";
+
+/// Check that `bytes` holds exactly one complete MessagePack value.
+fn validate(bytes: &[u8]) -> Result<(), Error<RError>> {
+    let mut reader = SliceReader::new(bytes);
+    let format = Format::decode_borrowed(&mut reader)?;
+    crate::de::skip_value(format, &mut reader)?;
+    if reader.rest().is_empty() {
+        Ok(())
+    } else {
+        Err(de::Error::custom("trailing bytes after raw value"))
+    }
+}
+
+/// Encoded bytes of a single MessagePack value, borrowed from the input.
+///
+/// Deserializing a `Raw` skips over the next value and captures its exact
+/// bytes without decoding them, and serializing it writes those bytes back
+/// verbatim. This is useful for routing or storing payloads that are only
+/// partially understood.
+///
+/// Borrowing requires a reader backed by a slice, such as
+/// [`from_slice`](crate::from_slice). Use [`RawBuf`] with other readers, or
+/// to capture part of an already decoded [`Value`](crate::value::Value).
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_serde::value::Raw;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Envelope<'a> {
+///     kind: u8,
+///     #[serde(borrow)]
+///     payload: Raw<'a>,
+/// }
+///
+/// // {"kind": 1, "payload": [true, "a"]}
+/// let buf: &[u8] = &[
+///     0x82, 0xa4, b'k', b'i', b'n', b'd', 0x01, 0xa7, b'p', b'a', b'y', b'l', b'o', b'a', b'd',
+///     0x92, 0xc3, 0xa1, 0x61,
+/// ];
+/// let envelope = messagepack_serde::from_slice::<Envelope>(buf).unwrap();
+/// assert_eq!(envelope.payload.as_bytes(), &[0x92, 0xc3, 0xa1, 0x61]);
+///
+/// let (flag, s) = envelope.payload.decode::<(bool, &str)>().unwrap();
+/// assert!(flag);
+/// assert_eq!(s, "a");
+///
+/// let mut out = [0u8; 32];
+/// let len = messagepack_serde::to_slice(&envelope, &mut out).unwrap();
+/// assert_eq!(&out[..len], buf);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Raw<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Raw<'a> {
+    /// Wrap `bytes`, checking that they hold exactly one MessagePack value.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error<RError>> {
+        validate(bytes)?;
+        Ok(Self { bytes })
+    }
+
+    /// Encoded bytes of the value.
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Deserialize the captured value as `T`.
+    pub fn decode<T>(&self) -> Result<T, Error<RError>>
+    where
+        T: Deserialize<'a>,
+    {
+        crate::from_slice(self.bytes)
+    }
+}
+
+impl AsRef<[u8]> for Raw<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl Serialize for Raw<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer
+            .serialize_newtype_struct(RAW_VALUE_STRUCT_NAME, &crate::extension::Bytes(self.bytes))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Raw<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct RawVisitor;
+        impl<'de> de::Visitor<'de> for RawVisitor {
+            type Value = Raw<'de>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("raw messagepack value")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let bytes = <&[u8]>::deserialize(deserializer)?;
+                Ok(Raw { bytes })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RAW_VALUE_STRUCT_NAME, RawVisitor)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod raw_buf {
+    use super::*;
+    use alloc::{boxed::Box, vec::Vec};
+
+    /// Owned encoded bytes of a single MessagePack value.
+    ///
+    /// This is the owned counterpart of [`Raw`] and can be deserialized from
+    /// any reader. It can also be deserialized from a
+    /// [`Value`](crate::value::Value) or [`ValueRef`](crate::value::ValueRef),
+    /// which re-encodes that subtree, so ints and lengths are minimized rather
+    /// than kept as in the original bytes.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")]
+    /// # fn main() {
+    /// use messagepack_serde::value::RawBuf;
+    ///
+    /// let buf: &[u8] = &[0x92, 0x01, 0x02];
+    /// let raw = messagepack_serde::from_reader::<_, RawBuf>(buf).unwrap();
+    /// assert_eq!(raw.as_bytes(), buf);
+    /// assert_eq!(raw.decode::<(u8, u8)>().unwrap(), (1, 2));
+    /// # }
+    /// # #[cfg(not(feature = "std"))]
+    /// # fn main() {}
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct RawBuf {
+        bytes: Box<[u8]>,
+    }
+
+    impl RawBuf {
+        /// Wrap `bytes`, checking that they hold exactly one MessagePack value.
+        pub fn new(bytes: Vec<u8>) -> Result<Self, Error<RError>> {
+            validate(&bytes)?;
+            Ok(Self {
+                bytes: bytes.into_boxed_slice(),
+            })
+        }
+
+        /// Encoded bytes of the value.
+        pub fn as_bytes(&self) -> &[u8] {
+            &self.bytes
+        }
+
+        /// Borrow as [`Raw`].
+        pub fn as_raw(&self) -> Raw<'_> {
+            Raw { bytes: &self.bytes }
+        }
+
+        /// Consume and return the encoded bytes.
+        pub fn into_vec(self) -> Vec<u8> {
+            self.bytes.into_vec()
+        }
+
+        /// Deserialize the captured value as `T`.
+        pub fn decode<'a, T>(&'a self) -> Result<T, Error<RError>>
+        where
+            T: Deserialize<'a>,
+        {
+            crate::from_slice(&self.bytes)
+        }
+    }
+
+    impl AsRef<[u8]> for RawBuf {
+        fn as_ref(&self) -> &[u8] {
+            &self.bytes
+        }
+    }
+
+    impl From<Raw<'_>> for RawBuf {
+        fn from(raw: Raw<'_>) -> Self {
+            Self {
+                bytes: raw.bytes.into(),
+            }
+        }
+    }
+
+    impl Serialize for RawBuf {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            self.as_raw().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for RawBuf {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct RawBufVisitor;
+            impl<'de> de::Visitor<'de> for RawBufVisitor {
+                type Value = RawBuf;
+
+                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                    formatter.write_str("raw messagepack value")
+                }
+
+                fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    let bytes = deserialize_byte_buf(deserializer)?;
+                    Ok(RawBuf {
+                        bytes: bytes.into_boxed_slice(),
+                    })
+                }
+            }
+
+            deserializer.deserialize_newtype_struct(RAW_VALUE_STRUCT_NAME, RawBufVisitor)
+        }
+    }
+
+    fn deserialize_byte_buf<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct BytesVisitor;
+        impl de::Visitor<'_> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("bytes")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(v.to_vec())
+            }
+        }
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+#[cfg(feature = "alloc")]
+pub use raw_buf::RawBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Envelope<'a> {
+        id: u8,
+        #[serde(borrow)]
+        body: Raw<'a>,
+    }
+
+    #[rstest]
+    #[case(&[0xc0])]
+    #[case(&[0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0])]
+    #[case(&[0xa3, 0x61, 0x62, 0x63])]
+    #[case(&[0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x91, 0xc2])]
+    #[case(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x01])]
+    fn raw_roundtrip_in_struct(#[case] body: &[u8]) {
+        // {"id": 7, "body": body}
+        let mut buf = vec![0x82, 0xa2, b'i', b'd', 0x07, 0xa4, b'b', b'o', b'd', b'y'];
+        buf.extend_from_slice(body);
+
+        let envelope = crate::from_slice::<Envelope>(&buf).unwrap();
+        assert_eq!(envelope.id, 7);
+        assert_eq!(envelope.body.as_bytes(), body);
+
+        let mut out = [0u8; 32];
+        let len = crate::to_slice(&envelope, &mut out).unwrap();
+        assert_eq!(&out[..len], buf.as_slice());
+    }
+
+    #[test]
+    fn raw_keeps_non_canonical_encoding() {
+        // uint32 holding 1, which a re-encode would shrink
+        let buf: &[u8] = &[0xce, 0x00, 0x00, 0x00, 0x01];
+        let raw = crate::from_slice::<Raw>(buf).unwrap();
+        assert_eq!(raw.decode::<u8>().unwrap(), 1);
+
+        let mut out = [0u8; 5];
+        let len = crate::to_slice(&raw, &mut out).unwrap();
+        assert_eq!(&out[..len], buf);
+    }
+
+    #[test]
+    fn raw_inside_option() {
+        let raw = crate::from_slice::<Option<Raw>>(&[0x91, 0x01]).unwrap();
+        assert_eq!(raw.unwrap().as_bytes(), &[0x91, 0x01]);
+
+        let none = crate::from_slice::<Option<Raw>>(&[0xc0]).unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn raw_in_stream() {
+        let buf: &[u8] = &[0x01, 0x91, 0xc0, 0xa1, 0x61];
+        let stream = crate::de::StreamDeserializer::<_, Raw>::new(SliceReader::new(buf));
+        let values = stream.collect::<Result<Vec<_>, _>>().unwrap();
+        let bytes = values.iter().map(Raw::as_bytes).collect::<Vec<_>>();
+        assert_eq!(bytes, [&buf[..1], &buf[1..3], &buf[3..]]);
+    }
+
+    #[rstest]
+    #[case(&[0x92, 0x01])]
+    #[case(&[0x01, 0x02])]
+    #[case(&[])]
+    fn raw_new_rejects_invalid(#[case] buf: &[u8]) {
+        assert!(Raw::new(buf).is_err());
+    }
+
+    #[test]
+    fn raw_truncated_is_error() {
+        assert!(crate::from_slice::<Raw>(&[0x92, 0x01]).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn raw_buf_from_std_reader() {
+        let buf: &[u8] = &[0x92, 0xa1, 0x61, 0x81, 0x01, 0xc0];
+        let raw = crate::from_reader::<_, RawBuf>(buf).unwrap();
+        assert_eq!(raw.as_bytes(), buf);
+        assert_eq!(
+            crate::to_vec(&raw).unwrap(),
+            buf,
+            "raw bytes must be written back verbatim"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn borrowed_raw_from_std_reader_is_error() {
+        use messagepack_core::io::StdReader;
+
+        let reader = StdReader::new(std::io::Cursor::new(vec![0x01]));
+        assert!(crate::de::from_core_reader::<_, Raw>(reader).is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn raw_buf_in_option_from_copying_reader() {
+        use messagepack_core::io::IterReader;
+
+        let reader = IterReader::new([0x92, 0x01, 0x02].into_iter());
+        let raw = crate::de::from_core_reader::<_, Option<RawBuf>>(reader)
+            .unwrap()
+            .unwrap();
+        assert_eq!(raw.as_bytes(), &[0x92, 0x01, 0x02]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn raw_to_value_decodes_contents() {
+        use crate::value::{Value, to_value};
+
+        let raw = Raw::new(&[0x92, 0x01, 0xc3]).unwrap();
+        let v = to_value(raw).unwrap();
+        assert_eq!(v, Value::Array(vec![Value::from(1u8), Value::from(true)]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn raw_buf_from_value_reencodes() {
+        use crate::value::{Value, ValueRef};
+
+        #[derive(Debug, Deserialize)]
+        struct Owned {
+            id: u8,
+            body: RawBuf,
+        }
+
+        // {"id": 1, "body": [300, "a"]} with a non-minimal uint 32
+        let buf: &[u8] = &[
+            0x82, 0xa2, b'i', b'd', 0x01, 0xa4, b'b', b'o', b'd', b'y', 0x92, 0xce, 0x00, 0x00,
+            0x01, 0x2c, 0xa1, b'a',
+        ];
+        let minimized: &[u8] = &[0x92, 0xcd, 0x01, 0x2c, 0xa1, b'a'];
+
+        let value = crate::from_slice::<Value>(buf).unwrap();
+        let owned = Owned::deserialize(&value).unwrap();
+        assert_eq!(owned.id, 1);
+        assert_eq!(owned.body.as_bytes(), minimized);
+        assert_eq!(
+            Owned::deserialize(value.clone()).unwrap().body.as_bytes(),
+            minimized
+        );
+
+        let value_ref = crate::from_slice::<ValueRef<'_>>(buf).unwrap();
+        assert_eq!(
+            Owned::deserialize(&value_ref).unwrap().body.as_bytes(),
+            minimized
+        );
+
+        // The re-encoded bytes cannot be borrowed
+        assert!(Envelope::deserialize(&value).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Decode and encode ext payloads of a [`Value`] with user callbacks.
+
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+use messagepack_core::extension::{Extension, ExtensionOwned, ExtensionRef, TryFromExtensionError};
+use serde::{
+    Serialize, Serializer,
+    de::DeserializeSeed,
+    ser::{SerializeMap, SerializeSeq},
+};
+
+use super::{Value, value_owned::ValueSeed};
+
+type Decoder = dyn Fn(ExtensionRef<'_>) -> Result<Value, TryFromExtensionError> + Send + Sync;
+type Encoder = dyn Fn(&Value) -> Option<ExtensionOwned> + Send + Sync;
+
+/// Callbacks which turn ext payloads into richer [`Value`]s while decoding,
+/// and turn those values back into ext while encoding.
+///
+/// A registry is not global: pass [`ExtensionRegistry::seed`] to the
+/// deserializer that should use it, and wrap values with
+/// [`ExtensionRegistry::serializable`] when encoding. Ext types without a
+/// decoder stay [`Value::Extension`].
+///
+/// ## Example
+///
+/// ```rust
+/// use messagepack_core::extension::{ExtensionOwned, TryFromExtensionError};
+/// use messagepack_serde::value::{ExtensionRegistry, Value};
+///
+/// // Store ext 2 as a 16 byte UUID, and show it as a `{"uuid": bin}` map.
+/// let registry = ExtensionRegistry::new()
+///     .with_decoder(2, |ext| match ext.data.len() {
+///         16 => Ok(Value::Map(vec![(Value::from("uuid"), Value::Bin(ext.data.to_vec()))])),
+///         _ => Err(TryFromExtensionError::InvalidData),
+///     })
+///     .with_encoder(|value| match value.get("uuid")? {
+///         Value::Bin(id) => Some(ExtensionOwned::new(2, id.clone())),
+///         _ => None,
+///     });
+///
+/// let mut buf = vec![0x91, 0xd8, 0x02];
+/// buf.extend_from_slice(&[7; 16]);
+///
+/// let value = registry.from_slice(&buf).unwrap();
+/// assert_eq!(value[0]["uuid"], Value::Bin(vec![7; 16]));
+///
+/// let back = messagepack_serde::to_vec(&registry.serializable(&value)).unwrap();
+/// assert_eq!(back, buf);
+/// ```
+#[derive(Default)]
+pub struct ExtensionRegistry {
+    decoders: BTreeMap<i8, Box<Decoder>>,
+    encoders: Vec<Box<Encoder>>,
+}
+
+impl ExtensionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode ext of type `r#type` with `decoder`, replacing any earlier
+    /// decoder for that type.
+    pub fn with_decoder<F>(mut self, r#type: i8, decoder: F) -> Self
+    where
+        F: Fn(ExtensionRef<'_>) -> Result<Value, TryFromExtensionError> + Send + Sync + 'static,
+    {
+        self.decoders.insert(r#type, Box::new(decoder));
+        self
+    }
+
+    /// Encode values for which `encoder` returns `Some` as that ext.
+    ///
+    /// Encoders are tried in the order they were added, on every value in the
+    /// tree before its own encoding.
+    pub fn with_encoder<F>(mut self, encoder: F) -> Self
+    where
+        F: Fn(&Value) -> Option<ExtensionOwned> + Send + Sync + 'static,
+    {
+        self.encoders.push(Box::new(encoder));
+        self
+    }
+
+    /// Register an [`Extension`] type, mapping it to and from a [`Value`]
+    /// with `into_value` and `from_value`.
+    pub fn with_extension<T, I, F>(self, into_value: I, from_value: F) -> Self
+    where
+        T: Extension,
+        I: Fn(T) -> Value + Send + Sync + 'static,
+        F: Fn(&Value) -> Option<T> + Send + Sync + 'static,
+    {
+        self.with_decoder(T::TYPE, move |ext| {
+            T::decode_payload(ext.data).map(&into_value)
+        })
+        .with_encoder(move |value| {
+            let ext = from_value(value)?;
+            let mut data = Vec::new();
+            ext.encode_payload(&mut data).ok()?;
+            Some(ExtensionOwned::new(T::TYPE, data))
+        })
+    }
+
+    /// A [`DeserializeSeed`] producing a [`Value`] which uses this registry.
+    pub fn seed(&self) -> impl for<'de> DeserializeSeed<'de, Value = Value> + '_ {
+        ValueSeed(Some(self))
+    }
+
+    /// Deserialize a [`Value`] from `input` using this registry.
+    pub fn from_slice(
+        &self,
+        input: &[u8],
+    ) -> Result<Value, crate::de::Error<messagepack_core::io::RError>> {
+        crate::de::from_slice_seed(input, self.seed())
+    }
+
+    /// Wrap `value` so that serializing it runs the encoders of this registry.
+    pub fn serializable<'a>(&'a self, value: &'a Value) -> impl Serialize + 'a {
+        WithRegistry {
+            value,
+            registry: self,
+        }
+    }
+
+    pub(super) fn decode(&self, ext: ExtensionOwned) -> Result<Value, TryFromExtensionError> {
+        match self.decoders.get(&ext.r#type) {
+            Some(decoder) => decoder(ext.as_ref()),
+            None => Ok(Value::Extension(ext)),
+        }
+    }
+
+    fn encode(&self, value: &Value) -> Option<ExtensionOwned> {
+        self.encoders.iter().find_map(|encoder| encoder(value))
+    }
+}
+
+impl fmt::Debug for ExtensionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtensionRegistry")
+            .field("decoders", &self.decoders.keys())
+            .field("encoders", &self.encoders.len())
+            .finish()
+    }
+}
+
+struct WithRegistry<'a> {
+    value: &'a Value,
+    registry: &'a ExtensionRegistry,
+}
+
+impl Serialize for WithRegistry<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(ext) = self.registry.encode(self.value) {
+            return crate::extension::ext_ref::serialize(&ext.as_ref(), serializer);
+        }
+        let wrap = |value| WithRegistry {
+            value,
+            registry: self.registry,
+        };
+        match self.value {
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&wrap(item))?;
+                }
+                seq.end()
+            }
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(&wrap(k), &wrap(v))?;
+                }
+                map.end()
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use messagepack_core::{encode, io::IoWrite, timestamp::Timestamp};
+    use rstest::rstest;
+
+    #[derive(Debug, PartialEq)]
+    struct Point(i8, i8);
+
+    impl Extension for Point {
+        const TYPE: i8 = 7;
+
+        fn encode_payload<W: IoWrite>(
+            &self,
+            writer: &mut W,
+        ) -> Result<(), encode::Error<W::Error>> {
+            writer.write(&[self.0 as u8, self.1 as u8])?;
+            Ok(())
+        }
+
+        fn decode_payload(data: &[u8]) -> Result<Self, TryFromExtensionError> {
+            match data {
+                &[x, y] => Ok(Point(x as i8, y as i8)),
+                _ => Err(TryFromExtensionError::InvalidData),
+            }
+        }
+    }
+
+    fn registry() -> ExtensionRegistry {
+        ExtensionRegistry::new()
+            .with_extension(
+                |Point(x, y)| Value::Array(vec![Value::from("point"), x.into(), y.into()]),
+                |value| match value.as_array()? {
+                    [tag, x, y] if tag.as_string() == Some("point") => Some(Point(
+                        x.as_number()?.as_signed_int()?.try_into().ok()?,
+                        y.as_number()?.as_signed_int()?.try_into().ok()?,
+                    )),
+                    _ => None,
+                },
+            )
+            .with_extension(|ts: Timestamp| Value::from(ts.seconds()), |_| None)
+    }
+
+    #[rstest]
+    fn decodes_registered_types_and_keeps_others() {
+        let input = Value::Map(vec![
+            (
+                Value::from("at"),
+                Value::Extension(ExtensionOwned::new(7, vec![1, 0xff])),
+            ),
+            (
+                Value::Extension(ExtensionOwned::new(-1, vec![0, 0, 0, 9])),
+                Value::Extension(ExtensionOwned::new(5, vec![1])),
+            ),
+        ]);
+        let buf = crate::to_vec(&input).unwrap();
+
+        let value = registry().from_slice(&buf).unwrap();
+        assert_eq!(
+            value,
+            Value::Map(vec![
+                (
+                    Value::from("at"),
+                    Value::Array(vec![Value::from("point"), 1.into(), (-1).into()])
+                ),
+                (
+                    Value::from(9),
+                    Value::Extension(ExtensionOwned::new(5, vec![1]))
+                ),
+            ])
+        );
+
+        // Without a registry nothing changes.
+        assert_eq!(crate::from_slice::<Value>(&buf).unwrap(), input);
+    }
+
+    #[rstest]
+    fn seed_works_with_any_deserializer() {
+        let input = Value::Array(vec![Value::Extension(ExtensionOwned::new(7, vec![2, 3]))]);
+        let value = registry().seed().deserialize(&input).unwrap();
+        assert_eq!(value[0][0], Value::from("point"));
+    }
+
+    #[rstest]
+    fn encoders_turn_values_back_into_ext() {
+        let registry = registry();
+        let value = Value::Map(vec![(
+            Value::from("at"),
+            Value::Array(vec![Value::from("point"), 4.into(), 5.into()]),
+        )]);
+        let buf = crate::to_vec(&registry.serializable(&value)).unwrap();
+
+        let mut expected = vec![0x81, 0xa2, b'a', b't'];
+        expected.extend_from_slice(&[0xd5, 7, 4, 5]);
+        assert_eq!(buf, expected);
+        assert_eq!(registry.from_slice(&buf).unwrap(), value);
+    }
+
+    #[rstest]
+    fn invalid_payload_is_an_error() {
+        let buf = [0xd4, 7, 0];
+        let err = registry().from_slice(&buf).unwrap_err();
+        assert!(err.to_string().contains("invalid extension payload"));
+    }
+}
@@ -124,6 +124,10 @@ impl ser::Serializer for Serializer {
                 value.serialize(&mut ser)?;
                 ser.into_value()
             }
+            crate::value::RAW_VALUE_STRUCT_NAME => match value.serialize(self)? {
+                Value::Bin(bytes) => crate::from_slice(&bytes).map_err(ser::Error::custom),
+                _ => Err(ser::Error::custom("raw value must serialize as bytes")),
+            },
             _ => value.serialize(self),
         }
     }
@@ -1,12 +1,23 @@
 use super::number::Number;
+use super::registry::ExtensionRegistry;
 use super::value_ref::ValueRef;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use messagepack_core::extension::{ExtensionOwned, ExtensionRef};
-use serde::{de::Visitor, ser::SerializeMap};
+use messagepack_core::extension::{ExtensionOwned, ExtensionRef, FixedExtension};
+use messagepack_core::notation::{Emitter, Style};
+use messagepack_core::timestamp::{Timestamp, Timestamp32, Timestamp64, Timestamp96};
+use serde::{
+    de::{DeserializeSeed, Visitor},
+    ser::SerializeMap,
+};
 
 /// Owned representation of any MessagePack value.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+///
+/// `Value` implements [`Eq`], [`Ord`] and [`Hash`](core::hash::Hash).
+/// Values of different variants are ordered by variant in declaration order,
+/// and values of the same variant by their contents, with maps and arrays
+/// compared element by element. See [`Number`] for numeric semantics.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Value {
     /// Represents nil format.
     Nil,
@@ -56,6 +67,15 @@ impl Value {
         }
     }
 
+    /// If the `Value` is a timestamp ext, returns it as [`Timestamp96`].
+    ///
+    /// All of the timestamp 32, 64 and 96 layouts are recognized. The `Value`
+    /// itself keeps the original bytes, so re-encoding is lossless.
+    pub fn as_timestamp(&self) -> Option<Timestamp96> {
+        self.as_extension()
+            .and_then(super::timestamp_from_extension)
+    }
+
     /// If the `Value` is number, returns contained value.
     pub fn as_number(&self) -> Option<Number> {
         match self {
@@ -87,6 +107,119 @@ impl Value {
             _ => None,
         }
     }
+
+    /// If the `Value` is bin, returns a mutable reference to the bytes.
+    pub fn as_bin_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            Value::Bin(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is ext, returns a mutable reference to it.
+    pub fn as_extension_mut(&mut self) -> Option<&mut ExtensionOwned> {
+        match self {
+            Value::Extension(ext) => Some(ext),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is str, returns a mutable reference to the string.
+    pub fn as_string_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is array, returns a mutable reference to the elements.
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is map, returns a mutable reference to the pairs.
+    pub fn as_map_mut(&mut self) -> Option<&mut Vec<(Value, Value)>> {
+        match self {
+            Value::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Index into an array or map.
+    ///
+    /// Returns `None` if the entry does not exist or `self` has the wrong
+    /// type. Map lookups return the first entry whose key matches.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use messagepack_serde::Value;
+    ///
+    /// let v = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::from(1u8)]))]);
+    /// assert_eq!(v.get("a").and_then(|a| a.get(0)), Some(&Value::from(1u8)));
+    /// assert_eq!(v.get("b"), None);
+    /// assert_eq!(v["a"][0], Value::from(1u8));
+    /// assert_eq!(v["b"], Value::Nil);
+    /// ```
+    pub fn get<I: super::Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Mutably index into an array or map. See [`Value::get`].
+    pub fn get_mut<I: super::Index>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    /// Take the value out, leaving [`Value::Nil`] in its place.
+    pub fn take(&mut self) -> Value {
+        core::mem::replace(self, Value::Nil)
+    }
+
+    /// Insert an entry into a map, returning the previous value of `key`.
+    ///
+    /// A nil `Value` is turned into an empty map first. If `key` already
+    /// exists, the first matching entry is updated in place.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `self` is neither nil nor a map.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Value>
+    where
+        K: Into<Value>,
+        V: Into<Value>,
+    {
+        if self.is_nil() {
+            *self = Value::Map(Vec::new());
+        }
+        let Value::Map(entries) = self else {
+            panic!("cannot insert into a non-map value");
+        };
+
+        let key = key.into();
+        let value = value.into();
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(core::mem::replace(v, value)),
+            None => {
+                entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove an array element or the first matching map entry and return it.
+    ///
+    /// Returns `None` if the entry does not exist or `self` has the wrong type.
+    pub fn remove<I: super::Index>(&mut self, index: I) -> Option<Value> {
+        index.remove_from(self)
+    }
+
+    /// Iterate over the keys of a map, in order. Yields nothing for non-map values.
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.as_map().unwrap_or_default().iter().map(|(k, _)| k)
+    }
 }
 
 impl serde::Serialize for Value {
@@ -115,129 +248,191 @@ impl serde::Serialize for Value {
     }
 }
 
+/// Formats the value in the [notation](messagepack_core::notation). Use
+/// `{:#}` for the pretty printed layout.
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let style = Style::of(f);
+        self.emit(&mut Emitter::new(f, style))
+    }
+}
+
+impl Value {
+    fn emit<W: core::fmt::Write>(&self, emitter: &mut Emitter<'_, W>) -> core::fmt::Result {
+        match self {
+            Value::Nil => emitter.nil(),
+            Value::Bool(b) => emitter.bool(*b),
+            Value::Bin(b) => emitter.bin(b),
+            Value::Extension(ext) => emitter.ext(ext.as_ref()),
+            Value::Number(Number::PositiveInt(n)) => emitter.uint(*n),
+            Value::Number(Number::NegativeInt(n)) => emitter.int(*n),
+            Value::Number(Number::Float(n)) => emitter.f64(*n),
+            Value::String(s) => emitter.str(s),
+            Value::Array(items) => {
+                emitter.open('[', items.len())?;
+                for (i, item) in items.iter().enumerate() {
+                    emitter.element(i)?;
+                    item.emit(emitter)?;
+                }
+                emitter.close(']', items.len())
+            }
+            Value::Map(entries) => {
+                emitter.open('{', entries.len())?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    emitter.element(i)?;
+                    k.emit(emitter)?;
+                    emitter.key_separator()?;
+                    v.emit(emitter)?;
+                }
+                emitter.close('}', entries.len())
+            }
+        }
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        struct ValueVisitor;
-        impl<'de> Visitor<'de> for ValueVisitor {
-            type Value = Value;
-            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-                f.write_str("expect valid messagepack")
-            }
+        ValueSeed(None).deserialize(deserializer)
+    }
+}
 
-            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::Bool(v))
-            }
+/// Deserialize a [`Value`], decoding ext through `registry` when present.
+#[derive(Clone, Copy)]
+pub(super) struct ValueSeed<'r>(pub(super) Option<&'r ExtensionRegistry>);
 
-            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
 
-            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor(self.0))
+    }
+}
 
-            fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
-            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
+struct ValueVisitor<'r>(Option<&'r ExtensionRegistry>);
 
-            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
+impl<'de> Visitor<'de> for ValueVisitor<'_> {
+    type Value = Value;
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("expect valid messagepack")
+    }
 
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::from(v))
-            }
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Bool(v))
+    }
 
-            fn visit_none<E>(self) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::Nil)
-            }
-            fn visit_unit<E>(self) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::Nil)
-            }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::Bin(v.to_vec()))
-            }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
 
-            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
-            where
-                E: serde::de::Error,
-            {
-                Ok(Value::Bin(v))
-            }
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
 
-            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                let ext = crate::extension::ext_owned::deserialize(deserializer)?;
-                Ok(Value::Extension(ext))
-            }
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'de>,
-            {
-                let mut buf =
-                    Vec::with_capacity(super::cautiously_size_hint::<Value>(seq.size_hint()));
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::from(v))
+    }
 
-                while let Some(v) = seq.next_element::<Value>()? {
-                    buf.push(v);
-                }
-                Ok(Value::Array(buf))
-            }
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Nil)
+    }
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Nil)
+    }
 
-            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::MapAccess<'de>,
-            {
-                let mut buf = Vec::with_capacity(super::cautiously_size_hint::<(Value, Value)>(
-                    map.size_hint(),
-                ));
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Bin(v.to_vec()))
+    }
 
-                while let Some(v) = map.next_entry::<Value, Value>()? {
-                    buf.push(v);
-                }
-                Ok(Value::Map(buf))
-            }
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Bin(v))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let ext = crate::extension::ext_owned::deserialize(deserializer)?;
+        match self.0 {
+            Some(registry) => registry.decode(ext).map_err(serde::de::Error::custom),
+            None => Ok(Value::Extension(ext)),
         }
-        deserializer.deserialize_any(ValueVisitor)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut buf = Vec::with_capacity(super::cautiously_size_hint::<Value>(seq.size_hint()));
+
+        while let Some(v) = seq.next_element_seed(ValueSeed(self.0))? {
+            buf.push(v);
+        }
+        Ok(Value::Array(buf))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut buf = Vec::with_capacity(super::cautiously_size_hint::<(Value, Value)>(
+            map.size_hint(),
+        ));
+
+        while let Some(k) = map.next_key_seed(ValueSeed(self.0))? {
+            let v = map.next_value_seed(ValueSeed(self.0))?;
+            buf.push((k, v));
+        }
+        Ok(Value::Map(buf))
     }
 }
 
@@ -353,6 +548,30 @@ impl From<ExtensionOwned> for Value {
     }
 }
 
+impl From<Timestamp32> for Value {
+    fn from(v: Timestamp32) -> Self {
+        Value::Extension(FixedExtension::from(v).into())
+    }
+}
+
+impl From<Timestamp64> for Value {
+    fn from(v: Timestamp64) -> Self {
+        Value::Extension(FixedExtension::from(v).into())
+    }
+}
+
+impl From<Timestamp96> for Value {
+    fn from(v: Timestamp96) -> Self {
+        Value::Extension(FixedExtension::from(v).into())
+    }
+}
+
+impl From<Timestamp> for Value {
+    fn from(v: Timestamp) -> Self {
+        Value::Extension(FixedExtension::from(v).into())
+    }
+}
+
 impl From<ValueRef<'_>> for Value {
     fn from(v: ValueRef<'_>) -> Self {
         match v {
@@ -377,6 +596,7 @@ impl From<ValueRef<'_>> for Value {
 mod tests {
     use super::*;
     use crate::{from_slice, to_slice};
+    use rstest::rstest;
 
     #[test]
     fn owned_roundtrip_primitives() {
@@ -414,6 +634,129 @@ mod tests {
         let decoded = from_slice::<Value>(&buf[..len]).unwrap();
         assert_eq!(decoded, v);
     }
+
+    #[rstest]
+    #[case(Timestamp32::new(123).into(), 123, 0)]
+    #[case(Timestamp64::new(456, 789).unwrap().into(), 456, 789)]
+    #[case(Timestamp96::new(-1, 5).unwrap().into(), -1, 5)]
+    #[case(Timestamp::new(1 << 40, 5).unwrap().into(), 1 << 40, 5)]
+    fn timestamp_roundtrip_keeps_layout(
+        #[case] v: Value,
+        #[case] seconds: i64,
+        #[case] nanos: u32,
+    ) {
+        let mut buf = [0u8; 32];
+        let len = to_slice(&v, &mut buf).unwrap();
+        let decoded = from_slice::<Value>(&buf[..len]).unwrap();
+        assert_eq!(decoded, v);
+
+        let ts = decoded.as_timestamp().unwrap();
+        assert_eq!((ts.seconds(), ts.nanos()), (seconds, nanos));
+
+        let mut again = [0u8; 32];
+        let again_len = to_slice(&decoded, &mut again).unwrap();
+        assert_eq!(&again[..again_len], &buf[..len]);
+    }
+
+    #[rstest]
+    #[case(Value::Extension(ExtensionOwned { r#type: 1, data: vec![0; 4] }))]
+    #[case(Value::Extension(ExtensionOwned { r#type: -1, data: vec![0; 5] }))]
+    // nanoseconds out of range
+    #[case(Value::Extension(ExtensionOwned { r#type: -1, data: vec![0xff; 12] }))]
+    #[case(Value::Bin(vec![0; 4]))]
+    fn non_timestamp_is_none(#[case] v: Value) {
+        assert_eq!(v.as_timestamp(), None);
+    }
+
+    #[test]
+    fn timestamp96_field_from_value() {
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            #[serde(with = "crate::extension::timestamp96")]
+            at: Timestamp96,
+        }
+
+        let at = Timestamp96::new(-7, 42).unwrap();
+        let v = Value::Map(vec![(Value::from("at"), Value::from(at))]);
+        assert_eq!(Event::deserialize(&v).unwrap(), Event { at });
+        assert_eq!(Event::deserialize(v).unwrap(), Event { at });
+    }
+
+    #[test]
+    fn usable_as_set_element() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let values = [
+            Value::Number(Number::Float(f64::NAN)),
+            Value::from("a"),
+            Value::Number(Number::Float(f64::NAN)),
+            Value::Nil,
+            Value::from(1u8),
+            Value::from("a"),
+            Value::Number(Number::Float(1.0)),
+        ];
+
+        let hashed = values.iter().collect::<HashSet<_>>();
+        assert_eq!(hashed.len(), 5);
+
+        let ordered = values.iter().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(
+            ordered.into_iter().collect::<Vec<_>>(),
+            [
+                Value::Nil,
+                Value::from(1u8),
+                Value::Number(Number::Float(1.0)),
+                Value::Number(Number::Float(f64::NAN)),
+                Value::from("a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_and_take() {
+        let mut v = Value::Nil;
+        assert_eq!(v.insert("a", 1u8), None);
+        assert_eq!(v.insert(2u8, "b"), None);
+        assert_eq!(v.insert("a", 3u8), Some(Value::from(1u8)));
+        assert_eq!(
+            v.keys().collect::<Vec<_>>(),
+            [&Value::from("a"), &Value::from(2u8)]
+        );
+
+        let taken = v.get_mut("a").unwrap().take();
+        assert_eq!(taken, Value::from(3u8));
+        assert_eq!(v["a"], Value::Nil);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_into_array_panics() {
+        Value::Array(vec![]).insert("a", 1u8);
+    }
+
+    #[test]
+    fn as_mut_accessors() {
+        let mut v = Value::Array(vec![Value::from("x")]);
+        v.as_array_mut().unwrap().push(Value::Nil);
+        v.get_mut(0).unwrap().as_string_mut().unwrap().push('z');
+        assert_eq!(v, Value::Array(vec![Value::from("xz"), Value::Nil]));
+        assert!(v.as_map_mut().is_none());
+    }
+
+    #[test]
+    fn display_uses_notation() {
+        let v = crate::msgpack!({"a": [1, -1, 0.5, b"\x01"], "t": ext(-1, [0, 0, 0, 1])});
+        assert_eq!(
+            alloc::format!("{v}"),
+            r#"{"a": [1, -1, 0.5, h'01'], "t": timestamp32(1)}"#
+        );
+
+        let buf = crate::to_vec(&v).unwrap();
+        let r = crate::from_slice::<ValueRef<'_>>(&buf).unwrap();
+        assert_eq!(alloc::format!("{r:#}"), alloc::format!("{v:#}"));
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
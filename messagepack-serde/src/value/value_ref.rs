@@ -1,6 +1,8 @@
 use super::number::Number;
 use alloc::vec::Vec;
 use messagepack_core::extension::ExtensionRef;
+use messagepack_core::notation::{Emitter, Style};
+use messagepack_core::timestamp::Timestamp96;
 use serde::{de::Visitor, ser::SerializeMap};
 
 /// Represents any messagepack value.
@@ -8,7 +10,9 @@ use serde::{de::Visitor, ser::SerializeMap};
 /// ## Note
 ///
 /// Since [ValueRef] internally uses [alloc::vec::Vec], cloning may be computationally expensive.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+///
+/// Equality, ordering and hashing follow the same rules as [`Value`](super::Value).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ValueRef<'a> {
     /// Represents nil format
     Nil,
@@ -28,7 +32,7 @@ pub enum ValueRef<'a> {
     Map(Vec<(ValueRef<'a>, ValueRef<'a>)>),
 }
 
-impl ValueRef<'_> {
+impl<'a> ValueRef<'a> {
     /// Returns true if the `ValueRef` is nil
     pub fn is_nil(&self) -> bool {
         matches!(self, ValueRef::Nil)
@@ -58,6 +62,28 @@ impl ValueRef<'_> {
         }
     }
 
+    /// Index into an array or map. See [`Value::get`](super::Value::get).
+    pub fn get<I: super::Index>(&self, index: I) -> Option<&ValueRef<'a>> {
+        index.index_into_ref(self)
+    }
+
+    /// Iterate over the keys of a map, in order. Yields nothing for non-map values.
+    pub fn keys(&self) -> impl Iterator<Item = &ValueRef<'a>> {
+        let entries: &[(ValueRef<'a>, ValueRef<'a>)] = match self {
+            ValueRef::Map(entries) => entries,
+            _ => &[],
+        };
+        entries.iter().map(|(k, _)| k)
+    }
+
+    /// If the `ValueRef` is a timestamp ext, returns it as [`Timestamp96`].
+    ///
+    /// All of the timestamp 32, 64 and 96 layouts are recognized.
+    pub fn as_timestamp(&self) -> Option<Timestamp96> {
+        self.as_extension()
+            .and_then(|ext| super::timestamp_from_extension(*ext))
+    }
+
     /// If the `ValueRef` is number, returns contained value.
     pub fn as_number(&self) -> Option<Number> {
         match self {
@@ -117,6 +143,48 @@ impl serde::Serialize for ValueRef<'_> {
     }
 }
 
+/// Formats the value in the [notation](messagepack_core::notation). Use
+/// `{:#}` for the pretty printed layout.
+impl core::fmt::Display for ValueRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let style = Style::of(f);
+        self.emit(&mut Emitter::new(f, style))
+    }
+}
+
+impl ValueRef<'_> {
+    fn emit<W: core::fmt::Write>(&self, emitter: &mut Emitter<'_, W>) -> core::fmt::Result {
+        match self {
+            ValueRef::Nil => emitter.nil(),
+            ValueRef::Bool(b) => emitter.bool(*b),
+            ValueRef::Bin(b) => emitter.bin(b),
+            ValueRef::Extension(ext) => emitter.ext(*ext),
+            ValueRef::Number(Number::PositiveInt(n)) => emitter.uint(*n),
+            ValueRef::Number(Number::NegativeInt(n)) => emitter.int(*n),
+            ValueRef::Number(Number::Float(n)) => emitter.f64(*n),
+            ValueRef::String(s) => emitter.str(s),
+            ValueRef::Array(items) => {
+                emitter.open('[', items.len())?;
+                for (i, item) in items.iter().enumerate() {
+                    emitter.element(i)?;
+                    item.emit(emitter)?;
+                }
+                emitter.close(']', items.len())
+            }
+            ValueRef::Map(entries) => {
+                emitter.open('{', entries.len())?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    emitter.element(i)?;
+                    k.emit(emitter)?;
+                    emitter.key_separator()?;
+                    v.emit(emitter)?;
+                }
+                emitter.close('}', entries.len())
+            }
+        }
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for ValueRef<'de> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -381,6 +449,25 @@ mod tests {
         assert_eq!(buf[..len], expected);
     }
 
+    #[rstest]
+    // timestamp 32
+    #[case(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x7b], Some((123, 0)))]
+    // timestamp 64: 1 nanosecond, 2 seconds
+    #[case(&[0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02], Some((2, 1)))]
+    // timestamp 96: 3 nanoseconds, -1 seconds
+    #[case(
+        &[0xc7, 0x0c, 0xff, 0, 0, 0, 3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        Some((-1, 3))
+    )]
+    // other ext type
+    #[case(&[0xd6, 0x01, 0x00, 0x00, 0x00, 0x7b], None)]
+    #[case(&[0x05], None)]
+    fn value_ref_as_timestamp(#[case] buf: &[u8], #[case] expected: Option<(i64, u32)>) {
+        let v = from_slice::<ValueRef<'_>>(buf).unwrap();
+        let ts = v.as_timestamp().map(|ts| (ts.seconds(), ts.nanos()));
+        assert_eq!(ts, expected);
+    }
+
     // Verify deserialization of ValueRef scalars and simple composites.
     #[rstest]
     #[case(&[0xc0], ValueRef::Nil)]